@@ -1,30 +1,246 @@
 use crate::character::stats;
 use crate::character::skill;
+use crate::character::concentration::ConcentrationSave;
+use crate::character::condition::{exhaustion_modifiers, Condition, MAX_EXHAUSTION};
+use crate::character::description::Description;
+use crate::character::dice::{roll_d20, RollMode};
+use crate::character::effect::{ActiveEffect, Breakdown, Contribution, Duration, Modifier, ModifierKind, Target, ROUNDS_PER_HOUR};
+use crate::character::experience::{experience_for_level, level_for_experience};
+use crate::character::feature::{Feature, FeatureError, FeatureSource};
+use crate::character::item::{Item, ItemError, MAX_ATTUNED_ITEMS};
+use crate::character::migration::CURRENT_SCHEMA_VERSION;
+use crate::character::proficiencies::Proficiencies;
+use crate::character::recharge::RechargeOn;
+use crate::character::spell_slot::{ordinal, SpellSlotError, SpellSlots};
+use crate::character::validation::proficiency_bonus_for_level;
 
 use std::collections::HashMap;
+use std::convert::TryFrom;
 
-#[derive(Deserialize, Serialize)]
+use schemars::JsonSchema;
+
+fn current_schema_version() -> u32 { CURRENT_SCHEMA_VERSION }
+
+#[derive(Deserialize, Serialize, JsonSchema)]
 pub struct Character {
+    /// Lets editors find the JSON Schema for the file, e.g. "./character.schema.json".
+    #[serde(rename = "$schema", default, skip_serializing_if = "Option::is_none")]
+    json_schema: Option<String>,
+
+    /// Older files are migrated before they are deserialized, see `migration.rs`.
+    #[serde(default = "current_schema_version")]
+    schema_version: u32,
+
     name: String,
     race: String,
     class: String,
     level: u16,
 
+    #[serde(default)]
+    experience_points: u32,
+    /// The campaign levels characters up at story milestones, so experience
+    /// points are not tracked.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    milestone: bool,
+
     current_hitpoints: i16,
     max_hitpoints: u16,
 
+    #[schemars(schema_with = "crate::character::schema::stats_schema")]
     stats: HashMap<stats::Stat, i16>,
 
+    /// Every 4d6 roll made while generating the ability scores, rerolls
+    /// included, so the table can see how the scores came about.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    ability_rolls: Vec<stats::AbilityRoll>,
+
+    /// The abilities whose saving throws add the proficiency bonus.
+    #[serde(default)]
+    saving_throws: Vec<stats::Stat>,
+
+    /// The ability spells are cast with, e.g. Intellect for a wizard.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    spellcasting_ability: Option<stats::Stat>,
+
+    /// Armor class from the armor and shield worn, before effects like Mage
+    /// Armor or a Ring of Protection. Initiative, speed and the rest are
+    /// derived from it and the other base values, see `breakdown`.
     armor_class: u16,
-    initiative: u16,
     proficiency_bonus: u16,
     walking_speed_in_ft: u16,
 
-    skills: [skill::Skill; 17]
+    /// Every skill exactly once, in the order of the paper sheet.
+    skills: Vec<skill::Skill>,
+
+    #[serde(default, skip_serializing_if = "Proficiencies::is_empty")]
+    proficiencies: Proficiencies,
+
+    #[serde(default)]
+    inventory: Vec<Item>,
+
+    #[serde(default)]
+    features: Vec<Feature>,
+
+    #[serde(default)]
+    spell_slots: Vec<SpellSlots>,
+
+    /// The spells the character knows or has in their spellbook, as IDs of
+    /// the content packs, e.g. "spell:fire-bolt".
+    #[serde(default)]
+    spells: Vec<String>,
+
+    /// The IDs of the spells prepared for the day, for classes that prepare
+    /// their spells. Cantrips are always ready and never listed.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    prepared_spells: Vec<String>,
+
+    /// The ID of the spell the character is concentrating on. Casting another
+    /// concentration spell ends it, and so can taking damage.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    concentration: Option<String>,
+
+    /// Spells and other effects that last a while, e.g. Mage Armor.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    effects: Vec<ActiveEffect>,
+
+    #[serde(default)]
+    description: Description,
+
+    /// Path to an image or ASCII art file, relative to the character file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    portrait: Option<String>,
+
+    #[serde(default)]
+    conditions: Vec<Condition>,
+
+    #[serde(default)]
+    exhaustion: u8
+}
+
+/// The choices a new level 1 character starts from. Everything else on the
+/// sheet, like skill bonuses, hit points and initiative, is derived from them.
+pub struct NewCharacter {
+    pub name: String,
+    pub race: String,
+    pub class: String,
+    pub background: String,
+    /// Final ability scores, racial bonuses included.
+    pub stats: HashMap<stats::Stat, i16>,
+    pub ability_rolls: Vec<stats::AbilityRoll>,
+    /// Level 1 hit points before the Constitution modifier: the hit die's
+    /// maximum plus bonuses such as Dwarven Toughness.
+    pub hitpoints: u16,
+    pub walking_speed_in_ft: u16,
+    pub saving_throws: Vec<stats::Stat>,
+    pub skill_proficiencies: Vec<String>,
+    /// Armor, weapons, tools and languages.
+    pub proficiencies: Proficiencies,
+    pub spellcasting_ability: Option<stats::Stat>,
+    pub spell_slots: Vec<SpellSlots>,
+    pub spells: Vec<String>,
+    pub features: Vec<Feature>,
+    pub inventory: Vec<Item>
+}
+
+/// What one new level brings, worked out from the class progression.
+#[derive(Default)]
+pub struct LevelAdvance {
+    /// Added to the hit point maximum, Constitution modifier included.
+    pub hitpoints: u16,
+    /// One entry per +1 from an Ability Score Improvement, e.g. `[Strength, Strength]`.
+    pub ability_increases: Vec<stats::Stat>,
+    /// New class features and feats.
+    pub features: Vec<Feature>,
+    /// Set for classes that start casting after level 1, e.g. paladins at level 2.
+    pub spellcasting_ability: Option<stats::Stat>,
+    /// Slots per spell level at the new level, replacing the current ones.
+    pub spell_slots: Option<Vec<(u8, u16)>>,
+    /// Cantrips and spells learned, or copied into a spellbook.
+    pub spells: Vec<String>
 }
 
 impl Character {
 
+    /// A level 1 character with every ability at 10 and the standard skills,
+    /// ready to be filled in.
+    pub fn new(name: &str, race: &str, class: &str, max_hitpoints: u16) -> Character {
+        Character::create(NewCharacter {
+            name: name.to_owned(),
+            race: race.to_owned(),
+            class: class.to_owned(),
+            background: String::new(),
+            stats: stats::Stat::ALL.iter().map(|stat| (*stat, 10)).collect(),
+            ability_rolls: Vec::new(),
+            hitpoints: max_hitpoints,
+            walking_speed_in_ft: 30,
+            saving_throws: Vec::new(),
+            skill_proficiencies: Vec::new(),
+            proficiencies: Proficiencies::default(),
+            spellcasting_ability: None,
+            spell_slots: Vec::new(),
+            spells: Vec::new(),
+            features: Vec::new(),
+            inventory: Vec::new()
+        })
+    }
+
+    /// A level 1 character built from `new`. Armor class assumes no armor
+    /// (10 + Dexterity modifier), since armor is not worn yet.
+    pub fn create(new: NewCharacter) -> Character {
+        let modifier = |stat: stats::Stat| new.stats.get(&stat).copied().map(stats::modifier).unwrap_or(0);
+        let proficiency_bonus = proficiency_bonus_for_level(1);
+
+        let skills = skill::STANDARD_SKILLS.iter()
+            .map(|(name, stat)| {
+                let has_proficiency = new.skill_proficiencies.iter().any(|skill| skill.eq_ignore_ascii_case(name));
+                let bonus = modifier(*stat) + if has_proficiency { proficiency_bonus as i16 } else { 0 };
+                skill::Skill { has_proficiency, stat: *stat, name: (*name).to_owned(), bonus }
+            })
+            .collect();
+
+        let hitpoints = i16::try_from(new.hitpoints).unwrap_or(i16::MAX);
+        let max_hitpoints = hitpoints.saturating_add(modifier(stats::Stat::Constitution)).max(1) as u16;
+        let dexterity = modifier(stats::Stat::Dexterity);
+
+        Character {
+            json_schema: None,
+            schema_version: CURRENT_SCHEMA_VERSION,
+            name: new.name,
+            race: new.race,
+            class: new.class,
+            level: 1,
+            experience_points: 0,
+            milestone: false,
+            current_hitpoints: max_hitpoints as i16,
+            max_hitpoints,
+            armor_class: (10 + dexterity).max(0) as u16,
+            stats: new.stats,
+            ability_rolls: new.ability_rolls,
+            saving_throws: new.saving_throws,
+            spellcasting_ability: new.spellcasting_ability,
+            proficiency_bonus,
+            walking_speed_in_ft: new.walking_speed_in_ft,
+            skills,
+            proficiencies: new.proficiencies,
+            inventory: new.inventory,
+            features: new.features,
+            spell_slots: new.spell_slots,
+            spells: new.spells,
+            prepared_spells: Vec::new(),
+            concentration: None,
+            effects: Vec::new(),
+            description: Description { background: new.background, ..Description::default() },
+            portrait: None,
+            conditions: Vec::new(),
+            exhaustion: 0
+        }
+    }
+
+    pub fn get_schema_version(&self) -> &u32 { &self.schema_version }
+
+    /// The `$schema` reference only makes sense in JSON files.
+    pub fn set_json_schema(&mut self, json_schema: Option<String>) { self.json_schema = json_schema; }
+
     pub fn get_name(&self) -> &str { &self.name }
 
     pub fn get_race(&self) -> &str { &self.race }
@@ -33,15 +249,1036 @@ impl Character {
 
     pub fn get_level(&self) -> &u16 { &self.level }
 
+    pub fn get_experience_points(&self) -> &u32 { &self.experience_points }
+
+    pub fn uses_milestones(&self) -> bool { self.milestone }
+
+    /// The experience points the next level starts at. `None` at level 20
+    /// and with milestone levelling.
+    pub fn get_experience_for_next_level(&self) -> Option<u32> {
+        if self.milestone {
+            return None;
+        }
+        experience_for_level(self.level + 1)
+    }
+
+    /// Whether the experience points are enough for a level not taken yet.
+    pub fn can_level_up(&self) -> bool {
+        !self.milestone && level_for_experience(self.experience_points) > self.level
+    }
+
+    /// Adds experience points and says when they are enough for a new level.
+    pub fn award_experience(&mut self, amount: u32) -> Result<Vec<String>, String> {
+        if self.milestone {
+            return Err(format!("{} levels up at milestones and does not track experience", self.name));
+        }
+
+        let before = self.experience_points;
+        self.experience_points = self.experience_points.saturating_add(amount);
+        let mut log = vec![format!("Gained {} XP, {} -> {}", amount, before, self.experience_points)];
+
+        let level = level_for_experience(self.experience_points);
+        if level > self.level {
+            log.push(format!("Enough experience for level {}: time to level up!", level));
+        }
+
+        Ok(log)
+    }
+
+    /// Switches between milestone levelling and experience points. Experience
+    /// points gained so far are kept for when the campaign switches back.
+    pub fn set_milestone(&mut self, milestone: bool) -> Vec<String> {
+        if self.milestone == milestone {
+            return Vec::new();
+        }
+
+        self.milestone = milestone;
+        if milestone {
+            vec![format!("{} now levels up at milestones", self.name)]
+        } else {
+            vec![format!("{} now levels up with experience points ({} XP)", self.name, self.experience_points)]
+        }
+    }
+
     pub fn get_current_hitpoints(&self) -> &i16 { &self.current_hitpoints }
 
     pub fn get_max_hitpoints(&self) -> &u16 { &self.max_hitpoints }
 
-    pub fn get_armor_class(&self) -> &u16 { &self.armor_class }
+    pub fn get_armor_class(&self) -> u16 { self.breakdown(&Target::ArmorClass).total.max(0) as u16 }
 
-    pub fn get_initiative(&self) -> &u16 { &self.initiative }
+    pub fn get_initiative(&self) -> i16 { self.breakdown(&Target::Initiative).total }
 
     pub fn get_proficiency_bonus(&self) -> &u16 { &self.proficiency_bonus }
 
     pub fn get_walking_speed_in_ft(&self) -> &u16 { &self.walking_speed_in_ft }
-}
\ No newline at end of file
+
+    pub fn get_inventory(&self) -> &[Item] { &self.inventory }
+
+    pub fn get_attuned_count(&self) -> usize {
+        self.inventory.iter().filter(|item| item.attuned).count()
+    }
+
+    fn find_item_mut(&mut self, name: &str) -> Result<&mut Item, ItemError> {
+        self.inventory.iter_mut()
+            .find(|item| item.name.eq_ignore_ascii_case(name))
+            .ok_or_else(|| ItemError::NoSuchItem(name.to_owned()))
+    }
+
+    pub fn attune(&mut self, name: &str) -> Result<(), ItemError> {
+        let attuned_count = self.get_attuned_count();
+        let item = self.find_item_mut(name)?;
+
+        if !item.requires_attunement {
+            return Err(ItemError::AttunementNotRequired(item.name.clone()));
+        }
+        if item.attuned {
+            return Ok(());
+        }
+        if attuned_count >= MAX_ATTUNED_ITEMS {
+            return Err(ItemError::AttunementLimitReached);
+        }
+
+        item.attuned = true;
+        Ok(())
+    }
+
+    pub fn end_attunement(&mut self, name: &str) -> Result<(), ItemError> {
+        self.find_item_mut(name)?.attuned = false;
+        Ok(())
+    }
+
+    /// Spends `amount` charges of an item, e.g. a wand, returning what is left.
+    pub fn use_charges(&mut self, name: &str, amount: u16) -> Result<u16, ItemError> {
+        let item = self.find_item_mut(name)?;
+        let item_name = item.name.clone();
+
+        let charges = item.charges.as_mut()
+            .ok_or_else(|| ItemError::NoCharges(item_name.clone()))?;
+
+        if charges.current < amount {
+            return Err(ItemError::NotEnoughCharges { item: item_name, remaining: charges.current });
+        }
+
+        charges.current -= amount;
+        Ok(charges.current)
+    }
+
+    pub fn get_features(&self) -> &[Feature] { &self.features }
+
+    pub fn get_description(&self) -> &Description { &self.description }
+
+    pub fn get_proficiencies(&self) -> &Proficiencies { &self.proficiencies }
+
+    pub fn get_spell_slots(&self) -> &[SpellSlots] { &self.spell_slots }
+
+    pub fn get_spells(&self) -> &[String] { &self.spells }
+
+    pub fn get_prepared_spells(&self) -> &[String] { &self.prepared_spells }
+
+    pub fn is_prepared(&self, id: &str) -> bool {
+        self.prepared_spells.iter().any(|prepared| prepared.eq_ignore_ascii_case(id))
+    }
+
+    /// Prepares or unprepares the spell with ID `id`. Preparing fails once
+    /// `limit` spells are prepared.
+    pub fn set_prepared(&mut self, id: &str, prepared: bool, limit: usize) -> Result<(), String> {
+        let index = self.prepared_spells.iter().position(|known| known.eq_ignore_ascii_case(id));
+
+        match (prepared, index) {
+            (true, None) if self.prepared_spells.len() >= limit =>
+                return Err(format!("{} can prepare {} spells; unprepare one first", self.name, limit)),
+            (true, None) => self.prepared_spells.push(id.to_owned()),
+            (false, Some(index)) => { self.prepared_spells.remove(index); },
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Adds a spell ID to the spells the character knows, e.g. from the compendium.
+    pub fn add_spell(&mut self, id: &str) -> Result<(), String> {
+        if self.spells.iter().any(|known| known.eq_ignore_ascii_case(id)) {
+            return Err(format!("{} already knows {}", self.name, id));
+        }
+
+        self.spells.push(id.to_owned());
+        Ok(())
+    }
+
+    fn find_spell_slots_mut(&mut self, level: u8) -> Result<&mut SpellSlots, SpellSlotError> {
+        self.spell_slots.iter_mut()
+            .find(|slots| slots.level == level && slots.max > 0)
+            .ok_or(SpellSlotError::NoSlotsOfLevel(level))
+    }
+
+    /// Spends a spell slot of `level`, returning how many of that level are left.
+    pub fn use_spell_slot(&mut self, level: u8) -> Result<u16, SpellSlotError> {
+        let slots = self.find_spell_slots_mut(level)?;
+
+        if slots.current == 0 {
+            return Err(SpellSlotError::NoSlotsLeft(level));
+        }
+
+        slots.current -= 1;
+        Ok(slots.current)
+    }
+
+    /// Spends the lowest spell slot of `min_level` or higher, for casting a
+    /// spell of that level. Returns the level used and how many of it are left.
+    pub fn use_lowest_spell_slot(&mut self, min_level: u8) -> Result<(u8, u16), SpellSlotError> {
+        let mut usable: Vec<&SpellSlots> = self.spell_slots.iter()
+            .filter(|slots| slots.level >= min_level && slots.max > 0)
+            .collect();
+        if usable.is_empty() {
+            return Err(SpellSlotError::NoSlotsOfLevel(min_level));
+        }
+        usable.sort_by_key(|slots| slots.level);
+
+        let level = usable.iter()
+            .find(|slots| slots.current > 0)
+            .map(|slots| slots.level)
+            .ok_or(SpellSlotError::NoSlotsLeft(min_level))?;
+
+        self.use_spell_slot(level).map(|remaining| (level, remaining))
+    }
+
+    /// Gives back a spell slot of `level`, e.g. from Arcane Recovery or to undo a misclick.
+    pub fn restore_spell_slot(&mut self, level: u8) -> Result<u16, SpellSlotError> {
+        let slots = self.find_spell_slots_mut(level)?;
+
+        if slots.current >= slots.max {
+            return Err(SpellSlotError::AllSlotsLeft(level));
+        }
+
+        slots.current += 1;
+        Ok(slots.current)
+    }
+
+    pub fn get_portrait(&self) -> Option<&str> { self.portrait.as_deref() }
+
+    pub fn get_skills(&self) -> &[skill::Skill] { &self.skills }
+
+    pub fn get_ability_rolls(&self) -> &[stats::AbilityRoll] { &self.ability_rolls }
+
+    /// The ability score after effects, e.g. 19 Strength from Gauntlets of Ogre Power.
+    pub fn get_stat(&self, stat: &stats::Stat) -> Option<i16> {
+        self.stats.get(stat)?;
+        Some(self.breakdown(&Target::AbilityScore(*stat)).total)
+    }
+
+    /// The ability score on the sheet, before effects.
+    pub fn get_base_stat(&self, stat: &stats::Stat) -> Option<i16> { self.stats.get(stat).copied() }
+
+    pub fn get_modifier(&self, stat: &stats::Stat) -> Option<i16> {
+        self.get_stat(stat).map(stats::modifier)
+    }
+
+    pub fn has_saving_throw_proficiency(&self, stat: &stats::Stat) -> bool {
+        self.saving_throws.contains(stat)
+    }
+
+    pub fn get_saving_throw_bonus(&self, stat: &stats::Stat) -> Option<i16> {
+        self.stats.get(stat)?;
+        Some(self.breakdown(&Target::SavingThrow(*stat)).total)
+    }
+
+    pub fn get_spellcasting_ability(&self) -> Option<stats::Stat> { self.spellcasting_ability }
+
+    /// Spell attack bonus: proficiency bonus + spellcasting ability modifier.
+    pub fn get_spell_attack_bonus(&self) -> Option<i16> {
+        self.get_stat(&self.spellcasting_ability?)?;
+        Some(self.breakdown(&Target::SpellAttack).total)
+    }
+
+    /// Spell save DC: 8 + proficiency bonus + spellcasting ability modifier.
+    pub fn get_spell_save_dc(&self) -> Option<i16> {
+        self.get_stat(&self.spellcasting_ability?)?;
+        Some(self.breakdown(&Target::SpellSaveDc).total)
+    }
+
+    /// The bonus on the sheet, plus what effects on the ability or the skill change.
+    pub fn get_skill_bonus(&self, skill: &skill::Skill) -> i16 {
+        self.breakdown(&Target::Skill(skill.name.clone())).total
+    }
+
+    /// Roll mode for one skill: every ability check, plus effects on the skill.
+    pub fn skill_check_mode(&self, skill: &skill::Skill) -> RollMode {
+        self.breakdown(&Target::Skill(skill.name.clone())).mode
+    }
+
+    /// Darkvision range in feet from features like a race's Darkvision; 0 without it.
+    pub fn get_darkvision(&self) -> u16 {
+        self.breakdown(&Target::Darkvision).total.max(0) as u16
+    }
+
+    /// Saving throw modifiers that only apply against something, e.g.
+    /// "Advantage on INT WIS CHA against magic" for Gnome Cunning.
+    pub fn conditional_save_notes(&self) -> Vec<String> {
+        let mut notes: Vec<(String, String, Vec<&str>)> = Vec::new();
+
+        for contribution in self.get_modifiers() {
+            let modifier = &contribution.modifier;
+            let change = match (&modifier.against, modifier.kind) {
+                (None, _) => continue,
+                (Some(_), ModifierKind::Advantage) => "Advantage".to_owned(),
+                (Some(_), ModifierKind::Disadvantage) => "Disadvantage".to_owned(),
+                (Some(_), ModifierKind::Add) => format!("{:+}", modifier.value),
+                _ => continue
+            };
+            let against = modifier.against.clone().unwrap_or_default();
+            let stats = stats::Stat::ALL.iter()
+                .filter(|stat| modifier.target.covers(&Target::SavingThrow(**stat)))
+                .map(|stat| stat.abbreviation());
+
+            match notes.iter_mut().find(|note| note.0 == change && note.1 == against) {
+                Some(note) => note.2.extend(stats),
+                None => notes.push((change, against, stats.collect()))
+            }
+        }
+
+        notes.into_iter()
+            .filter(|(_, _, stats)| !stats.is_empty())
+            .map(|(change, against, stats)| format!("{} on {} against {}", change, stats.join(" "), against))
+            .collect()
+    }
+
+    /// 10 + the skill bonus, +5 with advantage and -5 with disadvantage on the check.
+    pub fn get_passive_score(&self, skill_name: &str) -> Option<i16> {
+        let skill = self.find_skill(skill_name)?;
+        let adjustment = match self.skill_check_mode(skill) {
+            RollMode::Normal => 0,
+            RollMode::Advantage => 5,
+            RollMode::Disadvantage => -5
+        };
+
+        Some(10 + self.get_skill_bonus(skill) + adjustment)
+    }
+
+    /// Looks a skill up by name, ignoring case, e.g. "sleight of hand".
+    pub fn find_skill(&self, name: &str) -> Option<&skill::Skill> {
+        self.skills.iter().find(|skill| skill.name.eq_ignore_ascii_case(name))
+    }
+
+    // ####### CONCENTRATION ########
+    pub fn get_concentration(&self) -> Option<&str> { self.concentration.as_deref() }
+
+    /// Starts concentrating on the spell with ID `spell`. Returns the spell
+    /// whose concentration this ends, if it is another one.
+    pub fn start_concentration(&mut self, spell: &str) -> Option<String> {
+        let previous = self.concentration.replace(spell.to_owned())
+            .filter(|previous| !previous.eq_ignore_ascii_case(spell));
+
+        if let Some(previous) = &previous {
+            self.end_concentration_effects(previous);
+        }
+        previous
+    }
+
+    /// Stops concentrating, which ends the effects of the spell.
+    pub fn end_concentration(&mut self) -> Option<String> {
+        let spell = self.concentration.take()?;
+        self.end_concentration_effects(&spell);
+        Some(spell)
+    }
+
+    /// Rolls the Constitution save against `dc` to keep concentrating, which
+    /// ends on a failure. `None` if the character is not concentrating.
+    pub fn roll_concentration_save(&mut self, dc: u16) -> Option<ConcentrationSave> {
+        let spell = self.concentration.clone()?;
+        let bonus = self.get_saving_throw_bonus(&stats::Stat::Constitution).unwrap_or(0);
+        let (kept_die, rolls) = roll_d20(self.saving_throw_mode(&stats::Stat::Constitution));
+        let total = kept_die + bonus;
+        let kept = total >= dc as i16;
+
+        if !kept {
+            self.end_concentration();
+        }
+
+        Some(ConcentrationSave { spell, dc, rolls, bonus, total, kept })
+    }
+    // ####### END CONCENTRATION ########
+
+    // ####### HIT POINTS ########
+    /// Hit points cannot go below 0; a character at 0 falls unconscious.
+    pub fn take_damage(&mut self, amount: u16) -> Vec<String> {
+        let mut log = Vec::new();
+
+        let before = self.current_hitpoints;
+        let damage = i16::try_from(amount).unwrap_or(i16::MAX);
+        self.current_hitpoints = self.current_hitpoints.saturating_sub(damage).max(0);
+        log.push(format!("Took {} damage, hit points {} -> {}", amount, before, self.current_hitpoints));
+
+        if self.current_hitpoints == 0 && before > 0 && !self.has_condition(Condition::Unconscious) {
+            self.conditions.push(Condition::Unconscious);
+            log.push("Dropped to 0 hit points and fell unconscious".to_owned());
+        }
+
+        log
+    }
+
+    /// Healing is capped at the effective maximum and wakes an unconscious character.
+    pub fn heal(&mut self, amount: u16) -> Vec<String> {
+        let mut log = Vec::new();
+
+        let before = self.current_hitpoints;
+        let max_hitpoints = self.get_effective_max_hitpoints() as i16;
+        let healing = i16::try_from(amount).unwrap_or(i16::MAX);
+        self.current_hitpoints = self.current_hitpoints.saturating_add(healing).min(max_hitpoints).max(before);
+        log.push(format!("Healed {}, hit points {} -> {}", amount, before, self.current_hitpoints));
+
+        self.regain_consciousness(before, &mut log);
+        log
+    }
+
+    /// Coming back from 0 hit points ends the unconsciousness that dropping to 0 caused.
+    fn regain_consciousness(&mut self, hitpoints_before: i16, log: &mut Vec<String>) {
+        if hitpoints_before <= 0 && self.current_hitpoints > 0 && self.has_condition(Condition::Unconscious) {
+            self.conditions.retain(|condition| *condition != Condition::Unconscious);
+            log.push("Regained consciousness".to_owned());
+        }
+    }
+
+    /// Gains a level and everything `advance` brings with it. The proficiency
+    /// bonus follows the new level, and skills, initiative and hit points
+    /// follow any ability that changes.
+    pub fn level_up(&mut self, advance: LevelAdvance) -> Result<Vec<String>, String> {
+        if self.level >= 20 {
+            return Err(format!("{} is already level {}", self.name, self.level));
+        }
+
+        for stat in &advance.ability_increases {
+            let increase = advance.ability_increases.iter().filter(|other| *other == stat).count() as i16;
+            let score = self.stats.get(stat).copied().unwrap_or(10);
+            if score.saturating_add(increase) > 20 {
+                return Err(format!("{} {} cannot go above 20", stat.abbreviation(), score));
+            }
+        }
+
+        // A higher Constitution modifier adds a hit point per level, so it has
+        // to fit on the sheet together with the hit die.
+        let constitution = self.stats.get(&stats::Stat::Constitution).copied().unwrap_or(10);
+        let constitution_increase = advance.ability_increases.iter().filter(|stat| **stat == stats::Stat::Constitution).count() as i16;
+        let constitution_hitpoints = (stats::modifier(constitution + constitution_increase) - stats::modifier(constitution)) * (self.level + 1) as i16;
+
+        let gain = i16::try_from(advance.hitpoints).ok();
+        let hitpoints = gain
+            .and_then(|gain| Some((i16::try_from(self.max_hitpoints).ok()?.checked_add(gain)?, self.current_hitpoints.checked_add(gain)?)))
+            .filter(|(max, current)| max.checked_add(constitution_hitpoints).is_some() && current.checked_add(constitution_hitpoints).is_some())
+            .ok_or_else(|| format!("{} more hit points would go above the most a sheet holds ({})",
+                i32::from(advance.hitpoints) + i32::from(constitution_hitpoints), i16::MAX))?;
+
+        let mut log = Vec::new();
+
+        self.level += 1;
+        log.push(format!("{} is now level {}", self.name, self.level));
+
+        self.max_hitpoints = hitpoints.0 as u16;
+        self.current_hitpoints = hitpoints.1;
+        log.push(format!("Hit point maximum increased by {} to {}", advance.hitpoints, self.max_hitpoints));
+
+        let proficiency_bonus = proficiency_bonus_for_level(self.level);
+        if proficiency_bonus != self.proficiency_bonus {
+            let increase = proficiency_bonus as i16 - self.proficiency_bonus as i16;
+            for skill in self.skills.iter_mut().filter(|skill| skill.has_proficiency) {
+                skill.bonus += increase;
+            }
+            self.proficiency_bonus = proficiency_bonus;
+            let change = if increase > 0 { "increased" } else { "lowered" };
+            log.push(format!("Proficiency bonus {} to +{}", change, proficiency_bonus));
+        }
+
+        for stat in &advance.ability_increases {
+            self.increase_stat(*stat, &mut log);
+        }
+
+        if let (None, Some(ability)) = (self.spellcasting_ability, advance.spellcasting_ability) {
+            self.spellcasting_ability = Some(ability);
+            log.push(format!("Casts spells with {:?}", ability));
+        }
+
+        if let Some(table) = advance.spell_slots {
+            self.set_spell_slots(&table, &mut log);
+        }
+
+        for feature in advance.features {
+            self.gain_feature(feature, &mut log);
+        }
+
+        for spell in advance.spells {
+            if !self.spells.iter().any(|known| known.eq_ignore_ascii_case(&spell)) {
+                log.push(format!("Learned {}", spell));
+                self.spells.push(spell);
+            }
+        }
+
+        Ok(log)
+    }
+
+    /// Raises an ability by one. A new modifier carries over to the skills that
+    /// use it and one hit point per level for Constitution.
+    fn increase_stat(&mut self, stat: stats::Stat, log: &mut Vec<String>) {
+        let before = self.stats.get(&stat).copied().unwrap_or(10);
+        let after = before + 1;
+        self.stats.insert(stat, after);
+        log.push(format!("{} {} -> {}", stat.abbreviation(), before, after));
+
+        let change = stats::modifier(after) - stats::modifier(before);
+        if change == 0 {
+            return;
+        }
+
+        for skill in self.skills.iter_mut().filter(|skill| skill.stat == stat) {
+            skill.bonus += change;
+        }
+
+        if stat == stats::Stat::Constitution {
+            // `level_up` checked that these fit.
+            let hitpoints = change * self.level as i16;
+            self.max_hitpoints = i16::try_from(self.max_hitpoints).unwrap_or(i16::MAX).saturating_add(hitpoints).max(1) as u16;
+            self.current_hitpoints = self.current_hitpoints.saturating_add(hitpoints);
+            log.push(format!("Hit point maximum increased by {} to {}", hitpoints, self.max_hitpoints));
+        }
+    }
+
+    /// Replaces the spell slot table, e.g. `[(1, 4), (2, 2)]`. Slots that were
+    /// spent stay spent, and spell levels missing from `table` are dropped,
+    /// which is how pact magic slots move up a level.
+    fn set_spell_slots(&mut self, table: &[(u8, u16)], log: &mut Vec<String>) {
+        let slots: Vec<SpellSlots> = table.iter()
+            .map(|(level, max)| {
+                let spent = self.spell_slots.iter()
+                    .find(|slots| slots.level == *level)
+                    .map(|slots| slots.max.saturating_sub(slots.current))
+                    .unwrap_or(0);
+                SpellSlots { level: *level, max: *max, current: max.saturating_sub(spent) }
+            })
+            .collect();
+
+        let unchanged = slots.len() == self.spell_slots.len()
+            && slots.iter().zip(&self.spell_slots).all(|(new, old)| new.level == old.level && new.max == old.max);
+        if !unchanged {
+            let table: Vec<String> = slots.iter().map(|slots| format!("{} {}", ordinal(slots.level), slots.max)).collect();
+            log.push(format!("Spell slots: {}", table.join(", ")));
+        }
+
+        self.spell_slots = slots;
+    }
+
+    /// Adds a feature, or replaces the one with the same name, e.g. Rage with
+    /// more uses. Uses already spent stay spent.
+    fn gain_feature(&mut self, mut feature: Feature, log: &mut Vec<String>) {
+        match self.features.iter().position(|known| known.name.eq_ignore_ascii_case(&feature.name)) {
+            Some(index) => {
+                let spent = self.features[index].uses.as_ref().map(|uses| uses.max.saturating_sub(uses.current)).unwrap_or(0);
+                if let Some(uses) = feature.uses.as_mut() {
+                    uses.current = uses.max.saturating_sub(spent);
+                    log.push(format!("{} improved to {} uses", feature.name, uses.max));
+                } else {
+                    log.push(format!("{} improved", feature.name));
+                }
+                self.features[index] = feature;
+            },
+            None => {
+                match feature.source {
+                    FeatureSource::Feat => log.push(format!("New feat: {}", feature.name)),
+                    source => log.push(format!("New {} feature: {}", source.to_string().to_lowercase(), feature.name))
+                }
+                self.features.push(feature);
+            }
+        }
+    }
+    // ####### END HIT POINTS ########
+
+    // ####### CONDITIONS ########
+    pub fn get_conditions(&self) -> &[Condition] { &self.conditions }
+
+    pub fn get_exhaustion(&self) -> &u8 { &self.exhaustion }
+
+    pub fn has_condition(&self, condition: Condition) -> bool {
+        self.conditions.contains(&condition)
+    }
+
+    /// Adds the condition if missing, removes it otherwise. Returns whether it is now active.
+    pub fn toggle_condition(&mut self, condition: Condition) -> bool {
+        if self.has_condition(condition) {
+            self.conditions.retain(|active| *active != condition);
+            false
+        } else {
+            self.conditions.push(condition);
+            true
+        }
+    }
+
+    pub fn set_exhaustion(&mut self, level: u8) -> Result<(), String> {
+        if level > MAX_EXHAUSTION {
+            return Err(format!("exhaustion goes from 0 to {}, got {}", MAX_EXHAUSTION, level));
+        }
+
+        self.exhaustion = level;
+
+        // Exhaustion 4 halves the maximum, which drags current hit points down with it.
+        let max_hitpoints = self.get_effective_max_hitpoints() as i16;
+        self.current_hitpoints = self.current_hitpoints.min(max_hitpoints);
+        Ok(())
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.exhaustion >= MAX_EXHAUSTION
+    }
+
+    /// Roll mode for ability checks, which includes every skill roll.
+    pub fn ability_check_mode(&self) -> RollMode {
+        self.breakdown(&Target::AbilityChecks).mode
+    }
+
+    pub fn attack_roll_mode(&self) -> RollMode {
+        self.breakdown(&Target::AttackRolls).mode
+    }
+
+    pub fn saving_throw_mode(&self, stat: &stats::Stat) -> RollMode {
+        self.breakdown(&Target::SavingThrow(*stat)).mode
+    }
+
+    /// Paralyzed, petrified, stunned and unconscious creatures fail STR and DEX saves.
+    pub fn fails_saving_throw(&self, stat: &stats::Stat) -> bool {
+        self.conditions.iter().any(|condition| condition.fails_save(stat))
+    }
+
+    /// Walking speed after effects, conditions and exhaustion are applied.
+    pub fn get_speed(&self) -> u16 {
+        self.breakdown(&Target::Speed).total.max(0) as u16
+    }
+
+    /// Hit point maximum after effects and exhaustion are applied.
+    pub fn get_effective_max_hitpoints(&self) -> u16 {
+        self.breakdown(&Target::MaxHitpoints).total.max(0) as u16
+    }
+    // ####### END CONDITIONS ########
+
+    // ####### EFFECTS ########
+    pub fn get_effects(&self) -> &[ActiveEffect] { &self.effects }
+
+    /// Every modifier in play with its source: features, items in use, active
+    /// effects, conditions and exhaustion.
+    pub fn get_modifiers(&self) -> Vec<Contribution> {
+        let features = self.features.iter()
+            .flat_map(|feature| feature.modifiers.iter().map(move |modifier| (feature.name.clone(), modifier.clone())));
+        let items = self.inventory.iter()
+            .filter(|item| item.in_use())
+            .flat_map(|item| item.modifiers.iter().map(move |modifier| (item.name.clone(), modifier.clone())));
+        let effects = self.effects.iter()
+            .flat_map(|effect| effect.modifiers.iter()
+                .map(move |modifier| (format!("{} ({})", effect.name, effect.duration), modifier.clone())));
+        let conditions = self.conditions.iter()
+            .flat_map(|condition| condition.modifiers().into_iter().map(move |modifier| (condition.to_string(), modifier)));
+        let exhaustion = exhaustion_modifiers(self.exhaustion).into_iter()
+            .map(|modifier| (format!("Exhaustion {}", self.exhaustion), modifier));
+
+        features.chain(items).chain(effects).chain(conditions).chain(exhaustion)
+            .map(|(source, modifier)| Contribution { source, modifier })
+            .collect()
+    }
+
+    /// Why `target` is what it is: the base values on the sheet it starts
+    /// from, then every modifier in play that changes it.
+    pub fn breakdown(&self, target: &Target) -> Breakdown {
+        let base_modifier = |stat: stats::Stat| self.stats.get(&stat).copied().map(stats::modifier).unwrap_or(0);
+        let modifier = |stat: stats::Stat| self.get_modifier(&stat).unwrap_or(0);
+        let ability = |stat: stats::Stat| (format!("{} modifier", stat.abbreviation()), modifier(stat));
+        let proficiency = ("proficiency".to_owned(), i16::try_from(self.proficiency_bonus).unwrap_or(i16::MAX));
+
+        let base = match target {
+            Target::ArmorClass => vec![("armor class on the sheet".to_owned(), i16::try_from(self.armor_class).unwrap_or(i16::MAX))],
+            Target::Initiative => vec![ability(stats::Stat::Dexterity)],
+            Target::Speed => vec![("walking speed".to_owned(), i16::try_from(self.walking_speed_in_ft).unwrap_or(i16::MAX))],
+            Target::Darkvision => Vec::new(),
+            Target::MaxHitpoints => vec![("hit point maximum".to_owned(), i16::try_from(self.max_hitpoints).unwrap_or(i16::MAX))],
+            Target::AbilityScore(stat) => self.stats.get(stat)
+                .map(|score| vec![("score on the sheet".to_owned(), *score)])
+                .unwrap_or_default(),
+            Target::SavingThrow(stat) if self.has_saving_throw_proficiency(stat) => vec![ability(*stat), proficiency],
+            Target::SavingThrow(stat) => vec![ability(*stat)],
+            Target::Skill(name) => match self.find_skill(name) {
+                Some(skill) => {
+                    // The bonus on the sheet holds the ability modifier before
+                    // effects, proficiency and anything else, like expertise.
+                    let proficient = if skill.has_proficiency { proficiency.1 } else { 0 };
+                    let other = skill.bonus.saturating_sub(base_modifier(skill.stat)).saturating_sub(proficient);
+
+                    let mut base = vec![ability(skill.stat)];
+                    if skill.has_proficiency {
+                        base.push(proficiency);
+                    }
+                    if other != 0 {
+                        base.push(("other bonuses on the sheet".to_owned(), other));
+                    }
+                    base
+                },
+                None => Vec::new()
+            },
+            Target::SpellAttack | Target::SpellSaveDc => match self.spellcasting_ability {
+                Some(stat) if *target == Target::SpellSaveDc => vec![("base".to_owned(), 8), proficiency, ability(stat)],
+                Some(stat) => vec![proficiency, ability(stat)],
+                None => Vec::new()
+            },
+            Target::SavingThrows | Target::AbilityChecks | Target::AttackRolls | Target::Damage(_) => Vec::new()
+        };
+
+        let contributions = self.get_modifiers();
+        match target {
+            // Abilities added to a score use the scores on the sheet, so a
+            // score never depends on itself.
+            Target::AbilityScore(_) => Breakdown::compute(target.clone(), base, &contributions, &base_modifier),
+            _ => Breakdown::compute(target.clone(), base, &contributions, &modifier)
+        }
+    }
+
+    /// Every number on the sheet effects can change, in the order of the
+    /// sheet, then the rolls and the damage types with a resistance.
+    pub fn breakdown_targets(&self) -> Vec<Target> {
+        let mut targets = vec![Target::ArmorClass, Target::Initiative, Target::Speed];
+        if self.get_darkvision() > 0 {
+            targets.push(Target::Darkvision);
+        }
+        targets.push(Target::MaxHitpoints);
+        targets.extend(stats::Stat::ALL.iter().map(|stat| Target::AbilityScore(*stat)));
+        targets.extend(stats::Stat::ALL.iter().map(|stat| Target::SavingThrow(*stat)));
+        targets.extend(self.skills.iter().map(|skill| Target::Skill(skill.name.clone())));
+        targets.extend(vec![Target::AbilityChecks, Target::AttackRolls]);
+        if self.spellcasting_ability.is_some() {
+            targets.extend(vec![Target::SpellAttack, Target::SpellSaveDc]);
+        }
+
+        for contribution in self.get_modifiers() {
+            if let Target::Damage(_) = contribution.modifier.target {
+                if !targets.iter().any(|target| target.covers(&contribution.modifier.target)) {
+                    targets.push(contribution.modifier.target);
+                }
+            }
+        }
+        targets
+    }
+
+    /// Where the resistance to `damage_type` comes from, if the character has one.
+    pub fn resistance_to(&self, damage_type: &str) -> Option<String> {
+        self.breakdown(&Target::Damage(damage_type.to_owned())).resistance().map(str::to_owned)
+    }
+
+    /// Starts an effect, replacing the one with the same name, e.g. when a
+    /// spell is cast again.
+    pub fn add_effect(&mut self, effect: ActiveEffect) -> String {
+        let changes: Vec<String> = effect.modifiers.iter().map(Modifier::to_string).collect();
+        let message = format!("{}: {} for {}", effect.name, changes.join(", "), effect.duration);
+
+        self.effects.retain(|active| !active.name.eq_ignore_ascii_case(&effect.name));
+        self.effects.push(effect);
+        message
+    }
+
+    fn end_concentration_effects(&mut self, spell: &str) {
+        self.effects.retain(|effect| {
+            !effect.concentration || !effect.spell.as_deref().is_some_and(|id| id.eq_ignore_ascii_case(spell))
+        });
+    }
+
+    /// Lets `rounds` go by. Effects count down and end when they run out,
+    /// together with the concentration on their spell.
+    pub fn pass_time(&mut self, rounds: u32) -> Vec<String> {
+        for effect in &mut self.effects {
+            if let Duration::Rounds(left) = &mut effect.duration {
+                *left = left.saturating_sub(rounds);
+            }
+        }
+
+        self.end_effects(|effect| effect.duration == Duration::Rounds(0))
+    }
+
+    fn end_effects(&mut self, ends: impl Fn(&ActiveEffect) -> bool) -> Vec<String> {
+        let (ended, active): (Vec<ActiveEffect>, Vec<ActiveEffect>) = self.effects.drain(..).partition(|effect| ends(effect));
+        self.effects = active;
+
+        ended.into_iter()
+            .map(|effect| {
+                let concentrating = self.concentration.as_ref()
+                    .is_some_and(|spell| effect.concentration && effect.spell.as_ref().is_some_and(|id| id.eq_ignore_ascii_case(spell)));
+                if concentrating {
+                    self.concentration = None;
+                }
+                format!("{} ended", effect.name)
+            })
+            .collect()
+    }
+    // ####### END EFFECTS ########
+
+    fn find_feature_mut(&mut self, name: &str) -> Result<&mut Feature, FeatureError> {
+        self.features.iter_mut()
+            .find(|feature| feature.name.eq_ignore_ascii_case(name))
+            .ok_or_else(|| FeatureError::NoSuchFeature(name.to_owned()))
+    }
+
+    /// Ticks off one use of a limited feature, returning the uses left.
+    pub fn use_feature(&mut self, name: &str) -> Result<u16, FeatureError> {
+        let feature = self.find_feature_mut(name)?;
+        let feature_name = feature.name.clone();
+
+        let uses = feature.uses.as_mut()
+            .ok_or_else(|| FeatureError::NotLimited(feature_name.clone()))?;
+
+        if uses.current == 0 {
+            return Err(FeatureError::NoUsesLeft(feature_name));
+        }
+
+        uses.current -= 1;
+        Ok(uses.current)
+    }
+
+    /// Gives back one use of a limited feature, e.g. to undo a misclick.
+    pub fn restore_feature_use(&mut self, name: &str) -> Result<u16, FeatureError> {
+        let feature = self.find_feature_mut(name)?;
+        let feature_name = feature.name.clone();
+
+        let uses = feature.uses.as_mut()
+            .ok_or_else(|| FeatureError::NotLimited(feature_name.clone()))?;
+
+        if uses.current >= uses.max {
+            return Err(FeatureError::AllUsesLeft(feature_name));
+        }
+
+        uses.current += 1;
+        Ok(uses.current)
+    }
+
+    fn recharge(&mut self, rest: RechargeOn) -> Vec<String> {
+        let items = self.inventory.iter_mut()
+            .filter_map(|item| {
+                let regained = item.recharge(rest);
+                let charges = item.charges.as_ref()?;
+
+                if regained > 0 {
+                    Some(format!("{} regains {} charges ({}/{})", item.name, regained, charges.current, charges.max))
+                } else {
+                    None
+                }
+            });
+
+        let features = self.features.iter_mut()
+            .filter_map(|feature| {
+                let regained = feature.recharge(rest);
+                let uses = feature.uses.as_ref()?;
+
+                if regained > 0 {
+                    Some(format!("{} regains {} uses ({}/{})", feature.name, regained, uses.current, uses.max))
+                } else {
+                    None
+                }
+            });
+
+        items.chain(features).collect()
+    }
+
+    /// Applies a short rest, an hour in which effects run down, and returns a
+    /// line for every resource that came back and every effect that ended.
+    pub fn short_rest(&mut self) -> Vec<String> {
+        let mut log = self.pass_time(ROUNDS_PER_HOUR);
+        log.extend(self.recharge(RechargeOn::ShortRest));
+        log
+    }
+
+    /// Applies a long rest: eight hours pass and effects that last until a
+    /// long rest end, one level of exhaustion goes away, hit points and spell
+    /// slots are restored and every recharge rule fires, including the "at dawn" ones.
+    /// Returns a line for every resource that came back and every effect that ended.
+    pub fn long_rest(&mut self) -> Vec<String> {
+        let mut log = self.pass_time(8 * ROUNDS_PER_HOUR);
+        log.extend(self.end_effects(|effect| effect.duration == Duration::UntilLongRest));
+
+        if self.exhaustion > 0 {
+            self.exhaustion -= 1;
+            log.push(format!("Exhaustion reduced to {}", self.exhaustion));
+        }
+
+        let max_hitpoints = self.get_effective_max_hitpoints() as i16;
+        let hitpoints_before = self.current_hitpoints;
+        if self.current_hitpoints < max_hitpoints {
+            log.push(format!("Hit points restored to {}", max_hitpoints));
+            self.current_hitpoints = max_hitpoints;
+        }
+        self.regain_consciousness(hitpoints_before, &mut log);
+
+        if self.spell_slots.iter().any(|slots| slots.current < slots.max) {
+            for slots in &mut self.spell_slots {
+                slots.current = slots.max;
+            }
+            log.push("Spell slots restored".to_owned());
+        }
+
+        log.extend(self.recharge(RechargeOn::LongRest));
+        log
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A level 1 fighter with every ability at 10 but CON 14: 12 hit points.
+    fn fighter() -> Character {
+        Character::create(new_fighter())
+    }
+
+    fn new_fighter() -> NewCharacter {
+        let mut stats: HashMap<stats::Stat, i16> = stats::Stat::ALL.iter().map(|stat| (*stat, 10)).collect();
+        stats.insert(stats::Stat::Constitution, 14);
+
+        NewCharacter {
+            name: "Tester".to_owned(),
+            race: "Human".to_owned(),
+            class: "Fighter".to_owned(),
+            background: "Soldier".to_owned(),
+            stats,
+            ability_rolls: Vec::new(),
+            hitpoints: 10,
+            walking_speed_in_ft: 30,
+            saving_throws: vec![stats::Stat::Strength, stats::Stat::Constitution],
+            skill_proficiencies: vec!["Athletics".to_owned()],
+            proficiencies: Proficiencies::default(),
+            spellcasting_ability: None,
+            spell_slots: Vec::new(),
+            spells: Vec::new(),
+            features: Vec::new(),
+            inventory: Vec::new()
+        }
+    }
+
+    #[test]
+    fn create_adds_the_constitution_modifier_to_hit_points() {
+        let character = fighter();
+        assert_eq!((character.current_hitpoints, character.max_hitpoints), (12, 12));
+
+        let mut new = NewCharacter { hitpoints: u16::MAX, ..new_fighter() };
+        new.stats.insert(stats::Stat::Constitution, 1);
+        let character = Character::create(new);
+        assert_eq!((character.current_hitpoints, character.max_hitpoints), (i16::MAX - 5, i16::MAX as u16 - 5));
+    }
+
+    #[test]
+    fn level_up_adds_hit_points_and_proficiency() {
+        let mut character = fighter();
+        character.level = 4;
+        character.take_damage(2);
+
+        character.level_up(LevelAdvance { hitpoints: 8, ..LevelAdvance::default() }).unwrap();
+        assert_eq!(character.level, 5);
+        assert_eq!((character.current_hitpoints, character.max_hitpoints), (18, 20));
+        assert_eq!(character.proficiency_bonus, 3);
+        assert_eq!(character.find_skill("Athletics").map(|skill| skill.bonus), Some(3));
+    }
+
+    #[test]
+    fn level_up_refuses_hit_points_that_do_not_fit() {
+        for hitpoints in &[40000, u16::MAX, i16::MAX as u16 - 5] {
+            let mut character = fighter();
+            assert!(character.level_up(LevelAdvance { hitpoints: *hitpoints, ..LevelAdvance::default() }).is_err());
+            assert_eq!((character.level, character.max_hitpoints), (1, 12), "{} hit points", hitpoints);
+        }
+    }
+
+    #[test]
+    fn level_up_refuses_constitution_hit_points_that_do_not_fit() {
+        let mut character = fighter();
+        character.max_hitpoints = i16::MAX as u16 - 7;
+        character.current_hitpoints = i16::MAX - 7;
+        character.stats.insert(stats::Stat::Constitution, 13);
+
+        let advance = LevelAdvance { hitpoints: 6, ability_increases: vec![stats::Stat::Constitution], ..LevelAdvance::default() };
+        assert!(character.level_up(advance).is_err());
+        assert_eq!((character.level, character.max_hitpoints), (1, i16::MAX as u16 - 7));
+        assert_eq!(character.stats[&stats::Stat::Constitution], 13);
+
+        let advance = LevelAdvance { hitpoints: 5, ability_increases: vec![stats::Stat::Constitution], ..LevelAdvance::default() };
+        character.level_up(advance).unwrap();
+        assert_eq!((character.max_hitpoints, character.current_hitpoints), (i16::MAX as u16, i16::MAX));
+    }
+
+    #[test]
+    fn level_up_lowers_a_proficiency_bonus_above_the_table() {
+        let mut character = fighter();
+        character.proficiency_bonus = 4;
+
+        character.level_up(LevelAdvance { hitpoints: 6, ..LevelAdvance::default() }).unwrap();
+        assert_eq!(character.proficiency_bonus, 2);
+    }
+
+    #[test]
+    fn level_up_stops_at_twenty() {
+        let mut character = fighter();
+        character.level = 20;
+        assert!(character.level_up(LevelAdvance::default()).is_err());
+    }
+
+    fn feature(name: &str, modifiers: Vec<Modifier>) -> Feature {
+        Feature { name: name.to_owned(), source: FeatureSource::Race, description: String::new(), uses: None, modifiers }
+    }
+
+    #[test]
+    fn darkvision_comes_from_features() {
+        let mut character = fighter();
+        assert_eq!(character.get_darkvision(), 0);
+        assert!(!character.breakdown_targets().contains(&Target::Darkvision));
+
+        character.features.push(feature("Darkvision", vec![Modifier::new(Target::Darkvision, ModifierKind::Base, 60)]));
+        assert_eq!(character.get_darkvision(), 60);
+        assert!(character.breakdown_targets().contains(&Target::Darkvision));
+    }
+
+    #[test]
+    fn conditional_modifiers_are_noted_but_not_applied() {
+        let mut character = fighter();
+        let against_magic = |stat| Modifier {
+            against: Some("magic".to_owned()),
+            ..Modifier::new(Target::SavingThrow(stat), ModifierKind::Advantage, 0)
+        };
+        character.features.push(feature("Gnome Cunning",
+            vec![against_magic(stats::Stat::Intellect), against_magic(stats::Stat::Wisdom), against_magic(stats::Stat::Charisma)]));
+
+        assert_eq!(character.saving_throw_mode(&stats::Stat::Wisdom), RollMode::Normal);
+        assert_eq!(character.conditional_save_notes(), vec!["Advantage on INT WIS CHA against magic"]);
+        assert_eq!(character.breakdown(&Target::SavingThrow(stats::Stat::Wisdom)).lines.len(), 2);
+    }
+
+    #[test]
+    fn take_damage_stops_at_zero_and_knocks_out() {
+        let mut character = fighter();
+
+        character.take_damage(5);
+        assert_eq!(character.current_hitpoints, 7);
+        assert!(!character.has_condition(Condition::Unconscious));
+
+        character.take_damage(20);
+        assert_eq!(character.current_hitpoints, 0);
+        assert!(character.has_condition(Condition::Unconscious));
+    }
+
+    #[test]
+    fn take_damage_does_not_wrap_on_huge_amounts() {
+        for amount in &[32767, 32768, 40000, u16::MAX] {
+            let mut character = fighter();
+            character.take_damage(*amount);
+            assert_eq!(character.current_hitpoints, 0, "{} damage", amount);
+        }
+    }
+
+    #[test]
+    fn heal_stops_at_the_maximum_and_wakes_up() {
+        let mut character = fighter();
+        character.take_damage(12);
+
+        character.heal(3);
+        assert_eq!(character.current_hitpoints, 3);
+        assert!(!character.has_condition(Condition::Unconscious));
+
+        for amount in &[32767, 32768, u16::MAX] {
+            character.heal(*amount);
+            assert_eq!(character.current_hitpoints, 12, "{} healing", amount);
+        }
+    }
+}
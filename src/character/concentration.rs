@@ -0,0 +1,31 @@
+/// The DC of the Constitution saving throw to keep concentrating after taking
+/// `damage`: half the damage, but never less than 10.
+pub fn concentration_save_dc(damage: u16) -> u16 {
+    (damage / 2).max(10)
+}
+
+/// A Constitution saving throw made to keep concentrating on a spell.
+pub struct ConcentrationSave {
+    /// The ID of the spell, e.g. "spell:bless".
+    pub spell: String,
+    pub dc: u16,
+    /// Two dice with advantage or disadvantage.
+    pub rolls: Vec<i16>,
+    pub bonus: i16,
+    pub total: i16,
+    pub kept: bool
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn concentration_save_dc_is_half_the_damage_but_at_least_ten() {
+        assert_eq!(concentration_save_dc(0), 10);
+        assert_eq!(concentration_save_dc(21), 10);
+        assert_eq!(concentration_save_dc(22), 11);
+        assert_eq!(concentration_save_dc(45), 22);
+        assert_eq!(concentration_save_dc(u16::MAX), 32767);
+    }
+}
@@ -0,0 +1,180 @@
+use std::fmt;
+
+use schemars::JsonSchema;
+
+use crate::character::effect::{Modifier, ModifierKind, Target};
+use crate::character::stats::Stat;
+
+/// Exhaustion goes from 1 to 6; at level 6 the creature dies.
+pub const MAX_EXHAUSTION: u8 = 6;
+
+/// What every level of exhaustion up to `level` does: disadvantage on ability
+/// checks, half speed, disadvantage on attacks and saves, half the hit point
+/// maximum, then a speed of 0.
+pub fn exhaustion_modifiers(level: u8) -> Vec<Modifier> {
+    let levels = [
+        (1, Target::AbilityChecks, ModifierKind::Disadvantage),
+        (2, Target::Speed, ModifierKind::Halve),
+        (3, Target::AttackRolls, ModifierKind::Disadvantage),
+        (3, Target::SavingThrows, ModifierKind::Disadvantage),
+        (4, Target::MaxHitpoints, ModifierKind::Halve),
+        (5, Target::Speed, ModifierKind::Set)
+    ];
+
+    levels.iter()
+        .filter(|(from, _, _)| level >= *from)
+        .map(|(_, target, kind)| Modifier::new(target.clone(), *kind, 0))
+        .collect()
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+pub enum Condition {
+    Blinded,
+    Charmed,
+    Deafened,
+    Frightened,
+    Grappled,
+    Incapacitated,
+    Invisible,
+    Paralyzed,
+    Petrified,
+    Poisoned,
+    Prone,
+    Restrained,
+    Stunned,
+    Unconscious
+}
+
+impl Condition {
+
+    pub const ALL: [Condition; 14] = [
+        Condition::Blinded, Condition::Charmed, Condition::Deafened, Condition::Frightened,
+        Condition::Grappled, Condition::Incapacitated, Condition::Invisible, Condition::Paralyzed,
+        Condition::Petrified, Condition::Poisoned, Condition::Prone, Condition::Restrained,
+        Condition::Stunned, Condition::Unconscious
+    ];
+
+    /// The modifiers the condition puts on a creature. Failed saves are not
+    /// modifiers, see `fails_save`.
+    pub fn modifiers(self) -> Vec<Modifier> {
+        let mut modifiers = Vec::new();
+
+        if self.sets_speed_to_zero() {
+            modifiers.push(Modifier::new(Target::Speed, ModifierKind::Set, 0));
+        }
+        if self.gives_disadvantage_on_ability_checks() {
+            modifiers.push(Modifier::new(Target::AbilityChecks, ModifierKind::Disadvantage, 0));
+        }
+        if self.gives_disadvantage_on_attacks() {
+            modifiers.push(Modifier::new(Target::AttackRolls, ModifierKind::Disadvantage, 0));
+        }
+        if self.gives_advantage_on_attacks() {
+            modifiers.push(Modifier::new(Target::AttackRolls, ModifierKind::Advantage, 0));
+        }
+        for stat in Stat::ALL.iter().filter(|stat| self.gives_disadvantage_on_save(stat)) {
+            modifiers.push(Modifier::new(Target::SavingThrow(*stat), ModifierKind::Disadvantage, 0));
+        }
+
+        modifiers
+    }
+
+    fn sets_speed_to_zero(self) -> bool {
+        matches!(self,
+            Condition::Grappled | Condition::Restrained | Condition::Paralyzed |
+            Condition::Petrified | Condition::Stunned | Condition::Unconscious)
+    }
+
+    fn gives_disadvantage_on_ability_checks(self) -> bool {
+        matches!(self, Condition::Poisoned | Condition::Frightened)
+    }
+
+    fn gives_disadvantage_on_attacks(self) -> bool {
+        matches!(self,
+            Condition::Blinded | Condition::Frightened | Condition::Poisoned |
+            Condition::Prone | Condition::Restrained)
+    }
+
+    fn gives_advantage_on_attacks(self) -> bool {
+        self == Condition::Invisible
+    }
+
+    fn gives_disadvantage_on_save(self, stat: &Stat) -> bool {
+        self == Condition::Restrained && *stat == Stat::Dexterity
+    }
+
+    pub fn fails_save(self, stat: &Stat) -> bool {
+        matches!(self,
+            Condition::Paralyzed | Condition::Petrified | Condition::Stunned | Condition::Unconscious)
+            && matches!(stat, Stat::Strength | Stat::Dexterity)
+    }
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::character::dice::RollMode;
+    use crate::character::effect::{Breakdown, Contribution};
+
+    fn compute(target: Target, base: i16, modifiers: Vec<Modifier>) -> Breakdown {
+        let contributions: Vec<Contribution> = modifiers.into_iter()
+            .map(|modifier| Contribution { source: "test".to_owned(), modifier })
+            .collect();
+
+        Breakdown::compute(target, vec![("sheet".to_owned(), base)], &contributions, &|_| 0)
+    }
+
+    fn has(modifiers: &[Modifier], target: Target, kind: ModifierKind) -> bool {
+        modifiers.iter().any(|modifier| modifier.target == target && modifier.kind == kind)
+    }
+
+    #[test]
+    fn poisoned_gives_disadvantage_on_ability_checks() {
+        let modifiers = Condition::Poisoned.modifiers();
+
+        assert!(has(&modifiers, Target::AbilityChecks, ModifierKind::Disadvantage));
+        assert_eq!(compute(Target::Skill("Athletics".to_owned()), 2, modifiers).mode, RollMode::Disadvantage);
+    }
+
+    #[test]
+    fn grappled_and_others_set_speed_to_zero() {
+        for condition in &Condition::ALL {
+            let speed = compute(Target::Speed, 30, condition.modifiers()).total;
+            let stopped = matches!(condition,
+                Condition::Grappled | Condition::Restrained | Condition::Paralyzed |
+                Condition::Petrified | Condition::Stunned | Condition::Unconscious);
+
+            assert_eq!(speed, if stopped { 0 } else { 30 }, "{}", condition);
+        }
+    }
+
+    #[test]
+    fn exhaustion_adds_up_level_by_level() {
+        assert!(exhaustion_modifiers(0).is_empty());
+        assert_eq!(exhaustion_modifiers(1).len(), 1);
+        assert!(has(&exhaustion_modifiers(1), Target::AbilityChecks, ModifierKind::Disadvantage));
+        assert!(has(&exhaustion_modifiers(2), Target::Speed, ModifierKind::Halve));
+        assert!(has(&exhaustion_modifiers(4), Target::MaxHitpoints, ModifierKind::Halve));
+        assert_eq!(exhaustion_modifiers(MAX_EXHAUSTION).len(), 6);
+    }
+
+    #[test]
+    fn exhaustion_halves_speed_then_stops_it() {
+        assert_eq!(compute(Target::Speed, 30, exhaustion_modifiers(1)).total, 30);
+        assert_eq!(compute(Target::Speed, 30, exhaustion_modifiers(2)).total, 15);
+        assert_eq!(compute(Target::Speed, 30, exhaustion_modifiers(4)).total, 15);
+        assert_eq!(compute(Target::Speed, 30, exhaustion_modifiers(5)).total, 0);
+    }
+
+    #[test]
+    fn exhaustion_halves_the_hit_point_maximum_from_level_four() {
+        assert_eq!(compute(Target::MaxHitpoints, 21, exhaustion_modifiers(2)).total, 21);
+        assert_eq!(compute(Target::MaxHitpoints, 21, exhaustion_modifiers(4)).total, 10);
+        assert_eq!(compute(Target::MaxHitpoints, 21, exhaustion_modifiers(5)).total, 10);
+    }
+}
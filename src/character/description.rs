@@ -0,0 +1,23 @@
+use schemars::JsonSchema;
+
+/// The roleplaying side of the sheet. Every field is free text so players can
+/// write as much or as little as they like.
+#[derive(Default, Deserialize, Serialize, JsonSchema)]
+#[serde(default)]
+pub struct Description {
+    pub background: String,
+    pub alignment: String,
+    pub age: String,
+    pub height: String,
+    pub weight: String,
+    pub eyes: String,
+    pub skin: String,
+    pub hair: String,
+
+    pub personality_traits: String,
+    pub ideals: String,
+    pub bonds: String,
+    pub flaws: String,
+    pub backstory: String,
+    pub allies_and_organizations: String
+}
@@ -0,0 +1,182 @@
+use std::fmt;
+
+use rand::Rng;
+
+/// The most dice one notation may roll, e.g. "100d6".
+const MAX_DICE: u16 = 100;
+/// The most sides a die may have: a d100. With `MAX_DICE` the total of a
+/// parsed roll always fits.
+const MAX_SIDES: u16 = 100;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Dice {
+    pub count: u16,
+    pub sides: u16,
+    pub modifier: i16
+}
+
+impl Dice {
+
+    /// Parses dice notation such as "1d6", "2d4+1" or "1d8-1".
+    pub fn parse(notation: &str) -> Result<Dice, String> {
+        let notation = notation.trim().to_lowercase();
+
+        let (dice, modifier) = match notation.find(['+', '-']) {
+            Some(index) => {
+                let modifier = notation[index..].replace('+', "").trim().parse::<i16>()
+                    .map_err(|_| format!("invalid modifier in \"{}\"", notation))?;
+                (&notation[..index], modifier)
+            },
+            None => (&notation[..], 0)
+        };
+
+        let mut parts = dice.trim().splitn(2, 'd');
+        let count = parts.next().unwrap_or("");
+        let sides = parts.next().ok_or_else(|| format!("\"{}\" is not dice notation", notation))?;
+
+        let count = if count.is_empty() { 1 } else {
+            count.parse::<u16>().map_err(|_| format!("invalid dice count in \"{}\"", notation))?
+        };
+        let sides = sides.parse::<u16>()
+            .map_err(|_| format!("invalid dice sides in \"{}\"", notation))?;
+
+        if sides == 0 {
+            return Err(format!("dice in \"{}\" have no sides", notation));
+        }
+        if count > MAX_DICE {
+            return Err(format!("\"{}\" rolls more than {} dice", notation, MAX_DICE));
+        }
+        if sides > MAX_SIDES {
+            return Err(format!("dice in \"{}\" have more than {} sides", notation, MAX_SIDES));
+        }
+
+        Ok(Dice { count, sides, modifier })
+    }
+
+    pub fn roll(&self) -> i16 {
+        self.total(&self.roll_each())
+    }
+
+    /// Adds `rolls` and the modifier up, stopping at the bounds of an i16
+    /// for dice built without `parse`.
+    pub fn total(&self, rolls: &[i16]) -> i16 {
+        let total = rolls.iter().map(|roll| i32::from(*roll)).sum::<i32>() + i32::from(self.modifier);
+        total.clamp(i32::from(i16::MIN), i32::from(i16::MAX)) as i16
+    }
+
+    /// Rolls every die separately, without the modifier.
+    pub fn roll_each(&self) -> Vec<i16> {
+        let mut rng = rand::thread_rng();
+
+        (0..self.count)
+            .map(|_| rng.gen_range(1..=self.sides).min(i16::MAX as u16) as i16)
+            .collect()
+    }
+}
+
+impl fmt::Display for Dice {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}d{}", self.count, self.sides)?;
+
+        match self.modifier {
+            0 => Ok(()),
+            m if m > 0 => write!(f, "+{}", m),
+            m => write!(f, "{}", m)
+        }
+    }
+}
+
+/// Whether a d20 roll is made normally, with advantage or with disadvantage.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RollMode {
+    Normal,
+    Advantage,
+    Disadvantage
+}
+
+impl RollMode {
+
+    /// Combines every source of advantage and disadvantage. Having at least one
+    /// of each cancels out, no matter how many there are.
+    pub fn from_sources(advantage: bool, disadvantage: bool) -> RollMode {
+        match (advantage, disadvantage) {
+            (true, false) => RollMode::Advantage,
+            (false, true) => RollMode::Disadvantage,
+            _ => RollMode::Normal
+        }
+    }
+
+    /// Adds another source, e.g. advantage asked for on the command line.
+    pub fn combine(self, other: RollMode) -> RollMode {
+        RollMode::from_sources(
+            self == RollMode::Advantage || other == RollMode::Advantage,
+            self == RollMode::Disadvantage || other == RollMode::Disadvantage)
+    }
+}
+
+/// Rolls a d20, twice with advantage or disadvantage. Returns the die that
+/// counts and every die rolled.
+pub fn roll_d20(mode: RollMode) -> (i16, Vec<i16>) {
+    let d20 = Dice { count: 1, sides: 20, modifier: 0 };
+
+    match mode {
+        RollMode::Normal => {
+            let roll = d20.roll();
+            (roll, vec![roll])
+        },
+        RollMode::Advantage | RollMode::Disadvantage => {
+            let rolls = vec![d20.roll(), d20.roll()];
+            let kept = if mode == RollMode::Advantage { rolls[0].max(rolls[1]) } else { rolls[0].min(rolls[1]) };
+            (kept, rolls)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_reads_dice_notation() {
+        assert_eq!(Dice::parse("2d4+1"), Ok(Dice { count: 2, sides: 4, modifier: 1 }));
+        assert_eq!(Dice::parse("d20"), Ok(Dice { count: 1, sides: 20, modifier: 0 }));
+        assert_eq!(Dice::parse(" 1D8-1 "), Ok(Dice { count: 1, sides: 8, modifier: -1 }));
+        assert!(Dice::parse("1d0").is_err());
+        assert!(Dice::parse("fireball").is_err());
+    }
+
+    #[test]
+    fn parse_rejects_dice_above_the_limits() {
+        assert!(Dice::parse("100d100").is_ok());
+        assert!(Dice::parse("101d6").is_err());
+        assert!(Dice::parse("1d101").is_err());
+        assert!(Dice::parse("100d1000").is_err());
+        assert!(Dice::parse("1d40000").is_err());
+        assert!(Dice::parse("1d70000").is_err());
+    }
+
+    #[test]
+    fn roll_stays_within_the_dice() {
+        let dice = Dice::parse("100d100+5").unwrap();
+        for _ in 0..20 {
+            let total = dice.roll();
+            assert!((105..=10005).contains(&total), "rolled {}", total);
+        }
+
+        let total = Dice::parse("3d1-10").unwrap().roll();
+        assert_eq!(total, -7);
+    }
+
+    #[test]
+    fn roll_saturates_for_dice_built_by_hand() {
+        let dice = Dice { count: 1000, sides: u16::MAX, modifier: i16::MAX };
+        assert_eq!(dice.roll(), i16::MAX);
+    }
+
+    #[test]
+    fn roll_modes_combine() {
+        assert_eq!(RollMode::Advantage.combine(RollMode::Disadvantage), RollMode::Normal);
+        assert_eq!(RollMode::Normal.combine(RollMode::Advantage), RollMode::Advantage);
+    }
+}
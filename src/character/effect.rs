@@ -0,0 +1,434 @@
+use std::fmt;
+
+use schemars::JsonSchema;
+
+use crate::character::dice::RollMode;
+use crate::character::stats::Stat;
+
+/// A round is 6 seconds, so a minute is 10 rounds.
+pub const ROUNDS_PER_MINUTE: u32 = 10;
+pub const ROUNDS_PER_HOUR: u32 = 60 * ROUNDS_PER_MINUTE;
+const ROUNDS_PER_DAY: u32 = 24 * ROUNDS_PER_HOUR;
+
+fn is_zero(value: &i16) -> bool { *value == 0 }
+
+/// The number or roll a modifier changes, e.g. `"armor_class"` or
+/// `{ "saving_throw": "Dexterity" }`.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Target {
+    ArmorClass,
+    Initiative,
+    /// Walking speed in feet.
+    Speed,
+    /// The range of darkvision in feet; 0 without it.
+    Darkvision,
+    MaxHitpoints,
+    AbilityScore(Stat),
+    SavingThrow(Stat),
+    /// Every saving throw, e.g. for a Cloak of Protection.
+    SavingThrows,
+    /// A skill by name, e.g. "Stealth".
+    Skill(String),
+    /// Every ability check, skills included.
+    AbilityChecks,
+    /// Every attack roll, spell attacks included.
+    AttackRolls,
+    SpellAttack,
+    SpellSaveDc,
+    /// Damage of one type, e.g. "fire", for resistances.
+    Damage(String)
+}
+
+impl Target {
+
+    /// Whether a modifier aimed at this target also changes `other`, e.g.
+    /// one on every saving throw changes the Dexterity save.
+    pub fn covers(&self, other: &Target) -> bool {
+        match (self, other) {
+            (Target::Skill(a), Target::Skill(b)) | (Target::Damage(a), Target::Damage(b)) => a.eq_ignore_ascii_case(b),
+            (Target::SavingThrows, Target::SavingThrow(_))
+            | (Target::AbilityChecks, Target::Skill(_))
+            | (Target::AttackRolls, Target::SpellAttack) => true,
+            _ => self == other
+        }
+    }
+
+    /// Whether the target is a number, rather than only rolled with
+    /// advantage or resisted, like attack rolls or fire damage.
+    pub fn has_value(&self) -> bool {
+        !matches!(self, Target::SavingThrows | Target::AbilityChecks | Target::AttackRolls | Target::Damage(_))
+    }
+
+    /// The name to head a breakdown with: "Armor class", "Dexterity saves".
+    pub fn title(&self) -> String {
+        let name = self.to_string();
+        let mut letters = name.chars();
+
+        match letters.next() {
+            Some(first) => first.to_uppercase().chain(letters).collect(),
+            None => name
+        }
+    }
+
+    /// Reads a target typed by a user: "ac", "initiative", "speed", "hp",
+    /// an ability like "dex", a save like "dex save", "saves", "checks",
+    /// "attacks", "spell attack", "spell dc", "fire damage" or a skill name.
+    pub fn from_name(name: &str) -> Option<Target> {
+        let name = name.trim().to_lowercase().replace(['_', ':', '-'], " ");
+        let words: Vec<&str> = name.split_whitespace().collect();
+
+        let target = match words.as_slice() {
+            ["ac"] | ["armor", "class"] => Target::ArmorClass,
+            ["initiative"] | ["init"] => Target::Initiative,
+            ["speed"] | ["walking", "speed"] => Target::Speed,
+            ["darkvision"] => Target::Darkvision,
+            ["hp"] | ["max", "hp"] | ["max", "hitpoints"] | ["max", "hit", "points"] => Target::MaxHitpoints,
+            ["saves"] | ["saving", "throws"] => Target::SavingThrows,
+            ["checks"] | ["ability", "checks"] => Target::AbilityChecks,
+            ["attacks"] | ["attack", "rolls"] => Target::AttackRolls,
+            ["spell", "attack"] => Target::SpellAttack,
+            ["spell", "dc"] | ["spell", "save", "dc"] => Target::SpellSaveDc,
+            [stat] if Stat::from_name(stat).is_some() => Target::AbilityScore(Stat::from_name(stat)?),
+            [stat, "save"] | ["save", stat] | ["saving", "throw", stat] => Target::SavingThrow(Stat::from_name(stat)?),
+            [kind, "damage"] | ["damage", kind] => Target::Damage((*kind).to_owned()),
+            _ => Target::Skill(words.join(" "))
+        };
+
+        Some(target)
+    }
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Target::ArmorClass => write!(f, "armor class"),
+            Target::Initiative => write!(f, "initiative"),
+            Target::Speed => write!(f, "speed"),
+            Target::Darkvision => write!(f, "darkvision"),
+            Target::MaxHitpoints => write!(f, "max hit points"),
+            Target::AbilityScore(stat) => write!(f, "{:?}", stat),
+            Target::SavingThrow(stat) => write!(f, "{:?} saves", stat),
+            Target::SavingThrows => write!(f, "saving throws"),
+            Target::Skill(name) => write!(f, "{}", name),
+            Target::AbilityChecks => write!(f, "ability checks"),
+            Target::AttackRolls => write!(f, "attack rolls"),
+            Target::SpellAttack => write!(f, "spell attack"),
+            Target::SpellSaveDc => write!(f, "spell save DC"),
+            Target::Damage(kind) => write!(f, "{} damage", kind)
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ModifierKind {
+    /// Adds `value`, or subtracts it when negative.
+    Add,
+    /// Replaces the base value when higher, e.g. Mage Armor's 13 + DEX or the
+    /// Strength 19 of Gauntlets of Ogre Power.
+    Base,
+    /// Halves the value after everything is added, rounding down.
+    Halve,
+    /// Fixes the final value, e.g. a speed of 0 while grappled. The lowest wins.
+    Set,
+    Advantage,
+    Disadvantage,
+    /// Halves damage of the target's type.
+    Resistance
+}
+
+/// One change an effect makes, e.g. `{ "target": "armor_class", "kind": "add", "value": 5 }`.
+#[derive(Clone, Debug, Deserialize, Serialize, JsonSchema)]
+pub struct Modifier {
+    pub target: Target,
+    pub kind: ModifierKind,
+    /// The number to add, or to use as the base or final value.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub value: i16,
+    /// Abilities whose modifiers are added to `value`, e.g. Dexterity for Mage Armor.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub plus: Vec<Stat>,
+    /// Only applies against this, e.g. "magic" for Gnome Cunning. The sheet
+    /// lists such modifiers but leaves them out of totals and roll modes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub against: Option<String>
+}
+
+impl Modifier {
+
+    pub fn new(target: Target, kind: ModifierKind, value: i16) -> Modifier {
+        Modifier { target, kind, value, plus: Vec::new(), against: None }
+    }
+
+    /// "13 + DEX", or just the value.
+    fn value_text(&self) -> String {
+        std::iter::once(self.value.to_string())
+            .chain(self.plus.iter().map(|stat| stat.abbreviation().to_owned()))
+            .collect::<Vec<_>>()
+            .join(" + ")
+    }
+}
+
+impl fmt::Display for Modifier {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.kind {
+            _ if self.against.is_some() => write!(f, "{} against {}",
+                Modifier { against: None, ..self.clone() }, self.against.as_deref().unwrap_or_default()),
+            ModifierKind::Add if self.plus.is_empty() => write!(f, "{:+} {}", self.value, self.target),
+            ModifierKind::Add => write!(f, "+{} {}", self.value_text(), self.target),
+            ModifierKind::Base => write!(f, "{} of {}", self.target, self.value_text()),
+            ModifierKind::Halve => write!(f, "half {}", self.target),
+            ModifierKind::Set => write!(f, "{} set to {}", self.target, self.value_text()),
+            ModifierKind::Advantage => write!(f, "advantage on {}", self.target),
+            ModifierKind::Disadvantage => write!(f, "disadvantage on {}", self.target),
+            ModifierKind::Resistance => write!(f, "resistance to {}", self.target)
+        }
+    }
+}
+
+/// How long an active effect lasts, e.g. `{ "rounds": 10 }`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Duration {
+    /// Counts down as time passes, see `Character::pass_time`.
+    Rounds(u32),
+    UntilLongRest,
+    /// Until it is ended by hand or the spell is dispelled.
+    Indefinite
+}
+
+impl Duration {
+
+    /// Reads a spell duration: "1 round", "10 minutes", "8 hours", "10 days"
+    /// or "Until dispelled". `None` for instantaneous spells. Durations too
+    /// long to count in rounds stop at the most that fits.
+    pub fn from_text(text: &str) -> Option<Duration> {
+        let mut words = text.split_whitespace();
+        let amount = words.next()?.parse::<u32>();
+        let unit = words.next().unwrap_or("").trim_end_matches('s').to_lowercase();
+
+        match (amount, unit.as_str()) {
+            (Ok(amount), "round") => Some(Duration::Rounds(amount)),
+            (Ok(amount), "minute") => Some(Duration::Rounds(amount.saturating_mul(ROUNDS_PER_MINUTE))),
+            (Ok(amount), "hour") => Some(Duration::Rounds(amount.saturating_mul(ROUNDS_PER_HOUR))),
+            (Ok(amount), "day") => Some(Duration::Rounds(amount.saturating_mul(ROUNDS_PER_DAY))),
+            _ if text.to_lowercase().starts_with("until") => Some(Duration::Indefinite),
+            _ => None
+        }
+    }
+}
+
+impl fmt::Display for Duration {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let ceil = |rounds: u32, unit: u32| rounds.div_ceil(unit);
+
+        match *self {
+            Duration::Rounds(1) => write!(f, "1 round"),
+            Duration::Rounds(rounds) if rounds < ROUNDS_PER_MINUTE => write!(f, "{} rounds", rounds),
+            Duration::Rounds(rounds) if rounds < ROUNDS_PER_HOUR => write!(f, "{} min", ceil(rounds, ROUNDS_PER_MINUTE)),
+            Duration::Rounds(rounds) if rounds < ROUNDS_PER_DAY => write!(f, "{} h", ceil(rounds, ROUNDS_PER_HOUR)),
+            Duration::Rounds(rounds) => write!(f, "{} days", ceil(rounds, ROUNDS_PER_DAY)),
+            Duration::UntilLongRest => write!(f, "until a long rest"),
+            Duration::Indefinite => write!(f, "until ended")
+        }
+    }
+}
+
+/// A lasting effect on the character, e.g. a spell like Shield:
+/// `{ "name": "Shield", "spell": "spell:shield", "modifiers": [...], "duration": { "rounds": 1 } }`.
+#[derive(Clone, Debug, Deserialize, Serialize, JsonSchema)]
+pub struct ActiveEffect {
+    pub name: String,
+    /// The ID of the spell the effect comes from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spell: Option<String>,
+    /// The effect ends when the concentration on `spell` does.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub concentration: bool,
+    pub modifiers: Vec<Modifier>,
+    pub duration: Duration
+}
+
+/// A modifier in play, with what it comes from, e.g. "Ring of Protection".
+pub struct Contribution {
+    pub source: String,
+    pub modifier: Modifier
+}
+
+/// One line of a breakdown, e.g. `{ "source": "Shield (1 round)", "change": "+5" }`.
+#[derive(Serialize)]
+pub struct BreakdownLine {
+    pub source: String,
+    pub change: String
+}
+
+/// Why a number is what it is: the base values it starts from and every
+/// modifier that changes it, in the order they apply.
+#[derive(Serialize)]
+pub struct Breakdown {
+    pub target: Target,
+    pub total: i16,
+    pub mode: RollMode,
+    pub lines: Vec<BreakdownLine>
+}
+
+impl Breakdown {
+
+    /// Adds the `base` parts up, then applies the modifiers aimed at `target`:
+    /// base replacements, additions, halving and fixed values, in that order.
+    /// `ability_modifier` turns the `plus` abilities of a modifier into numbers.
+    pub fn compute(target: Target, base: Vec<(String, i16)>, contributions: &[Contribution],
+                   ability_modifier: &dyn Fn(Stat) -> i16) -> Breakdown {
+        let mut lines: Vec<BreakdownLine> = base.iter()
+            .map(|(source, value)| BreakdownLine { source: source.clone(), change: format!("{:+}", value) })
+            .collect();
+        let mut base_total = base.iter().fold(0, |total: i16, (_, value)| total.saturating_add(*value));
+
+        let (conditional, applying): (Vec<&Contribution>, Vec<&Contribution>) = contributions.iter()
+            .filter(|contribution| contribution.modifier.target.covers(&target))
+            .partition(|contribution| contribution.modifier.against.is_some());
+        let value_of = |modifier: &Modifier| modifier.plus.iter()
+            .fold(modifier.value, |value, stat| value.saturating_add(ability_modifier(*stat)));
+        let of_kind = |kind: ModifierKind| applying.iter().filter(move |contribution| contribution.modifier.kind == kind);
+
+        for contribution in of_kind(ModifierKind::Base) {
+            let value = value_of(&contribution.modifier);
+            let change = if value > base_total { format!("base {}", value) } else { format!("base {} (lower, unused)", value) };
+            base_total = base_total.max(value);
+            lines.push(BreakdownLine { source: contribution.source.clone(), change });
+        }
+
+        let mut total = base_total;
+        for contribution in of_kind(ModifierKind::Add) {
+            let value = value_of(&contribution.modifier);
+            total = total.saturating_add(value);
+            lines.push(BreakdownLine { source: contribution.source.clone(), change: format!("{:+}", value) });
+        }
+        for contribution in of_kind(ModifierKind::Halve) {
+            total = total.div_euclid(2);
+            lines.push(BreakdownLine { source: contribution.source.clone(), change: "halved".to_owned() });
+        }
+        for contribution in of_kind(ModifierKind::Set) {
+            let value = value_of(&contribution.modifier);
+            total = total.min(value);
+            lines.push(BreakdownLine { source: contribution.source.clone(), change: format!("set to {}", value) });
+        }
+
+        let mut advantage = false;
+        let mut disadvantage = false;
+        for contribution in applying.iter() {
+            let change = match contribution.modifier.kind {
+                ModifierKind::Advantage => { advantage = true; "advantage" },
+                ModifierKind::Disadvantage => { disadvantage = true; "disadvantage" },
+                ModifierKind::Resistance => "resistance",
+                _ => continue
+            };
+            lines.push(BreakdownLine { source: contribution.source.clone(), change: change.to_owned() });
+        }
+
+        for contribution in conditional {
+            let modifier = Modifier { target: target.clone(), ..contribution.modifier.clone() };
+            lines.push(BreakdownLine { source: contribution.source.clone(), change: modifier.to_string() });
+        }
+
+        Breakdown { target, total, mode: RollMode::from_sources(advantage, disadvantage), lines }
+    }
+
+    /// "14", "+6 with advantage", or only the roll mode or resistance for
+    /// targets without a number, like attack rolls or fire damage.
+    pub fn value_text(&self) -> String {
+        let mode = match self.mode {
+            RollMode::Normal => None,
+            RollMode::Advantage => Some("advantage"),
+            RollMode::Disadvantage => Some("disadvantage")
+        };
+        let value = match self.target {
+            Target::ArmorClass | Target::Speed | Target::Darkvision | Target::MaxHitpoints | Target::AbilityScore(_) | Target::SpellSaveDc =>
+                self.total.to_string(),
+            _ => format!("{:+}", self.total)
+        };
+
+        match (self.target.has_value(), mode) {
+            _ if self.resistance().is_some() => "resistance".to_owned(),
+            _ if matches!(self.target, Target::Damage(_)) => "no resistance".to_owned(),
+            (false, None) => "normal".to_owned(),
+            (false, Some(mode)) => mode.to_owned(),
+            (true, None) => value,
+            (true, Some(mode)) => format!("{} with {}", value, mode)
+        }
+    }
+
+    /// The first source of a resistance in the breakdown of a damage type.
+    pub fn resistance(&self) -> Option<&str> {
+        self.lines.iter()
+            .find(|line| line.change == "resistance")
+            .map(|line| line.source.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contribution(source: &str, modifier: Modifier) -> Contribution {
+        Contribution { source: source.to_owned(), modifier }
+    }
+
+    fn compute(base: i16, contributions: &[Contribution]) -> Breakdown {
+        let dexterity = |stat| if stat == Stat::Dexterity { 3 } else { 0 };
+        Breakdown::compute(Target::ArmorClass, vec![("armor class on the sheet".to_owned(), base)], contributions, &dexterity)
+    }
+
+    #[test]
+    fn breakdown_applies_base_add_halve_and_set_in_order() {
+        let mage_armor = Modifier { plus: vec![Stat::Dexterity], ..Modifier::new(Target::ArmorClass, ModifierKind::Base, 13) };
+        let breakdown = compute(10, &[
+            contribution("Mage Armor", mage_armor),
+            contribution("Shield", Modifier::new(Target::ArmorClass, ModifierKind::Add, 5)),
+            contribution("Ring of Protection", Modifier::new(Target::ArmorClass, ModifierKind::Add, 1))
+        ]);
+        assert_eq!(breakdown.total, 22);
+        assert_eq!(breakdown.lines.len(), 4);
+
+        let breakdown = compute(20, &[
+            contribution("Exhaustion", Modifier::new(Target::ArmorClass, ModifierKind::Halve, 0)),
+            contribution("Grappled", Modifier::new(Target::ArmorClass, ModifierKind::Set, 0))
+        ]);
+        assert_eq!(breakdown.total, 0);
+    }
+
+    #[test]
+    fn breakdown_leaves_conditional_modifiers_out_of_the_total() {
+        let against_magic = Modifier { against: Some("magic".to_owned()), ..Modifier::new(Target::ArmorClass, ModifierKind::Add, 2) };
+        let breakdown = compute(12, &[contribution("Charm", against_magic)]);
+
+        assert_eq!(breakdown.total, 12);
+        assert_eq!(breakdown.lines[1].change, "+2 armor class against magic");
+    }
+
+    #[test]
+    fn breakdown_saturates_instead_of_overflowing() {
+        let huge = |value| contribution("Cursed item", Modifier::new(Target::ArmorClass, ModifierKind::Add, value));
+
+        assert_eq!(compute(10, &[huge(i16::MAX)]).total, i16::MAX);
+        assert_eq!(compute(10, &[huge(i16::MIN), huge(i16::MIN)]).total, i16::MIN);
+
+        let plus = Modifier { plus: vec![Stat::Dexterity], ..Modifier::new(Target::ArmorClass, ModifierKind::Add, i16::MAX) };
+        assert_eq!(compute(i16::MAX, &[contribution("Plus", plus)]).total, i16::MAX);
+    }
+
+    #[test]
+    fn duration_from_text_counts_rounds() {
+        assert_eq!(Duration::from_text("1 round"), Some(Duration::Rounds(1)));
+        assert_eq!(Duration::from_text("10 minutes"), Some(Duration::Rounds(100)));
+        assert_eq!(Duration::from_text("8 hours"), Some(Duration::Rounds(4800)));
+        assert_eq!(Duration::from_text("Until dispelled"), Some(Duration::Indefinite));
+        assert_eq!(Duration::from_text("Instantaneous"), None);
+    }
+
+    #[test]
+    fn duration_from_text_does_not_overflow() {
+        assert_eq!(Duration::from_text("4000000000 days"), Some(Duration::Rounds(u32::MAX)));
+    }
+}
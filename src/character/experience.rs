@@ -0,0 +1,15 @@
+/// The experience points each level starts at, from level 1 to 20.
+pub const EXPERIENCE_FOR_LEVEL: [u32; 20] = [
+    0, 300, 900, 2_700, 6_500, 14_000, 23_000, 34_000, 48_000, 64_000,
+    85_000, 100_000, 120_000, 140_000, 165_000, 195_000, 225_000, 265_000, 305_000, 355_000
+];
+
+/// The level `experience_points` are worth, e.g. 1000 -> 3.
+pub fn level_for_experience(experience_points: u32) -> u16 {
+    EXPERIENCE_FOR_LEVEL.iter().filter(|needed| experience_points >= **needed).count() as u16
+}
+
+/// The experience points `level` starts at, or `None` past level 20.
+pub fn experience_for_level(level: u16) -> Option<u32> {
+    EXPERIENCE_FOR_LEVEL.get(level.max(1) as usize - 1).copied()
+}
@@ -0,0 +1,91 @@
+use std::error::Error;
+use std::fmt;
+
+use schemars::JsonSchema;
+
+use crate::character::effect::Modifier;
+use crate::character::recharge::{Recharge, RechargeOn};
+
+/// Where a feature comes from, mirroring the groups on the paper sheet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+pub enum FeatureSource {
+    Race,
+    Class,
+    Background,
+    Feat
+}
+
+impl fmt::Display for FeatureSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            FeatureSource::Race => "Race",
+            FeatureSource::Class => "Class",
+            FeatureSource::Background => "Background",
+            FeatureSource::Feat => "Feat"
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+/// A limited-use resource, e.g. Arcane Recovery: `{ "max": 1, "current": 1, "recharge": "regains all on a long rest" }`.
+#[derive(Deserialize, Serialize, JsonSchema)]
+pub struct Uses {
+    pub max: u16,
+    pub current: u16,
+    pub recharge: Recharge
+}
+
+#[derive(Deserialize, Serialize, JsonSchema)]
+pub struct Feature {
+    pub name: String,
+    pub source: FeatureSource,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uses: Option<Uses>,
+    /// What the feature always does, e.g. +5 initiative for Alert.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub modifiers: Vec<Modifier>
+}
+
+impl Feature {
+
+    /// Recharges the feature if its rule applies to `rest`, returning how many
+    /// uses were regained.
+    pub fn recharge(&mut self, rest: RechargeOn) -> u16 {
+        match &mut self.uses {
+            Some(uses) if uses.recharge.applies_on(rest) => {
+                let regained = uses.recharge.regain(uses.current, uses.max);
+                uses.current += regained;
+                regained
+            },
+            _ => 0
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum FeatureError {
+    NoSuchFeature(String),
+    NotLimited(String),
+    NoUsesLeft(String),
+    AllUsesLeft(String)
+}
+
+impl fmt::Display for FeatureError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FeatureError::NoSuchFeature(name) =>
+                write!(f, "there is no feature called \"{}\"", name),
+            FeatureError::NotLimited(name) =>
+                write!(f, "{} has no limited uses", name),
+            FeatureError::NoUsesLeft(name) =>
+                write!(f, "{} has no uses left", name),
+            FeatureError::AllUsesLeft(name) =>
+                write!(f, "{} already has all of its uses", name)
+        }
+    }
+}
+
+impl Error for FeatureError {}
@@ -0,0 +1,129 @@
+use std::fmt;
+use std::path::Path;
+
+use crate::character::Character;
+
+/// The file formats a character can be stored in. They all go through serde,
+/// so the layout is the same; only the syntax differs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Yaml,
+    Toml,
+    Ron
+}
+
+impl Format {
+
+    pub fn from_extension(path: &Path) -> Option<Format> {
+        let extension = path.extension()?.to_str()?.to_lowercase();
+
+        match extension.as_str() {
+            "json" => Some(Format::Json),
+            "yaml" | "yml" => Some(Format::Yaml),
+            "toml" => Some(Format::Toml),
+            "ron" => Some(Format::Ron),
+            _ => None
+        }
+    }
+
+    /// Guesses the format from the first meaningful line of a document.
+    pub fn sniff(text: &str) -> Format {
+        let first_line = text.lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty() && !is_comment(line));
+
+        match first_line {
+            Some(line) if line.starts_with('{') => Format::Json,
+            Some(line) if line.starts_with('(') || line.starts_with("Character(") || line.starts_with("#![") => Format::Ron,
+            Some(line) if line.starts_with('[') || is_toml_assignment(line) => Format::Toml,
+            _ => Format::Yaml
+        }
+    }
+
+    /// The extension wins; files without a known one are sniffed.
+    pub fn detect(path: &Path, text: &str) -> Format {
+        Format::from_extension(path).unwrap_or_else(|| Format::sniff(text))
+    }
+
+    pub fn serialize(self, character: &Character) -> Result<String, String> {
+        match self {
+            Format::Json => serde_json::to_string_pretty(character).map_err(|e| e.to_string()),
+            Format::Yaml => serde_yaml::to_string(character).map_err(|e| e.to_string()),
+            Format::Toml => toml::to_string_pretty(character).map_err(|e| e.to_string()),
+            Format::Ron => ron::ser::to_string_pretty(character, ron::ser::PrettyConfig::default())
+                .map_err(|e| e.to_string())
+        }
+    }
+}
+
+/// `#` comments are YAML and TOML, `//` is RON; `#![enable(...)]` is a RON attribute.
+fn is_comment(line: &str) -> bool {
+    line.starts_with("//") || (line.starts_with('#') && !line.starts_with("#!["))
+}
+
+/// `name = "Dandelion"` is TOML, `name: Dandelion` is YAML.
+fn is_toml_assignment(line: &str) -> bool {
+    match (line.find('='), line.find(':')) {
+        (Some(equals), Some(colon)) => equals < colon,
+        (Some(_), None) => true,
+        _ => false
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Format::Json => "JSON",
+            Format::Yaml => "YAML",
+            Format::Toml => "TOML",
+            Format::Ron => "RON"
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sniffs_each_format_from_its_first_line() {
+        assert_eq!(Format::sniff("{\n  \"name\": \"Dandelion\"\n}"), Format::Json);
+        assert_eq!(Format::sniff("# A gnome wizard\nname: Dandelion\nlevel: 3\n"), Format::Yaml);
+        assert_eq!(Format::sniff("# A gnome wizard\nname = \"Dandelion\"\nlevel = 3\n"), Format::Toml);
+        assert_eq!(Format::sniff("[stats]\nStrength = 8\n"), Format::Toml);
+        assert_eq!(Format::sniff("// A gnome wizard\n(\n    name: \"Dandelion\",\n)"), Format::Ron);
+        assert_eq!(Format::sniff("#![enable(implicit_some)]\n(name: \"Dandelion\")"), Format::Ron);
+    }
+
+    #[test]
+    fn ambiguous_lines_go_by_what_comes_first() {
+        // Both a colon and an equals sign: YAML if the colon comes first.
+        assert_eq!(Format::sniff("description: a = b"), Format::Yaml);
+        assert_eq!(Format::sniff("description = \"a: b\""), Format::Toml);
+        // Nothing to go by is read as YAML, the most forgiving of the four.
+        assert_eq!(Format::sniff(""), Format::Yaml);
+        assert_eq!(Format::sniff("# only a comment\n\n"), Format::Yaml);
+    }
+
+    #[test]
+    fn sniffs_what_each_format_writes() {
+        let mut character: Character = serde_json::from_str(include_str!("../../resources/characters/dandelion.json")).unwrap();
+        // `$schema` is not a RON identifier; exporting drops it the same way.
+        character.set_json_schema(None);
+
+        for format in &[Format::Json, Format::Yaml, Format::Toml, Format::Ron] {
+            assert_eq!(Format::sniff(&format.serialize(&character).unwrap()), *format);
+        }
+    }
+
+    #[test]
+    fn detect_prefers_the_extension() {
+        assert_eq!(Format::detect(Path::new("dandelion.JSON"), "name: Dandelion"), Format::Json);
+        assert_eq!(Format::detect(Path::new("dandelion.yml"), "{}"), Format::Yaml);
+        assert_eq!(Format::detect(Path::new("dandelion"), "name = \"Dandelion\""), Format::Toml);
+        assert_eq!(Format::detect(Path::new("dandelion.sheet"), "(name: \"Dandelion\")"), Format::Ron);
+    }
+}
@@ -0,0 +1,150 @@
+use std::error::Error;
+use std::fmt;
+
+use schemars::JsonSchema;
+
+use crate::character::effect::Modifier;
+use crate::character::recharge::{Recharge, RechargeOn};
+
+/// 5e allows a creature to be attuned to no more than three magic items at once.
+pub const MAX_ATTUNED_ITEMS: usize = 3;
+
+fn default_quantity() -> u16 { 1 }
+
+#[derive(Deserialize, Serialize, JsonSchema)]
+pub struct Charges {
+    pub max: u16,
+    pub current: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recharge: Option<Recharge>
+}
+
+#[derive(Deserialize, Serialize, JsonSchema)]
+pub struct Item {
+    pub name: String,
+    #[serde(default = "default_quantity")]
+    pub quantity: u16,
+    #[serde(default)]
+    pub requires_attunement: bool,
+    #[serde(default)]
+    pub attuned: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub charges: Option<Charges>,
+    /// What the item does while in use, e.g. +1 armor class for a Ring of Protection.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub modifiers: Vec<Modifier>
+}
+
+impl Item {
+
+    /// Items that require attunement only work while attuned.
+    pub fn in_use(&self) -> bool {
+        !self.requires_attunement || self.attuned
+    }
+
+    /// Recharges the item if its rule applies to `rest`, returning how many
+    /// charges were regained.
+    pub fn recharge(&mut self, rest: RechargeOn) -> u16 {
+        match &mut self.charges {
+            Some(Charges { max, current, recharge: Some(recharge) }) if recharge.applies_on(rest) => {
+                let regained = recharge.regain(*current, *max);
+                *current += regained;
+                regained
+            },
+            _ => 0
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ItemError {
+    NoSuchItem(String),
+    AttunementNotRequired(String),
+    AttunementLimitReached,
+    NoCharges(String),
+    NotEnoughCharges { item: String, remaining: u16 }
+}
+
+impl fmt::Display for ItemError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ItemError::NoSuchItem(name) =>
+                write!(f, "there is no \"{}\" in the inventory", name),
+            ItemError::AttunementNotRequired(name) =>
+                write!(f, "{} does not require attunement", name),
+            ItemError::AttunementLimitReached =>
+                write!(f, "already attuned to {} items", MAX_ATTUNED_ITEMS),
+            ItemError::NoCharges(name) =>
+                write!(f, "{} has no charges", name),
+            ItemError::NotEnoughCharges { item, remaining } =>
+                write!(f, "{} only has {} charges left", item, remaining)
+        }
+    }
+}
+
+impl Error for ItemError {}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use serde_json::json;
+
+    use super::*;
+    use crate::character::Character;
+
+    /// Dandelion attuned to nothing, with `count` rings that need attunement.
+    fn with_items(count: usize) -> Character {
+        let mut document: serde_json::Value = serde_json::from_str(include_str!("../../resources/characters/dandelion.json")).unwrap();
+        let items = document["inventory"].as_array_mut().unwrap();
+        for item in items.iter_mut() {
+            item["attuned"] = json!(false);
+        }
+        for index in 0..count {
+            items.push(json!({ "name": format!("Ring {}", index + 1), "requires_attunement": true }));
+        }
+
+        serde_json::from_value(document).unwrap()
+    }
+
+    #[test]
+    fn attunement_stops_at_three_items() {
+        let mut character = with_items(MAX_ATTUNED_ITEMS + 1);
+        for index in 1..=MAX_ATTUNED_ITEMS {
+            character.attune(&format!("Ring {}", index)).unwrap();
+        }
+
+        assert_eq!(character.attune("Ring 4"), Err(ItemError::AttunementLimitReached));
+        assert_eq!(character.get_attuned_count(), MAX_ATTUNED_ITEMS);
+
+        character.end_attunement("Ring 1").unwrap();
+        assert_eq!(character.attune("Ring 4"), Ok(()));
+    }
+
+    #[test]
+    fn attuning_again_is_not_another_item() {
+        let mut character = with_items(MAX_ATTUNED_ITEMS);
+        for _ in 0..=MAX_ATTUNED_ITEMS {
+            character.attune("Ring 1").unwrap();
+        }
+
+        assert_eq!(character.get_attuned_count(), 1);
+        assert_eq!(character.attune("Quarterstaff"), Err(ItemError::AttunementNotRequired("Quarterstaff".to_owned())));
+    }
+
+    #[test]
+    fn recharge_regains_charges_up_to_the_maximum() {
+        let mut wand = Item {
+            name: "Wand".to_owned(),
+            quantity: 1,
+            requires_attunement: false,
+            attuned: false,
+            charges: Some(Charges { max: 7, current: 5, recharge: Recharge::try_from("regains all at dawn".to_owned()).ok() }),
+            modifiers: Vec::new()
+        };
+
+        assert_eq!(wand.recharge(RechargeOn::ShortRest), 0);
+        assert_eq!(wand.recharge(RechargeOn::Dawn), 2);
+        assert_eq!(wand.charges.as_ref().map(|charges| charges.current), Some(7));
+    }
+}
@@ -0,0 +1,208 @@
+use std::convert::TryFrom;
+
+use serde_json::{json, Map, Value};
+
+use crate::character::stats::modifier;
+use crate::rules::slug;
+
+/// The version written by this build. Bump it together with a new entry in
+/// `MIGRATIONS` whenever the layout of `Character` changes.
+pub const CURRENT_SCHEMA_VERSION: u32 = 4;
+
+/// Files written before versioning existed have no `schema_version` at all.
+const UNVERSIONED_SCHEMA_VERSION: u32 = 1;
+
+/// Upgrades a document from `from` to `from + 1`, returning a line for each change.
+struct Migration {
+    from: u32,
+    apply: fn(&mut Map<String, Value>) -> Vec<String>
+}
+
+const MIGRATIONS: [Migration; 3] = [
+    Migration { from: 1, apply: add_schema_version },
+    Migration { from: 2, apply: spell_names_to_ids },
+    Migration { from: 3, apply: derive_initiative }
+];
+
+/// Version 1 -> 2: the version number itself becomes part of the file.
+fn add_schema_version(document: &mut Map<String, Value>) -> Vec<String> {
+    document.insert("schema_version".to_owned(), Value::from(2));
+
+    vec!["add `schema_version`".to_owned()]
+}
+
+/// Version 2 -> 3: spells are referenced by content pack ID, e.g. "Fire Bolt"
+/// becomes "spell:fire-bolt".
+fn spell_names_to_ids(document: &mut Map<String, Value>) -> Vec<String> {
+    document.insert("schema_version".to_owned(), Value::from(3));
+
+    let spells = match document.get_mut("spells").and_then(Value::as_array_mut) {
+        Some(spells) => spells,
+        None => return Vec::new()
+    };
+
+    let mut changes = Vec::new();
+    for spell in spells.iter_mut() {
+        if let Some(name) = spell.as_str().filter(|name| !name.contains(':')) {
+            let id = format!("spell:{}", slug(name));
+            changes.push(format!("spell `{}` -> `{}`", name, id));
+            *spell = Value::from(id);
+        }
+    }
+
+    changes
+}
+
+/// Version 3 -> 4: initiative is derived from the Dexterity modifier and
+/// effects. A stored initiative that differs, e.g. from the Alert feat, is
+/// kept as an effect.
+fn derive_initiative(document: &mut Map<String, Value>) -> Vec<String> {
+    document.insert("schema_version".to_owned(), Value::from(4));
+
+    let initiative = match document.remove("initiative").and_then(|initiative| initiative.as_i64()) {
+        Some(initiative) => initiative,
+        None => return Vec::new()
+    };
+    let dexterity = document.get("stats")
+        .and_then(|stats| stats.get("Dexterity"))
+        .and_then(Value::as_i64)
+        .and_then(|score| i16::try_from(score).ok())
+        .map(|score| i64::from(modifier(score)))
+        .unwrap_or(0);
+
+    if initiative == dexterity {
+        return vec!["remove `initiative`, now the Dexterity modifier".to_owned()];
+    }
+
+    let bonus = initiative.saturating_sub(dexterity).clamp(i64::from(i16::MIN), i64::from(i16::MAX));
+    let effect = json!({
+        "name": "Initiative bonus",
+        "modifiers": [{ "target": "initiative", "kind": "add", "value": bonus }],
+        "duration": "indefinite"
+    });
+    match document.entry("effects").or_insert_with(|| Value::Array(Vec::new())).as_array_mut() {
+        Some(effects) => effects.push(effect),
+        None => return vec!["remove `initiative`; `effects` is not a list, so its bonus is lost".to_owned()]
+    }
+
+    vec![format!("remove `initiative`, keeping its {:+} over the Dexterity modifier as an effect", bonus)]
+}
+
+pub fn schema_version(document: &Value) -> Result<u32, String> {
+    match document.get("schema_version") {
+        None => Ok(UNVERSIONED_SCHEMA_VERSION),
+        Some(version) => version.as_u64()
+            .map(|version| version as u32)
+            .ok_or_else(|| format!("`schema_version` must be a whole number, found {}", version))
+    }
+}
+
+/// Runs every migration needed to bring `document` up to `CURRENT_SCHEMA_VERSION`,
+/// returning a description of each change made, e.g. "1 -> 2: add `schema_version`".
+pub fn migrate(document: &mut Value) -> Result<Vec<String>, String> {
+    let mut version = schema_version(document)?;
+
+    if version > CURRENT_SCHEMA_VERSION {
+        return Err(format!(
+            "the file uses schema version {}, but this build only understands up to {}; please upgrade",
+            version, CURRENT_SCHEMA_VERSION));
+    }
+
+    let object = document.as_object_mut()
+        .ok_or_else(|| "a character file must be a JSON object".to_owned())?;
+
+    let mut changes = Vec::new();
+
+    while version < CURRENT_SCHEMA_VERSION {
+        let migration = MIGRATIONS.iter()
+            .find(|migration| migration.from == version)
+            .ok_or_else(|| format!("no migration from schema version {}", version))?;
+
+        changes.extend((migration.apply)(object).into_iter()
+            .map(|change| format!("{} -> {}: {}", version, version + 1, change)));
+
+        version += 1;
+    }
+
+    Ok(changes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn add_schema_version_writes_version_two() {
+        let mut document = json!({ "name": "Dandelion" });
+        let changes = add_schema_version(document.as_object_mut().unwrap());
+
+        assert_eq!(document["schema_version"], 2);
+        assert_eq!(changes, vec!["add `schema_version`"]);
+    }
+
+    #[test]
+    fn spell_names_to_ids_keeps_existing_ids() {
+        let mut document = json!({ "schema_version": 2, "spells": ["Fire Bolt", "spell:shield"] });
+        let changes = spell_names_to_ids(document.as_object_mut().unwrap());
+
+        assert_eq!(document["schema_version"], 3);
+        assert_eq!(document["spells"], json!(["spell:fire-bolt", "spell:shield"]));
+        assert_eq!(changes, vec!["spell `Fire Bolt` -> `spell:fire-bolt`"]);
+    }
+
+    #[test]
+    fn spell_names_to_ids_without_spells() {
+        let mut document = json!({ "schema_version": 2 });
+
+        assert!(spell_names_to_ids(document.as_object_mut().unwrap()).is_empty());
+        assert_eq!(document["schema_version"], 3);
+    }
+
+    #[test]
+    fn derive_initiative_drops_the_dexterity_modifier() {
+        let mut document = json!({ "schema_version": 3, "initiative": 2, "stats": { "Dexterity": 14 } });
+        let changes = derive_initiative(document.as_object_mut().unwrap());
+
+        assert_eq!(document["schema_version"], 4);
+        assert!(document.get("initiative").is_none());
+        assert!(document.get("effects").is_none());
+        assert_eq!(changes, vec!["remove `initiative`, now the Dexterity modifier"]);
+    }
+
+    #[test]
+    fn derive_initiative_keeps_a_bonus_as_an_effect() {
+        let mut document = json!({ "schema_version": 3, "initiative": 7, "stats": { "Dexterity": 14 }, "effects": [] });
+        derive_initiative(document.as_object_mut().unwrap());
+
+        assert!(document.get("initiative").is_none());
+        assert_eq!(document["effects"][0]["name"], "Initiative bonus");
+        assert_eq!(document["effects"][0]["modifiers"][0]["value"], 5);
+    }
+
+    #[test]
+    fn derive_initiative_keeps_the_bonus_in_range() {
+        let mut document = json!({ "schema_version": 3, "initiative": 32767, "stats": { "Dexterity": 1 } });
+        derive_initiative(document.as_object_mut().unwrap());
+
+        assert_eq!(document["effects"][0]["modifiers"][0]["value"], 32767);
+    }
+
+    #[test]
+    fn migrate_brings_unversioned_files_to_the_current_version() {
+        let mut document = json!({ "name": "Dandelion" });
+        let changes = migrate(&mut document).unwrap();
+
+        assert_eq!(schema_version(&document), Ok(CURRENT_SCHEMA_VERSION));
+        assert_eq!(changes[0], "1 -> 2: add `schema_version`");
+        assert!(migrate(&mut document).unwrap().is_empty());
+    }
+
+    #[test]
+    fn migrate_refuses_newer_files() {
+        let mut document = json!({ "schema_version": CURRENT_SCHEMA_VERSION + 1 });
+        assert!(migrate(&mut document).is_err());
+
+        let mut document = json!({ "schema_version": "two" });
+        assert!(migrate(&mut document).is_err());
+    }
+}
@@ -1,4 +1,40 @@
-pub use self::character::Character;
+pub use self::character::{Character, LevelAdvance, NewCharacter};
+pub use self::concentration::{concentration_save_dc, ConcentrationSave};
+pub use self::condition::{Condition, MAX_EXHAUSTION};
+pub use self::effect::{ActiveEffect, Breakdown, Duration, Modifier, Target, ROUNDS_PER_HOUR, ROUNDS_PER_MINUTE};
+pub use self::dice::{roll_d20, Dice, RollMode};
+pub use self::experience::experience_for_level;
+pub use self::feature::{Feature, FeatureSource, Uses};
+pub use self::item::{Item, MAX_ATTUNED_ITEMS};
+pub use self::stats::{modifier, AbilityRoll, Stat};
+pub use self::migration::CURRENT_SCHEMA_VERSION;
+pub use self::proficiencies::Proficiencies;
+pub use self::schema::character_schema;
+pub use self::skill::PASSIVE_SKILLS;
+pub use self::recharge::Recharge;
+pub use self::spell_slot::{ordinal, SpellSlots, MAX_SPELL_LEVEL};
+pub use self::format::Format;
+pub use self::storage::{load_character, save_character};
+pub use self::summary::Summary;
+pub use self::validation::{validate, Finding, Severity};
+#[allow(clippy::module_inception)]
 mod character;
 mod stats;
-mod skill;
\ No newline at end of file
+mod skill;
+mod concentration;
+mod condition;
+mod description;
+mod dice;
+mod effect;
+mod experience;
+mod feature;
+mod format;
+mod item;
+mod migration;
+mod proficiencies;
+mod recharge;
+mod schema;
+mod spell_slot;
+mod storage;
+mod summary;
+mod validation;
\ No newline at end of file
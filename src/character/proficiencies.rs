@@ -0,0 +1,61 @@
+use schemars::JsonSchema;
+
+/// What the character is trained with besides skills and saving throws, and
+/// the languages they speak. Each list is free text, e.g. "Light crossbows".
+#[derive(Clone, Default, Deserialize, Serialize, JsonSchema)]
+#[serde(default)]
+pub struct Proficiencies {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub armor: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub weapons: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tools: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub languages: Vec<String>
+}
+
+impl Proficiencies {
+
+    pub fn is_empty(&self) -> bool {
+        self.armor.is_empty() && self.weapons.is_empty() && self.tools.is_empty() && self.languages.is_empty()
+    }
+
+    /// Adds the entries of `other` that are not there yet, ignoring case, e.g.
+    /// the languages of a race and a background.
+    pub fn merge(&mut self, other: &Proficiencies) {
+        let lists = [
+            (&mut self.armor, &other.armor),
+            (&mut self.weapons, &other.weapons),
+            (&mut self.tools, &other.tools),
+            (&mut self.languages, &other.languages)
+        ];
+
+        for (list, additions) in lists {
+            for addition in additions {
+                if !list.iter().any(|entry| entry.eq_ignore_ascii_case(addition)) {
+                    list.push(addition.clone());
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merge_skips_entries_already_there() {
+        let mut proficiencies = Proficiencies { languages: vec!["Common".to_owned()], ..Proficiencies::default() };
+        proficiencies.merge(&Proficiencies {
+            languages: vec!["common".to_owned(), "Gnomish".to_owned()],
+            tools: vec!["Tinker's tools".to_owned()],
+            ..Proficiencies::default()
+        });
+
+        assert_eq!(proficiencies.languages, vec!["Common", "Gnomish"]);
+        assert_eq!(proficiencies.tools, vec!["Tinker's tools"]);
+        assert!(proficiencies.armor.is_empty());
+    }
+}
@@ -0,0 +1,190 @@
+use std::convert::TryFrom;
+use std::fmt;
+
+use schemars::gen::SchemaGenerator;
+use schemars::schema::{InstanceType, Metadata, Schema, SchemaObject, StringValidation};
+use schemars::JsonSchema;
+
+use crate::character::dice::Dice;
+
+/// The moment a limited resource gets its uses or charges back.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RechargeOn {
+    ShortRest,
+    LongRest,
+    Dawn
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RechargeAmount {
+    All,
+    Fixed(u16),
+    Roll(Dice)
+}
+
+/// A recharge rule written the way the books do, e.g. "regains 1d6+1 at dawn",
+/// "regains all on a long rest" or "regains 1 on a short rest".
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Recharge {
+    pub amount: RechargeAmount,
+    pub on: RechargeOn
+}
+
+impl Recharge {
+
+    /// Whether this rule applies after a rest. A long rest also covers short rest
+    /// and dawn recharges, since the party wakes up to a new day.
+    pub fn applies_on(&self, rest: RechargeOn) -> bool {
+        match rest {
+            RechargeOn::ShortRest => self.on == RechargeOn::ShortRest,
+            RechargeOn::LongRest | RechargeOn::Dawn => true
+        }
+    }
+
+    /// Returns how many uses are regained, without going over `max`.
+    pub fn regain(&self, current: u16, max: u16) -> u16 {
+        let missing = max.saturating_sub(current);
+
+        let rolled = match self.amount {
+            RechargeAmount::All => missing,
+            RechargeAmount::Fixed(amount) => amount,
+            RechargeAmount::Roll(dice) => dice.roll().max(0) as u16
+        };
+
+        rolled.min(missing)
+    }
+}
+
+impl JsonSchema for Recharge {
+    fn schema_name() -> String {
+        "Recharge".to_owned()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        SchemaObject {
+            instance_type: Some(InstanceType::String.into()),
+            string: Some(Box::new(StringValidation {
+                pattern: Some(r"^regains (all|\d+|\d*d\d+([+-]\d+)?) (at dawn|on a long rest|on a short rest)$".to_owned()),
+                ..Default::default()
+            })),
+            metadata: Some(Box::new(Metadata {
+                description: Some("When and how many uses come back".to_owned()),
+                examples: vec![
+                    "regains 1d6+1 at dawn".into(),
+                    "regains all on a long rest".into(),
+                    "regains 1 on a short rest".into()
+                ],
+                ..Default::default()
+            })),
+            ..Default::default()
+        }.into()
+    }
+}
+
+impl TryFrom<String> for Recharge {
+    type Error = String;
+
+    fn try_from(rule: String) -> Result<Recharge, String> {
+        let lowered = rule.trim().to_lowercase();
+        let rest = lowered.strip_prefix("regains ")
+            .ok_or_else(|| format!("recharge rule \"{}\" must start with \"regains\"", rule))?;
+
+        let (amount, on) = if let Some(amount) = rest.strip_suffix(" at dawn") {
+            (amount, RechargeOn::Dawn)
+        } else if let Some(amount) = rest.strip_suffix(" on a long rest") {
+            (amount, RechargeOn::LongRest)
+        } else if let Some(amount) = rest.strip_suffix(" on a short rest") {
+            (amount, RechargeOn::ShortRest)
+        } else {
+            return Err(format!(
+                "recharge rule \"{}\" must end with \"at dawn\", \"on a long rest\" or \"on a short rest\"",
+                rule));
+        };
+
+        let amount = match amount.trim() {
+            "all" => RechargeAmount::All,
+            amount if amount.contains('d') => RechargeAmount::Roll(Dice::parse(amount)?),
+            amount => RechargeAmount::Fixed(amount.parse::<u16>()
+                .map_err(|_| format!("invalid amount \"{}\" in recharge rule \"{}\"", amount, rule))?)
+        };
+
+        Ok(Recharge { amount, on })
+    }
+}
+
+impl From<Recharge> for String {
+    fn from(recharge: Recharge) -> String {
+        recharge.to_string()
+    }
+}
+
+impl fmt::Display for Recharge {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.amount {
+            RechargeAmount::All => write!(f, "regains all")?,
+            RechargeAmount::Fixed(amount) => write!(f, "regains {}", amount)?,
+            RechargeAmount::Roll(dice) => write!(f, "regains {}", dice)?
+        }
+
+        match self.on {
+            RechargeOn::ShortRest => write!(f, " on a short rest"),
+            RechargeOn::LongRest => write!(f, " on a long rest"),
+            RechargeOn::Dawn => write!(f, " at dawn")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(rule: &str) -> Result<Recharge, String> {
+        Recharge::try_from(rule.to_owned())
+    }
+
+    #[test]
+    fn parses_the_rules_the_books_use() {
+        assert_eq!(parse("regains 1d6+1 at dawn"), Ok(Recharge {
+            amount: RechargeAmount::Roll(Dice { count: 1, sides: 6, modifier: 1 }),
+            on: RechargeOn::Dawn
+        }));
+        assert_eq!(parse("regains all on a long rest"), Ok(Recharge { amount: RechargeAmount::All, on: RechargeOn::LongRest }));
+        assert_eq!(parse("regains 1 on a short rest"), Ok(Recharge { amount: RechargeAmount::Fixed(1), on: RechargeOn::ShortRest }));
+        assert_eq!(parse("  Regains ALL at Dawn "), Ok(Recharge { amount: RechargeAmount::All, on: RechargeOn::Dawn }));
+    }
+
+    #[test]
+    fn writes_rules_back_the_same_way() {
+        for rule in &["regains 1d6+1 at dawn", "regains all on a long rest", "regains 1 on a short rest"] {
+            assert_eq!(parse(rule).unwrap().to_string(), *rule);
+        }
+    }
+
+    #[test]
+    fn rejects_other_text() {
+        assert!(parse("1d6+1 at dawn").is_err());
+        assert!(parse("regains 1d6+1 at noon").is_err());
+        assert!(parse("regains some at dawn").is_err());
+        assert!(parse("regains -1 at dawn").is_err());
+        assert!(parse("regains 1d0 at dawn").is_err());
+    }
+
+    #[test]
+    fn regains_up_to_the_maximum() {
+        assert_eq!(parse("regains all on a long rest").unwrap().regain(2, 7), 5);
+        assert_eq!(parse("regains 3 on a short rest").unwrap().regain(6, 7), 1);
+
+        let regained = parse("regains 1d6+1 at dawn").unwrap().regain(0, 7);
+        assert!((2..=7).contains(&regained));
+    }
+
+    #[test]
+    fn a_long_rest_covers_every_recharge() {
+        let short = parse("regains 1 on a short rest").unwrap();
+        let dawn = parse("regains 1 at dawn").unwrap();
+
+        assert!(short.applies_on(RechargeOn::ShortRest) && short.applies_on(RechargeOn::LongRest));
+        assert!(!dawn.applies_on(RechargeOn::ShortRest) && dawn.applies_on(RechargeOn::LongRest));
+    }
+}
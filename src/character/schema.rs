@@ -0,0 +1,33 @@
+use schemars::gen::SchemaGenerator;
+use schemars::schema::{InstanceType, Schema, SchemaObject};
+
+use crate::character::Character;
+use crate::character::stats::Stat;
+
+/// `stats` is a map keyed by `Stat`; spell out every ability so editors can
+/// autocomplete the names and flag misspellings such as "Intelligence".
+pub fn stats_schema(gen: &mut SchemaGenerator) -> Schema {
+    let score = gen.subschema_for::<i16>();
+
+    let mut schema = SchemaObject {
+        instance_type: Some(InstanceType::Object.into()),
+        ..Default::default()
+    };
+
+    let object = schema.object();
+    for stat in Stat::ALL.iter() {
+        let name = format!("{:?}", stat);
+        object.properties.insert(name.clone(), score.clone());
+        object.required.insert(name);
+    }
+    object.additional_properties = Some(Box::new(Schema::Bool(false)));
+
+    schema.into()
+}
+
+/// The JSON Schema of a character file, pretty printed.
+pub fn character_schema() -> String {
+    let schema = schemars::schema_for!(Character);
+
+    serde_json::to_string_pretty(&schema).expect("a JSON schema is always serializable")
+}
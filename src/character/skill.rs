@@ -1,9 +1,35 @@
+use schemars::JsonSchema;
+
 use crate::character::stats;
 
-#[derive(Deserialize, Serialize)]
+#[derive(Deserialize, Serialize, JsonSchema)]
 pub struct Skill {
     pub has_proficiency: bool,
     pub stat: stats::Stat,
     pub name: String,
     pub bonus: i16
-}
\ No newline at end of file
+}
+/// The skills of the Player's Handbook and the ability each one uses.
+pub const STANDARD_SKILLS: [(&str, stats::Stat); 18] = [
+    ("Acrobatics", stats::Stat::Dexterity),
+    ("Animal Handling", stats::Stat::Wisdom),
+    ("Arcana", stats::Stat::Intellect),
+    ("Athletics", stats::Stat::Strength),
+    ("Deception", stats::Stat::Charisma),
+    ("History", stats::Stat::Intellect),
+    ("Insight", stats::Stat::Wisdom),
+    ("Intimidation", stats::Stat::Charisma),
+    ("Investigation", stats::Stat::Intellect),
+    ("Medicine", stats::Stat::Wisdom),
+    ("Nature", stats::Stat::Intellect),
+    ("Perception", stats::Stat::Wisdom),
+    ("Performance", stats::Stat::Charisma),
+    ("Persuasion", stats::Stat::Charisma),
+    ("Religion", stats::Stat::Intellect),
+    ("Sleight of Hand", stats::Stat::Dexterity),
+    ("Stealth", stats::Stat::Dexterity),
+    ("Survival", stats::Stat::Wisdom)
+];
+
+/// The skills whose passive scores are shown on the sheet.
+pub const PASSIVE_SKILLS: [&str; 3] = ["Perception", "Investigation", "Insight"];
@@ -0,0 +1,50 @@
+use std::error::Error;
+use std::fmt;
+
+use schemars::JsonSchema;
+
+/// The 9 spell levels, cantrips do not use slots.
+pub const MAX_SPELL_LEVEL: u8 = 9;
+
+/// Spell slots of one level, e.g. `{ "level": 1, "max": 4, "current": 3 }`.
+/// Every slot comes back on a long rest.
+#[derive(Deserialize, Serialize, JsonSchema)]
+pub struct SpellSlots {
+    pub level: u8,
+    pub max: u16,
+    pub current: u16
+}
+
+/// "1st", "2nd", "3rd", "4th", ...
+pub fn ordinal(level: u8) -> String {
+    let suffix = match level {
+        1 => "st",
+        2 => "nd",
+        3 => "rd",
+        _ => "th"
+    };
+
+    format!("{}{}", level, suffix)
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum SpellSlotError {
+    NoSlotsOfLevel(u8),
+    NoSlotsLeft(u8),
+    AllSlotsLeft(u8)
+}
+
+impl fmt::Display for SpellSlotError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SpellSlotError::NoSlotsOfLevel(level) =>
+                write!(f, "there are no {} level spell slots", ordinal(*level)),
+            SpellSlotError::NoSlotsLeft(level) =>
+                write!(f, "no {} level spell slots left", ordinal(*level)),
+            SpellSlotError::AllSlotsLeft(level) =>
+                write!(f, "every {} level spell slot is already available", ordinal(*level))
+        }
+    }
+}
+
+impl Error for SpellSlotError {}
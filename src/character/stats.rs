@@ -1,4 +1,8 @@
-#[derive(Eq, PartialEq, Hash, Deserialize, Serialize)]
+use schemars::JsonSchema;
+
+use crate::character::dice::Dice;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Deserialize, Serialize, JsonSchema)]
 pub enum Stat {
     Strength,
     Dexterity,
@@ -6,4 +10,59 @@ pub enum Stat {
     Intellect,
     Wisdom,
     Charisma
-}
\ No newline at end of file
+}
+
+impl Stat {
+
+    pub const ALL: [Stat; 6] = [
+        Stat::Strength, Stat::Dexterity, Stat::Constitution,
+        Stat::Intellect, Stat::Wisdom, Stat::Charisma
+    ];
+
+    pub fn abbreviation(&self) -> &'static str {
+        match self {
+            Stat::Strength => "STR",
+            Stat::Dexterity => "DEX",
+            Stat::Constitution => "CON",
+            Stat::Intellect => "INT",
+            Stat::Wisdom => "WIS",
+            Stat::Charisma => "CHA"
+        }
+    }
+
+    /// Reads a name typed by a user, either the abbreviation or the full name.
+    pub fn from_name(name: &str) -> Option<Stat> {
+        let name = name.trim().to_lowercase();
+
+        Stat::ALL.iter().copied().find(|stat| {
+            stat.abbreviation().eq_ignore_ascii_case(&name)
+                || format!("{:?}", stat).eq_ignore_ascii_case(&name)
+                || (*stat == Stat::Intellect && name == "intelligence")
+        })
+    }
+}
+
+/// The ability modifier for a score, e.g. 8 -> -1, 10 -> +0, 17 -> +3.
+pub fn modifier(score: i16) -> i16 {
+    score.saturating_sub(10).div_euclid(2)
+}
+
+/// One 4d6-drop-lowest roll made while generating ability scores, e.g.
+/// `{ "dice": [6, 5, 3, 2], "score": 14 }`.
+#[derive(Clone, Debug, Deserialize, Serialize, JsonSchema)]
+pub struct AbilityRoll {
+    pub dice: Vec<i16>,
+    pub score: i16
+}
+
+impl AbilityRoll {
+
+    /// Rolls four six-sided dice and keeps the three highest.
+    pub fn roll() -> AbilityRoll {
+        let mut dice = Dice { count: 4, sides: 6, modifier: 0 }.roll_each();
+        dice.sort_unstable_by(|a, b| b.cmp(a));
+        let score = dice.iter().take(3).sum();
+
+        AbilityRoll { dice, score }
+    }
+}
@@ -0,0 +1,198 @@
+use std::ffi::OsString;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+
+use crate::character::{Character, MAX_ATTUNED_ITEMS, MAX_EXHAUSTION};
+use crate::character::format::Format;
+use crate::character::migration::{self, CURRENT_SCHEMA_VERSION};
+use crate::error::SheetError;
+
+pub struct LoadedCharacter {
+    pub character: Character,
+    pub format: Format,
+    /// What was upgraded to bring an older file up to the current schema.
+    pub migrations: Vec<String>
+}
+
+/// 1-based line and column of a byte offset, for parsers that only report spans.
+fn line_and_column(text: &str, offset: usize) -> (usize, usize) {
+    let before = &text[..offset.min(text.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.rfind('\n').map(|newline| offset - newline).unwrap_or(offset + 1);
+
+    (line, column)
+}
+
+/// Parses JSON, YAML or TOML into a generic document that migrations can work on.
+fn parse_document(path: &Path, text: &str, format: Format) -> Result<Value, SheetError> {
+    match format {
+        Format::Json => {
+            let deserializer = &mut serde_json::Deserializer::from_str(text);
+            serde_path_to_error::deserialize(deserializer)
+                .map_err(|e| SheetError::parse(path, e))
+        },
+        Format::Yaml => serde_yaml::from_str(text).map_err(|e| {
+            let (line, column) = e.location().map(|location| (location.line(), location.column())).unwrap_or((0, 0));
+            SheetError::parse_at(path, "?".to_owned(), e.to_string(), line, column)
+        }),
+        Format::Toml => toml::from_str(text).map_err(|e| {
+            let (line, column) = e.span().map(|span| line_and_column(text, span.start)).unwrap_or((0, 0));
+            SheetError::parse_at(path, "?".to_owned(), e.message().trim().replace('\n', "; "), line, column)
+        }),
+        Format::Ron => unreachable!("RON files are deserialized directly")
+    }
+}
+
+/// RON writes enums as bare identifiers, which a generic document cannot hold,
+/// so RON files skip the migration step and must already be current.
+fn load_ron(path: &Path, text: &str) -> Result<Character, SheetError> {
+    let deserializer = &mut ron::Deserializer::from_str(text).map_err(|e|
+        SheetError::parse_at(path, "?".to_owned(), e.code.to_string(), e.position.line, e.position.col))?;
+
+    let character: Character = serde_path_to_error::deserialize(deserializer).map_err(|e| {
+        let field = e.path().to_string();
+        SheetError::parse_at(path, field, e.into_inner().to_string(), 0, 0)
+    })?;
+
+    if *character.get_schema_version() != CURRENT_SCHEMA_VERSION {
+        return Err(SheetError::validation(path, "schema_version", format!(
+            "RON files cannot be migrated; convert this file to JSON with a build that understands schema version {}",
+            character.get_schema_version())));
+    }
+
+    Ok(character)
+}
+
+/// Loads a character in any supported format, upgrading older files in memory.
+pub fn load_character(path: &Path) -> Result<LoadedCharacter, SheetError> {
+    let text = fs::read_to_string(path)
+        .map_err(|e| SheetError::io(path, e))?;
+
+    let format = Format::detect(path, &text);
+
+    let (character, migrations) = if format == Format::Ron {
+        (load_ron(path, &text)?, Vec::new())
+    } else {
+        let mut document = parse_document(path, &text, format)?;
+
+        let migrations = migration::migrate(&mut document)
+            .map_err(|message| SheetError::validation(path, "schema_version", message))?;
+
+        // Up to date JSON is parsed from the text again so errors keep their line and column.
+        let character: Character = if format == Format::Json && migrations.is_empty() {
+            let deserializer = &mut serde_json::Deserializer::from_str(&text);
+            serde_path_to_error::deserialize(deserializer)
+        } else {
+            serde_path_to_error::deserialize(document)
+        }.map_err(|e| SheetError::parse(path, e))?;
+
+        (character, migrations)
+    };
+
+    check_limits(&character, path)?;
+
+    Ok(LoadedCharacter { character, format, migrations })
+}
+
+/// Rejects values the sheet cannot represent, even though they parse fine.
+fn check_limits(character: &Character, path: &Path) -> Result<(), SheetError> {
+    if *character.get_exhaustion() > MAX_EXHAUSTION {
+        return Err(SheetError::validation(path, "exhaustion",
+            format!("exhaustion goes from 0 to {}, found {}", MAX_EXHAUSTION, character.get_exhaustion())));
+    }
+
+    if character.get_attuned_count() > MAX_ATTUNED_ITEMS {
+        return Err(SheetError::validation(path, "inventory",
+            format!("at most {} items can be attuned, found {}", MAX_ATTUNED_ITEMS, character.get_attuned_count())));
+    }
+
+    Ok(())
+}
+
+/// `dandelion.json` -> `dandelion.json.<suffix>`, next to the original file.
+fn sibling_path(path: &Path, suffix: &str) -> PathBuf {
+    let mut file_name = path.file_name().map(OsString::from).unwrap_or_default();
+    file_name.push(".");
+    file_name.push(suffix);
+
+    path.with_file_name(file_name)
+}
+
+pub fn backup_path(path: &Path) -> PathBuf {
+    sibling_path(path, "bak")
+}
+
+/// The format to write `path` in: its extension, else what the file on disk
+/// already looks like, else JSON.
+fn save_format(path: &Path) -> Format {
+    Format::from_extension(path)
+        .or_else(|| fs::read_to_string(path).ok().map(|text| Format::sniff(&text)))
+        .unwrap_or(Format::Json)
+}
+
+/// Writes the character atomically: the new contents go to a temporary file
+/// which is then renamed over the original, so a crash never leaves a half
+/// written sheet behind. The previous version is kept as `<file>.bak`.
+pub fn save_character(character: &Character, path: &Path) -> Result<(), io::Error> {
+    let text = save_format(path).serialize(character)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+    let temp_path = sibling_path(path, "tmp");
+    {
+        let mut temp_file = fs::File::create(&temp_path)?;
+        temp_file.write_all(text.as_bytes())?;
+        if !text.ends_with('\n') {
+            temp_file.write_all(b"\n")?;
+        }
+        temp_file.sync_all()?;
+    }
+
+    if path.exists() {
+        fs::copy(path, backup_path(path))?;
+    }
+
+    fs::rename(&temp_path, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DANDELION: &str = include_str!("../../resources/characters/dandelion.json");
+
+    #[test]
+    fn save_character_keeps_a_backup_and_no_temporary_file() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("dandelion.json");
+        fs::write(&path, DANDELION).unwrap();
+
+        let mut character = load_character(&path).unwrap().character;
+        let before = *character.get_current_hitpoints();
+        character.take_damage(3);
+        save_character(&character, &path).unwrap();
+
+        assert_eq!(fs::read_to_string(backup_path(&path)).unwrap(), DANDELION);
+        assert!(!sibling_path(&path, "tmp").exists());
+
+        let reloaded = load_character(&path).unwrap();
+        assert_eq!(*reloaded.character.get_current_hitpoints(), before - 3);
+        assert_eq!(reloaded.character.get_name(), character.get_name());
+        assert!(reloaded.migrations.is_empty());
+    }
+
+    #[test]
+    fn save_character_without_a_previous_file() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("dandelion.json");
+        let character: Character = serde_json::from_str(DANDELION).unwrap();
+
+        save_character(&character, &path).unwrap();
+
+        assert!(!backup_path(&path).exists());
+        assert!(!sibling_path(&path, "tmp").exists());
+        assert_eq!(load_character(&path).unwrap().character.get_name(), character.get_name());
+    }
+}
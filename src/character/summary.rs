@@ -0,0 +1,186 @@
+use crate::character::{ActiveEffect, Character, Condition, Proficiencies, RollMode, Stat, Target, PASSIVE_SKILLS};
+use crate::character::effect::ModifierKind;
+
+/// Everything the sheet computes from a character, for `--format json`:
+/// the stored values plus modifiers, totals, passive scores and spell DC.
+#[derive(Serialize)]
+pub struct Summary<'a> {
+    name: &'a str,
+    race: &'a str,
+    class: &'a str,
+    level: u16,
+    experience: Experience,
+    hit_points: HitPoints,
+    armor_class: u16,
+    initiative: i16,
+    proficiency_bonus: u16,
+    speed: u16,
+    /// Darkvision range in feet; 0 without it.
+    darkvision: u16,
+    abilities: Vec<Ability>,
+    /// Saving throw modifiers that only apply against something, e.g. magic.
+    save_notes: Vec<String>,
+    skills: Vec<Skill<'a>>,
+    passive_scores: Vec<PassiveScore<'a>>,
+    proficiencies: &'a Proficiencies,
+    spellcasting: Option<Spellcasting>,
+    spell_slots: Vec<SpellSlots>,
+    /// The IDs of the prepared spells; empty for classes that know their spells.
+    prepared_spells: &'a [String],
+    /// The ID of the spell being concentrated on.
+    concentration: Option<&'a str>,
+    /// Spells and other effects that last a while, with the time left.
+    effects: &'a [ActiveEffect],
+    /// Damage types that deal half damage, e.g. "poison".
+    resistances: Vec<String>,
+    conditions: &'a [Condition],
+    exhaustion: u8,
+    attack_roll_mode: RollMode
+}
+
+#[derive(Serialize)]
+struct Experience {
+    points: u32,
+    milestone: bool,
+    /// Where the next level starts; `None` at level 20 or with milestones.
+    next_level: Option<u32>,
+    level_up_available: bool
+}
+
+#[derive(Serialize)]
+struct HitPoints {
+    current: i16,
+    max: u16,
+    /// The maximum after exhaustion.
+    effective_max: u16
+}
+
+#[derive(Serialize)]
+struct Ability {
+    ability: Stat,
+    score: Option<i16>,
+    modifier: Option<i16>,
+    save: Option<i16>,
+    save_proficient: bool,
+    save_mode: RollMode,
+    save_fails: bool
+}
+
+#[derive(Serialize)]
+struct Skill<'a> {
+    name: &'a str,
+    ability: Stat,
+    proficient: bool,
+    total: i16,
+    mode: RollMode
+}
+
+#[derive(Serialize)]
+struct PassiveScore<'a> {
+    skill: &'a str,
+    score: i16
+}
+
+#[derive(Serialize)]
+struct Spellcasting {
+    ability: Stat,
+    modifier: Option<i16>,
+    attack_bonus: Option<i16>,
+    save_dc: Option<i16>
+}
+
+#[derive(Serialize)]
+struct SpellSlots {
+    level: u8,
+    max: u16,
+    current: u16
+}
+
+impl<'a> Summary<'a> {
+
+    pub fn new(character: &'a Character) -> Summary<'a> {
+        let abilities = Stat::ALL.iter()
+            .map(|stat| Ability {
+                ability: *stat,
+                score: character.get_stat(stat),
+                modifier: character.get_modifier(stat),
+                save: character.get_saving_throw_bonus(stat),
+                save_proficient: character.has_saving_throw_proficiency(stat),
+                save_mode: character.saving_throw_mode(stat),
+                save_fails: character.fails_saving_throw(stat)
+            })
+            .collect();
+
+        let skills = character.get_skills().iter()
+            .map(|skill| Skill {
+                name: &skill.name,
+                ability: skill.stat,
+                proficient: skill.has_proficiency,
+                total: character.get_skill_bonus(skill),
+                mode: character.skill_check_mode(skill)
+            })
+            .collect();
+
+        let passive_scores = PASSIVE_SKILLS.iter()
+            .filter_map(|skill| Some(PassiveScore { skill, score: character.get_passive_score(skill)? }))
+            .collect();
+
+        let spellcasting = character.get_spellcasting_ability().map(|ability| Spellcasting {
+            ability,
+            modifier: character.get_modifier(&ability),
+            attack_bonus: character.get_spell_attack_bonus(),
+            save_dc: character.get_spell_save_dc()
+        });
+
+        let mut resistances: Vec<String> = character.get_modifiers().into_iter()
+            .filter(|contribution| contribution.modifier.kind == ModifierKind::Resistance && contribution.modifier.against.is_none())
+            .filter_map(|contribution| match contribution.modifier.target {
+                Target::Damage(kind) => Some(kind.to_lowercase()),
+                _ => None
+            })
+            .collect();
+        resistances.sort();
+        resistances.dedup();
+
+        let spell_slots = character.get_spell_slots().iter()
+            .map(|slots| SpellSlots { level: slots.level, max: slots.max, current: slots.current })
+            .collect();
+
+        Summary {
+            name: character.get_name(),
+            race: character.get_race(),
+            class: character.get_class(),
+            level: *character.get_level(),
+            experience: Experience {
+                points: *character.get_experience_points(),
+                milestone: character.uses_milestones(),
+                next_level: character.get_experience_for_next_level(),
+                level_up_available: character.can_level_up()
+            },
+            hit_points: HitPoints {
+                current: *character.get_current_hitpoints(),
+                max: *character.get_max_hitpoints(),
+                effective_max: character.get_effective_max_hitpoints()
+            },
+            armor_class: character.get_armor_class(),
+            initiative: character.get_initiative(),
+            proficiency_bonus: *character.get_proficiency_bonus(),
+            speed: character.get_speed(),
+            darkvision: character.get_darkvision(),
+            abilities,
+            save_notes: character.conditional_save_notes(),
+            skills,
+            passive_scores,
+            proficiencies: character.get_proficiencies(),
+            spellcasting,
+            spell_slots,
+            prepared_spells: character.get_prepared_spells(),
+            concentration: character.get_concentration(),
+            effects: character.get_effects(),
+            resistances,
+            conditions: character.get_conditions(),
+            exhaustion: *character.get_exhaustion(),
+            attack_roll_mode: character.attack_roll_mode()
+        }
+    }
+}
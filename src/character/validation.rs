@@ -0,0 +1,290 @@
+use std::collections::HashSet;
+use std::convert::TryFrom;
+use std::fmt;
+
+use crate::character::{Character, Target};
+use crate::character::skill::STANDARD_SKILLS;
+use crate::character::spell_slot::MAX_SPELL_LEVEL;
+use crate::character::stats::{self, Stat};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning
+}
+
+/// One problem found on a character. `rule` is a stable ID so findings can be
+/// searched for and talked about, e.g. "HP001".
+#[derive(Serialize)]
+pub struct Finding {
+    pub rule: &'static str,
+    pub severity: Severity,
+    pub field: String,
+    pub message: String
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning"
+        };
+
+        write!(f, "{}[{}] {}: {}", severity, self.rule, self.field, self.message)
+    }
+}
+
+/// Proficiency bonus by character level: +2 at 1st, +1 every four levels.
+pub fn proficiency_bonus_for_level(level: u16) -> u16 {
+    2 + (level.max(1) - 1) / 4
+}
+
+struct Findings(Vec<Finding>);
+
+impl Findings {
+    fn error(&mut self, rule: &'static str, field: &str, message: String) {
+        self.0.push(Finding { rule, severity: Severity::Error, field: field.to_owned(), message });
+    }
+
+    fn warning(&mut self, rule: &'static str, field: &str, message: String) {
+        self.0.push(Finding { rule, severity: Severity::Warning, field: field.to_owned(), message });
+    }
+}
+
+/// Checks a character for values that parse fine but make no sense together.
+/// Errors are contradictions, warnings are things that are probably mistakes.
+pub fn validate(character: &Character) -> Vec<Finding> {
+    let mut findings = Findings(Vec::new());
+
+    check_level(character, &mut findings);
+    check_hitpoints(character, &mut findings);
+    check_stats(character, &mut findings);
+    check_skills(character, &mut findings);
+    check_resources(character, &mut findings);
+    check_modifiers(character, &mut findings);
+
+    findings.0
+}
+
+fn check_level(character: &Character, findings: &mut Findings) {
+    let level = *character.get_level();
+
+    if !(1..=20).contains(&level) {
+        findings.error("LVL001", "level", format!("level must be between 1 and 20, found {}", level));
+    }
+
+    let expected = proficiency_bonus_for_level(level);
+    if *character.get_proficiency_bonus() != expected {
+        findings.warning("PROF001", "proficiency_bonus", format!(
+            "a level {} character has a proficiency bonus of +{}, found +{}",
+            level, expected, character.get_proficiency_bonus()));
+    }
+}
+
+fn check_hitpoints(character: &Character, findings: &mut Findings) {
+    let current = *character.get_current_hitpoints();
+    let max = *character.get_max_hitpoints();
+
+    if max == 0 {
+        findings.error("HP001", "max_hitpoints", "maximum hit points must be above 0".to_owned());
+    }
+
+    if current > i16::try_from(max).unwrap_or(i16::MAX) {
+        findings.error("HP002", "current_hitpoints", format!(
+            "current hit points ({}) are above the maximum ({})", current, max));
+    }
+}
+
+fn check_stats(character: &Character, findings: &mut Findings) {
+    for stat in Stat::ALL.iter() {
+        match character.get_base_stat(stat) {
+            None => findings.error("STAT001", "stats", format!("{:?} is missing", stat)),
+            Some(score) if !(1..=30).contains(&score) =>
+                findings.error("STAT002", "stats", format!("{:?} must be between 1 and 30, found {}", stat, score)),
+            Some(_) => {}
+        }
+    }
+}
+
+fn check_skills(character: &Character, findings: &mut Findings) {
+    let mut seen = HashSet::new();
+
+    for (index, skill) in character.get_skills().iter().enumerate() {
+        let field = format!("skills[{}]", index);
+
+        if !seen.insert(skill.name.to_lowercase()) {
+            findings.error("SKILL001", &field, format!("{} is listed more than once", skill.name));
+        }
+
+        match STANDARD_SKILLS.iter().find(|(name, _)| name.eq_ignore_ascii_case(&skill.name)) {
+            None => findings.warning("SKILL002", &field, format!("{} is not a standard skill", skill.name)),
+            Some((name, stat)) if *stat != skill.stat => findings.warning("SKILL003", &field, format!(
+                "{} uses {:?}, found {:?}", name, stat, skill.stat)),
+            Some(_) => {}
+        }
+
+        if let Some(score) = character.get_base_stat(&skill.stat) {
+            let proficiency = if skill.has_proficiency { *character.get_proficiency_bonus() as i16 } else { 0 };
+            let expected = stats::modifier(score) + proficiency;
+
+            if skill.bonus != expected {
+                findings.warning("SKILL004", &field, format!(
+                    "{} bonus should be {:+} ({} modifier{}), found {:+}",
+                    skill.name, expected, skill.stat.abbreviation(),
+                    if skill.has_proficiency { " + proficiency" } else { "" }, skill.bonus));
+            }
+        }
+    }
+
+    for (name, _) in STANDARD_SKILLS.iter() {
+        if !seen.contains(&name.to_lowercase()) {
+            findings.warning("SKILL005", "skills", format!("{} is missing", name));
+        }
+    }
+}
+
+fn check_resources(character: &Character, findings: &mut Findings) {
+    for (index, item) in character.get_inventory().iter().enumerate() {
+        let field = format!("inventory[{}]", index);
+
+        if item.attuned && !item.requires_attunement {
+            findings.warning("ITEM001", &field, format!("{} is attuned but does not require attunement", item.name));
+        }
+
+        if let Some(charges) = &item.charges {
+            if charges.current > charges.max {
+                findings.error("ITEM002", &field, format!(
+                    "{} has {} charges but holds at most {}", item.name, charges.current, charges.max));
+            }
+        }
+    }
+
+    for (index, feature) in character.get_features().iter().enumerate() {
+        if let Some(uses) = &feature.uses {
+            if uses.current > uses.max {
+                findings.error("FEAT001", &format!("features[{}]", index), format!(
+                    "{} has {} uses but at most {}", feature.name, uses.current, uses.max));
+            }
+        }
+    }
+
+    let mut seen_levels = HashSet::new();
+    for (index, slots) in character.get_spell_slots().iter().enumerate() {
+        let field = format!("spell_slots[{}]", index);
+
+        if !(1..=MAX_SPELL_LEVEL).contains(&slots.level) {
+            findings.error("SLOT001", &field, format!(
+                "spell slot levels go from 1 to {}, found {}", MAX_SPELL_LEVEL, slots.level));
+        } else if !seen_levels.insert(slots.level) {
+            findings.error("SLOT002", &field, format!("level {} spell slots are listed more than once", slots.level));
+        }
+
+        if slots.current > slots.max {
+            findings.error("SLOT003", &field, format!(
+                "{} of {} level spell slots are available", slots.current, slots.max));
+        }
+    }
+}
+
+/// Modifiers on a skill the sheet does not have never apply, e.g. a typo in "Stealth".
+fn check_modifiers(character: &Character, findings: &mut Findings) {
+    for contribution in character.get_modifiers() {
+        if let Target::Skill(name) = &contribution.modifier.target {
+            if character.find_skill(name).is_none() {
+                findings.warning("EFF001", "modifiers", format!(
+                    "{} changes {}, which is not a skill on the sheet", contribution.source, name));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+
+    /// The rules and severities `validate` finds on Dandelion after `change`.
+    fn findings(change: impl FnOnce(&mut Value)) -> Vec<(&'static str, Severity)> {
+        let mut document: Value = serde_json::from_str(include_str!("../../resources/characters/dandelion.json")).unwrap();
+        change(&mut document);
+        let character: Character = serde_json::from_value(document).unwrap();
+
+        validate(&character).into_iter().map(|finding| (finding.rule, finding.severity)).collect()
+    }
+
+    fn finds(change: impl FnOnce(&mut Value), rule: &'static str, severity: Severity) {
+        let found = findings(change);
+        assert!(found.contains(&(rule, severity)), "expected {} {:?}, found {:?}", rule, severity, found);
+    }
+
+    #[test]
+    fn dandelion_is_valid() {
+        assert!(findings(|_| {}).is_empty());
+    }
+
+    #[test]
+    fn proficiency_bonus_follows_the_level() {
+        let table: Vec<u16> = [1, 4, 5, 8, 9, 12, 13, 16, 17, 20].iter().map(|level| proficiency_bonus_for_level(*level)).collect();
+        assert_eq!(table, vec![2, 2, 3, 3, 4, 4, 5, 5, 6, 6]);
+        assert_eq!(proficiency_bonus_for_level(0), 2);
+    }
+
+    #[test]
+    fn level_and_proficiency_bonus() {
+        finds(|sheet| sheet["level"] = json!(0), "LVL001", Severity::Error);
+        finds(|sheet| sheet["level"] = json!(21), "LVL001", Severity::Error);
+        assert_eq!(findings(|sheet| sheet["level"] = json!(5)), vec![("PROF001", Severity::Warning)]);
+        assert!(!findings(|sheet| { sheet["level"] = json!(5); sheet["proficiency_bonus"] = json!(3) })
+            .contains(&("PROF001", Severity::Warning)));
+    }
+
+    #[test]
+    fn hit_points() {
+        finds(|sheet| sheet["max_hitpoints"] = json!(0), "HP001", Severity::Error);
+        assert_eq!(findings(|sheet| sheet["current_hitpoints"] = json!(18)), vec![("HP002", Severity::Error)]);
+        assert!(findings(|sheet| sheet["current_hitpoints"] = json!(17)).is_empty());
+        assert!(findings(|sheet| { sheet["max_hitpoints"] = json!(40000); sheet["current_hitpoints"] = json!(5) }).is_empty());
+    }
+
+    #[test]
+    fn stats() {
+        finds(|sheet| { sheet["stats"].as_object_mut().unwrap().remove("Wisdom"); }, "STAT001", Severity::Error);
+        finds(|sheet| sheet["stats"]["Strength"] = json!(31), "STAT002", Severity::Error);
+        finds(|sheet| sheet["stats"]["Strength"] = json!(0), "STAT002", Severity::Error);
+    }
+
+    #[test]
+    fn skills() {
+        finds(|sheet| {
+            let first = sheet["skills"][0].clone();
+            sheet["skills"].as_array_mut().unwrap().push(first);
+        }, "SKILL001", Severity::Error);
+        finds(|sheet| sheet["skills"][0]["name"] = json!("Basket Weaving"), "SKILL002", Severity::Warning);
+        finds(|sheet| sheet["skills"][0]["stat"] = json!("Charisma"), "SKILL003", Severity::Warning);
+        finds(|sheet| sheet["skills"][0]["bonus"] = json!(9), "SKILL004", Severity::Warning);
+        finds(|sheet| { sheet["skills"].as_array_mut().unwrap().remove(0); }, "SKILL005", Severity::Warning);
+    }
+
+    #[test]
+    fn items_features_and_spell_slots() {
+        finds(|sheet| sheet["inventory"].as_array_mut().unwrap().push(json!({ "name": "Ring", "attuned": true })),
+            "ITEM001", Severity::Warning);
+        finds(|sheet| sheet["inventory"].as_array_mut().unwrap().push(json!({ "name": "Wand", "charges": { "max": 3, "current": 4 } })),
+            "ITEM002", Severity::Error);
+        finds(|sheet| sheet["features"].as_array_mut().unwrap().push(json!({
+            "name": "Lucky", "source": "Feat", "uses": { "max": 3, "current": 4, "recharge": "regains all on a long rest" }
+        })), "FEAT001", Severity::Error);
+        finds(|sheet| sheet["spell_slots"][0]["level"] = json!(10), "SLOT001", Severity::Error);
+        finds(|sheet| sheet["spell_slots"][1]["level"] = json!(1), "SLOT002", Severity::Error);
+        finds(|sheet| sheet["spell_slots"][0]["current"] = json!(5), "SLOT003", Severity::Error);
+    }
+
+    #[test]
+    fn modifiers_on_missing_skills() {
+        finds(|sheet| sheet["features"].as_array_mut().unwrap().push(json!({
+            "name": "Sneaky", "source": "Feat", "modifiers": [{ "target": { "skill": "Stelth" }, "kind": "add", "value": 1 }]
+        })), "EFF001", Severity::Warning);
+    }
+}
@@ -0,0 +1,702 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+use crate::character::{RollMode, Stat, MAX_SPELL_LEVEL, ROUNDS_PER_HOUR, ROUNDS_PER_MINUTE};
+use crate::rules::{parse_ability_increases, HitPointGain};
+
+const USAGE: &str = "\
+Usage: terminal_test_1 [--library <dir>] [--content <dir>]... <command> [options]
+       terminal_test_1 <character>        same as `show <character>`
+       terminal_test_1                    choose a character from the library
+
+A <character> or <file> is a path, or the file name or character name of a
+file in the library, e.g. `dandelion`. The library is --library <dir>, else
+$SHEET_LIBRARY, else $XDG_DATA_HOME/terminal_test_1/characters
+(~/.local/share/terminal_test_1/characters).
+
+Races, classes, feats, items and spells come from the built-in SRD, with the
+JSON and YAML files of content packs layered on top: every --content <dir> in
+order, else the directories in $SHEET_CONTENT, then the pack of the
+character, e.g. dandelion.content/ next to dandelion.json. Entries of later
+packs replace earlier entries with the same ID, e.g. `spell:fire-bolt`.
+
+Commands:
+  list       List the characters in the library
+  show       Open the character sheet
+  roll       Roll a check, a save or dice for a character
+  validate   Check a character file against the rules
+  export     Write a character in another file format
+  new        Create a new level 1 character file
+  level-up   Gain a level
+  damage     Take damage
+  heal       Regain hit points
+  rest       Take a short or long rest
+  slot       Use or restore a spell slot
+  cast       Cast a spell
+  prepare    Prepare spells for the day
+  unprepare  Stop preparing spells
+  time       Let rounds, minutes or hours pass
+  explain    Show where a number on the sheet comes from
+  xp         Award experience points
+  milestone  Switch between milestone and experience levelling
+  content    Show which pack every rules entry comes from
+  migrate    Upgrade a character file to the current schema
+  schema     Print the JSON Schema of character files
+
+Run `terminal_test_1 <command> --help` for the options of a command.
+
+Exit codes:
+  0   success
+  1   the sheet could not be shown or a command failed
+  2   invalid command line
+  65  the character file is invalid
+  74  a file could not be read or written";
+
+const LIST_USAGE: &str = "\
+Usage: terminal_test_1 list [--format <text | json>]
+
+Lists the name, race, class and level of every character in the library.
+
+Options:
+  --format json   print the list as JSON";
+
+const SHOW_USAGE: &str = "\
+Usage: terminal_test_1 show [<file>] [--autosave] [--format <text | json>]
+
+Opens the character sheet. Ctrl-S saves, q quits. Without <file> the
+characters in the library are listed to choose from.
+
+Options:
+  --autosave        save after every change
+  --format json     print every computed value (modifiers, saves, skill
+                    totals, passive scores, spell DC) instead";
+
+const ROLL_USAGE: &str = "\
+Usage: terminal_test_1 roll <file> <check> [--advantage | --disadvantage] [--format <text | json>]
+
+<check> is one of:
+  a skill         stealth, sleight-of-hand
+  an ability      dex, intelligence
+  a saving throw  dex-save, wisdom-save
+  dice            2d6+3
+
+Conditions and exhaustion on the character are applied to d20 rolls.
+
+Options:
+  --advantage      roll the d20 twice and keep the higher die
+  --disadvantage   roll the d20 twice and keep the lower die
+  --format json    print the roll as JSON";
+
+const VALIDATE_USAGE: &str = "\
+Usage: terminal_test_1 validate <file> [--format <text | json>]
+
+Prints every problem found with rule IDs. Exits with 65 if any is an error.
+
+Options:
+  --format json   print the findings as JSON";
+
+const EXPORT_USAGE: &str = "\
+Usage: terminal_test_1 export <file> <output>
+
+Writes the character to <output> in the format of its extension:
+.json, .yaml, .toml or .ron. `convert` is an alias.";
+
+/// The most level 1 hit points `new --hp` takes, far above any hit die.
+const MAX_NEW_HITPOINTS: u16 = 99;
+
+const NEW_USAGE: &str = "\
+Usage: terminal_test_1 new <file>
+       terminal_test_1 new <file> --name <name> [--race <race>] [--class <class>] [--hp <hit points>]
+
+Without --name, a guided wizard walks through race, class, background,
+ability scores, skills, equipment and spells, then opens the new sheet.
+With --name, creates a level 1 character with every ability at 10 and
+<hit points> (8 by default, at most 99) before the Constitution modifier.
+
+Refuses to overwrite an existing file. A bare name such as `dandelion`
+creates dandelion.json in the library.";
+
+const LEVEL_UP_USAGE: &str = "\
+Usage: terminal_test_1 level-up <file> [--roll | --hp <hit points>]
+           [--asi <ability>[,<ability>] | --feat <name>] [--spells <spell>,...]
+
+Increases the class level by one. Hit points go up by the average of the
+class hit die plus the Constitution modifier, by a roll of the die with
+--roll, or by exactly <hit points> with --hp. The proficiency bonus, class
+features and spell slots follow the class progression in the rules.
+
+Levels with an Ability Score Improvement take +2 to one ability
+(`--asi STR`), +1 to two (`--asi STR,DEX`) or a feat (`--feat Alert`).
+Classes that learn spells pick them with --spells, e.g.
+`--spells \"Shield,Sleep\"`. Choices left out are asked for when running in
+a terminal.";
+
+const DAMAGE_USAGE: &str = "\
+Usage: terminal_test_1 damage <amount> <file>... [--type <damage type>]
+
+Every character takes <amount> damage, e.g. `damage 12 party/*.json`.
+Hit points stop at 0, where the character falls unconscious. Characters
+concentrating on a spell roll a Constitution save with a DC of half the
+damage, at least 10, and stop concentrating on a failure. Each file is saved
+and its new state printed.
+
+Options:
+  --type <damage type>   e.g. `fire`; characters resistant to it take half";
+
+const HEAL_USAGE: &str = "\
+Usage: terminal_test_1 heal <amount> <file>...
+
+Every character regains <amount> hit points, up to the maximum reduced by
+exhaustion. Each file is saved and its new state printed.";
+
+const REST_USAGE: &str = "\
+Usage: terminal_test_1 rest <short | long> <file>...
+
+Recharges items and features; a long rest also restores hit points and spell
+slots and removes a level of exhaustion. Each file is saved and its new state
+printed.";
+
+const SLOT_USAGE: &str = "\
+Usage: terminal_test_1 slot <use | restore> <level> <file>...
+
+Spends or gives back one spell slot of <level> (1-9). Each file is saved and
+its new state printed.";
+
+const CAST_USAGE: &str = "\
+Usage: terminal_test_1 cast <file> <spell> [--ritual]
+
+Casts a spell by ID or name, e.g. `cast dandelion \"Magic Missile\"`, with the
+lowest spell slot that fits; cantrips need none. Classes that prepare spells
+can only cast the ones they prepared. A concentration spell ends the
+concentration on any other, with a warning. The file is saved and its new
+state printed.
+
+Options:
+  --ritual   cast a ritual spell as a ritual, without a spell slot";
+
+const PREPARE_USAGE: &str = "\
+Usage: terminal_test_1 prepare <file> <spell>...
+       terminal_test_1 unprepare <file> <spell>...
+
+Prepares spells by ID or name, or stops preparing them. Wizards prepare spells
+from their spellbook, clerics, druids and paladins from their whole class
+list, up to their spellcasting modifier plus their class level. Classes that
+know their spells do not prepare them. The file is saved and its new state
+printed.";
+
+const TIME_USAGE: &str = "\
+Usage: terminal_test_1 time <duration> <file>...
+
+Lets time pass for every character, e.g. `time 1 party/*.json` at the end of
+a combat round. <duration> is rounds of 6 seconds, or minutes and hours like
+`10m` or `1h`. Effects like Shield or Mage Armor end when their time runs
+out. Each file is saved and its new state printed.";
+
+const EXPLAIN_USAGE: &str = "\
+Usage: terminal_test_1 explain <file> [<value>] [--format <text | json>]
+
+Shows why a number on the sheet is what it is: the base value it starts from
+and every feature, item, spell, condition and level of exhaustion that
+changes it. <value> is one of `ac`, `initiative`, `speed`, `hp`, an ability
+like `dex`, a save like `dex save`, a skill like `stealth`, `checks`,
+`attacks`, `spell attack`, `spell dc` or a damage type like `fire damage`.
+Without <value> every number is listed.
+
+Options:
+  --format json   print the breakdowns as JSON";
+
+const XP_USAGE: &str = "\
+Usage: terminal_test_1 xp <amount> <file>...
+
+Every character gains <amount> experience points, e.g. `xp 450 party/*.json`,
+and is told when they are enough for a new level. Characters with milestone
+levelling are left alone. Each file is saved and its new state printed.";
+
+const MILESTONE_USAGE: &str = "\
+Usage: terminal_test_1 milestone <on | off> <file>...
+
+Turns milestone levelling on for campaigns that level up at story beats
+instead of tracking experience points, or back off. Experience points are
+kept either way.";
+
+const CONTENT_USAGE: &str = "\
+Usage: terminal_test_1 content [<file>] [--changes] [--format <text | json>]
+
+Lists the ID of every race, class, background, feat, item, equipment pack and
+spell with the pack it comes from and the packs it replaced. With <file> the
+pack of that character is included. Conflicts, where one pack defines an ID
+in more than one place, are reported at the end.
+
+Options:
+  --changes       only list entries that packs add or replace
+  --format json   print the list as JSON";
+
+const MIGRATE_USAGE: &str = "\
+Usage: terminal_test_1 migrate <file> [--dry-run]
+
+Upgrades the file to the current schema, keeping the original as <file>.bak.
+
+Options:
+  --dry-run   print the changes without writing them";
+
+const SCHEMA_USAGE: &str = "\
+Usage: terminal_test_1 schema [output]
+
+Prints the JSON Schema of character files, or writes it to [output].";
+
+/// How query commands print their results.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    Text,
+    Json
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rest {
+    Short,
+    Long
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SlotChange {
+    Use,
+    Restore
+}
+
+pub enum Command {
+    List { format: OutputFormat },
+    /// Without a file the library picker is shown first.
+    Show { file: Option<String>, autosave: bool, format: OutputFormat },
+    Roll { file: String, check: String, mode: RollMode, format: OutputFormat },
+    Validate { file: String, format: OutputFormat },
+    Export { file: String, output: String },
+    /// Without a name the character is created with the guided wizard instead.
+    New { file: String, name: Option<String>, race: String, class: String, hitpoints: u16 },
+    /// Choices left empty are asked for when the level needs them.
+    LevelUp { file: String, hitpoints: HitPointGain, ability_increases: Vec<Stat>, feat: Option<String>, spells: Vec<String> },
+    Damage { files: Vec<String>, amount: u16, damage_type: Option<String> },
+    Heal { files: Vec<String>, amount: u16 },
+    Rest { files: Vec<String>, rest: Rest },
+    Slot { files: Vec<String>, change: SlotChange, level: u8 },
+    Cast { file: String, spell: String, ritual: bool },
+    /// `prepare` is false to unprepare the spells instead.
+    Prepare { file: String, spells: Vec<String>, prepare: bool },
+    Time { files: Vec<String>, rounds: u32 },
+    /// Without a value every number on the sheet is explained.
+    Explain { file: String, value: Option<String>, format: OutputFormat },
+    Experience { files: Vec<String>, amount: u32 },
+    Milestone { files: Vec<String>, milestone: bool },
+    /// With a file, the pack of that character is loaded too.
+    Content { file: Option<String>, changes: bool, format: OutputFormat },
+    Migrate { file: String, dry_run: bool },
+    Schema { output: Option<String> }
+}
+
+/// Why the command line could not be turned into a `Command`.
+#[derive(Debug)]
+pub enum CliError {
+    /// `--help` was given; the text should be printed and the program exit successfully.
+    Help(&'static str),
+    Usage { message: String, usage: &'static str }
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CliError::Help(usage) => write!(f, "{}", usage),
+            CliError::Usage { message, usage } => write!(f, "{}\n\n{}", message, usage)
+        }
+    }
+}
+
+impl Error for CliError {}
+
+/// The arguments after the subcommand, split into positionals, flags and options.
+struct Arguments {
+    positionals: Vec<String>,
+    flags: Vec<String>,
+    options: HashMap<String, String>,
+    usage: &'static str
+}
+
+impl Arguments {
+
+    /// `flags` are switches such as `--autosave`, `options` take a value, e.g. `--hp 8`.
+    fn parse(args: &[String], flags: &[&str], options: &[&str], usage: &'static str) -> Result<Arguments, CliError> {
+        let mut arguments = Arguments { positionals: Vec::new(), flags: Vec::new(), options: HashMap::new(), usage };
+        let mut args = args.iter();
+
+        while let Some(arg) = args.next() {
+            if arg == "--help" || arg == "-h" {
+                return Err(CliError::Help(usage));
+            }
+
+            if !arg.starts_with("--") {
+                arguments.positionals.push(arg.clone());
+            } else if flags.contains(&arg.as_str()) {
+                arguments.flags.push(arg.clone());
+            } else if options.contains(&arg.as_str()) {
+                let value = args.next().ok_or_else(|| arguments.error(format!("{} needs a value", arg)))?;
+                arguments.options.insert(arg.clone(), value.clone());
+            } else {
+                return Err(arguments.error(format!("unknown option {}", arg)));
+            }
+        }
+
+        Ok(arguments)
+    }
+
+    fn error(&self, message: String) -> CliError {
+        CliError::Usage { message, usage: self.usage }
+    }
+
+    /// Checks that exactly `names` were given and returns them in order.
+    fn positionals(&self, names: &[&str]) -> Result<Vec<String>, CliError> {
+        if let Some(missing) = names.get(self.positionals.len()) {
+            return Err(self.error(format!("missing <{}>", missing)));
+        }
+        if let Some(extra) = self.positionals.get(names.len()) {
+            return Err(self.error(format!("unexpected argument `{}`", extra)));
+        }
+
+        Ok(self.positionals.clone())
+    }
+
+    /// The leading `names`, then at least one file, e.g. `damage <amount> <file>...`.
+    fn positionals_and_files(&self, names: &[&str]) -> Result<(Vec<String>, Vec<String>), CliError> {
+        if let Some(missing) = names.iter().chain(["file"].iter()).nth(self.positionals.len()) {
+            return Err(self.error(format!("missing <{}>", missing)));
+        }
+
+        let (values, files) = self.positionals.split_at(names.len());
+        Ok((values.to_vec(), files.to_vec()))
+    }
+
+    fn flag(&self, name: &str) -> bool {
+        self.flags.iter().any(|flag| flag == name)
+    }
+
+    fn option(&self, name: &str) -> Option<&str> {
+        self.options.get(name).map(String::as_str)
+    }
+
+    fn output_format(&self) -> Result<OutputFormat, CliError> {
+        match self.option("--format") {
+            None | Some("text") => Ok(OutputFormat::Text),
+            Some("json") => Ok(OutputFormat::Json),
+            Some(other) => Err(self.error(format!("--format is `text` or `json`, got `{}`", other)))
+        }
+    }
+
+    fn number(&self, name: &str, value: &str) -> Result<u16, CliError> {
+        value.parse()
+            .map_err(|_| self.error(format!("{} must be a whole number, got `{}`", name, value)))
+    }
+}
+
+pub struct Config {
+    pub command: Command,
+    /// `--library <dir>`, given before the command.
+    pub library: Option<String>,
+    /// Every `--content <dir>` given before the command, in order.
+    pub content: Vec<String>
+}
+
+impl Config {
+    pub fn new(args: &[String]) -> Result<Config, CliError> {
+        let mut library = None;
+        let mut content = Vec::new();
+        let mut args = args;
+        while let Some(option @ ("--library" | "--content")) = args.get(1).map(String::as_str) {
+            let dir = match args.get(2) {
+                Some(dir) => dir.clone(),
+                None => return Err(CliError::Usage { message: format!("{} needs a value", option), usage: USAGE })
+            };
+            if option == "--library" {
+                library = Some(dir);
+            } else {
+                content.push(dir);
+            }
+            args = &args[2..];
+        }
+
+        let subcommand = match args.get(1) {
+            Some(subcommand) => subcommand.as_str(),
+            None => return Ok(Config { command: Command::Show { file: None, autosave: false, format: OutputFormat::Text }, library, content })
+        };
+        let rest = &args[2..];
+
+        let command = match subcommand {
+            "--help" | "-h" | "help" => return Err(CliError::Help(USAGE)),
+            "list" => {
+                let arguments = Arguments::parse(rest, &[], &["--format"], LIST_USAGE)?;
+                arguments.positionals(&[])?;
+
+                Command::List { format: arguments.output_format()? }
+            },
+            "show" => parse_show(rest)?,
+            "roll" => {
+                let arguments = Arguments::parse(rest, &["--advantage", "--disadvantage"], &["--format"], ROLL_USAGE)?;
+                let mut positionals = arguments.positionals(&["file", "check"])?.into_iter();
+                let mode = RollMode::from_sources(arguments.flag("--advantage"), arguments.flag("--disadvantage"));
+
+                Command::Roll {
+                    file: positionals.next().unwrap_or_default(),
+                    check: positionals.next().unwrap_or_default(),
+                    mode,
+                    format: arguments.output_format()?
+                }
+            },
+            "validate" => {
+                let arguments = Arguments::parse(rest, &[], &["--format"], VALIDATE_USAGE)?;
+                let file = arguments.positionals(&["file"])?.remove(0);
+
+                Command::Validate { file, format: arguments.output_format()? }
+            },
+            "export" | "convert" => {
+                let arguments = Arguments::parse(rest, &[], &[], EXPORT_USAGE)?;
+                let mut positionals = arguments.positionals(&["file", "output"])?.into_iter();
+
+                Command::Export { file: positionals.next().unwrap_or_default(), output: positionals.next().unwrap_or_default() }
+            },
+            "new" => {
+                let arguments = Arguments::parse(rest, &[], &["--name", "--race", "--class", "--hp"], NEW_USAGE)?;
+                let file = arguments.positionals(&["file"])?.remove(0);
+                let name = arguments.option("--name");
+                if name.is_none() && ["--race", "--class", "--hp"].iter().any(|option| arguments.option(option).is_some()) {
+                    return Err(arguments.error("--race, --class and --hp need --name".to_owned()));
+                }
+                let hitpoints = match arguments.option("--hp") {
+                    Some(hitpoints) => arguments.number("--hp", hitpoints)?,
+                    None => 8
+                };
+                if hitpoints > MAX_NEW_HITPOINTS {
+                    return Err(arguments.error(format!("--hp is at most {} at level 1, got {}", MAX_NEW_HITPOINTS, hitpoints)));
+                }
+
+                Command::New {
+                    file,
+                    name: name.map(str::to_owned),
+                    race: arguments.option("--race").unwrap_or("Human").to_owned(),
+                    class: arguments.option("--class").unwrap_or("Fighter").to_owned(),
+                    hitpoints
+                }
+            },
+            "level-up" => {
+                let arguments = Arguments::parse(rest, &["--roll"], &["--hp", "--asi", "--feat", "--spells"], LEVEL_UP_USAGE)?;
+                let file = arguments.positionals(&["file"])?.remove(0);
+                let hitpoints = match (arguments.option("--hp"), arguments.flag("--roll")) {
+                    (Some(_), true) => return Err(arguments.error("choose --roll or --hp, not both".to_owned())),
+                    (Some(hitpoints), false) => HitPointGain::Fixed(arguments.number("--hp", hitpoints)?),
+                    (None, true) => HitPointGain::Roll,
+                    (None, false) => HitPointGain::Average
+                };
+                let ability_increases = match arguments.option("--asi") {
+                    Some(text) => parse_ability_increases(text).map_err(|message| arguments.error(message))?,
+                    None => Vec::new()
+                };
+                let feat = arguments.option("--feat").map(str::to_owned);
+                if !ability_increases.is_empty() && feat.is_some() {
+                    return Err(arguments.error("choose --asi or --feat, not both".to_owned()));
+                }
+                let spells = arguments.option("--spells")
+                    .map(|spells| spells.split(',').map(|spell| spell.trim().to_owned()).filter(|spell| !spell.is_empty()).collect())
+                    .unwrap_or_default();
+
+                Command::LevelUp { file, hitpoints, ability_increases, feat, spells }
+            },
+            "damage" => {
+                let arguments = Arguments::parse(rest, &[], &["--type"], DAMAGE_USAGE)?;
+                let (values, files) = arguments.positionals_and_files(&["amount"])?;
+                let amount = arguments.number("<amount>", &values[0])?;
+
+                Command::Damage { files, amount, damage_type: arguments.option("--type").map(str::to_lowercase) }
+            },
+            "heal" => {
+                let arguments = Arguments::parse(rest, &[], &[], HEAL_USAGE)?;
+                let (values, files) = arguments.positionals_and_files(&["amount"])?;
+                let amount = arguments.number("<amount>", &values[0])?;
+
+                Command::Heal { files, amount }
+            },
+            "rest" => {
+                let arguments = Arguments::parse(rest, &[], &[], REST_USAGE)?;
+                let (values, files) = arguments.positionals_and_files(&["short | long"])?;
+                let rest = match values[0].as_str() {
+                    "short" => Rest::Short,
+                    "long" => Rest::Long,
+                    other => return Err(arguments.error(format!("a rest is `short` or `long`, got `{}`", other)))
+                };
+
+                Command::Rest { files, rest }
+            },
+            "slot" => {
+                let arguments = Arguments::parse(rest, &[], &[], SLOT_USAGE)?;
+                let (values, files) = arguments.positionals_and_files(&["use | restore", "level"])?;
+                let change = match values[0].as_str() {
+                    "use" => SlotChange::Use,
+                    "restore" => SlotChange::Restore,
+                    other => return Err(arguments.error(format!("a slot change is `use` or `restore`, got `{}`", other)))
+                };
+                let level = match values[1].parse::<u8>() {
+                    Ok(level) if (1..=MAX_SPELL_LEVEL).contains(&level) => level,
+                    _ => return Err(arguments.error(format!(
+                        "<level> goes from 1 to {}, got `{}`", MAX_SPELL_LEVEL, values[1])))
+                };
+
+                Command::Slot { files, change, level }
+            },
+            "cast" => {
+                let arguments = Arguments::parse(rest, &["--ritual"], &[], CAST_USAGE)?;
+                let mut positionals = arguments.positionals(&["file", "spell"])?.into_iter();
+
+                Command::Cast {
+                    file: positionals.next().unwrap_or_default(),
+                    spell: positionals.next().unwrap_or_default(),
+                    ritual: arguments.flag("--ritual")
+                }
+            },
+            "prepare" | "unprepare" => {
+                let arguments = Arguments::parse(rest, &[], &[], PREPARE_USAGE)?;
+                let (file, spells) = match arguments.positionals.split_first() {
+                    Some((file, spells)) if !spells.is_empty() => (file.clone(), spells.to_vec()),
+                    Some(_) => return Err(arguments.error("missing <spell>".to_owned())),
+                    None => return Err(arguments.error("missing <file>".to_owned()))
+                };
+
+                Command::Prepare { file, spells, prepare: subcommand == "prepare" }
+            },
+            "time" => {
+                let arguments = Arguments::parse(rest, &[], &[], TIME_USAGE)?;
+                let (values, files) = arguments.positionals_and_files(&["duration"])?;
+                let rounds = parse_rounds(&values[0]).ok_or_else(|| arguments.error(format!(
+                    "<duration> is rounds, or minutes and hours like `10m` or `1h`, got `{}`", values[0])))?;
+
+                Command::Time { files, rounds }
+            },
+            "explain" => {
+                let arguments = Arguments::parse(rest, &[], &["--format"], EXPLAIN_USAGE)?;
+                let (file, value) = match arguments.positionals.split_first() {
+                    Some((file, value)) => (file.clone(), value),
+                    None => return Err(arguments.error("missing <file>".to_owned()))
+                };
+
+                Command::Explain {
+                    file,
+                    // Values like `dex save` may come as more than one argument.
+                    value: if value.is_empty() { None } else { Some(value.join(" ")) },
+                    format: arguments.output_format()?
+                }
+            },
+            "xp" => {
+                let arguments = Arguments::parse(rest, &[], &[], XP_USAGE)?;
+                let (values, files) = arguments.positionals_and_files(&["amount"])?;
+                let amount = values[0].parse()
+                    .map_err(|_| arguments.error(format!("<amount> must be a whole number, got `{}`", values[0])))?;
+
+                Command::Experience { files, amount }
+            },
+            "milestone" => {
+                let arguments = Arguments::parse(rest, &[], &[], MILESTONE_USAGE)?;
+                let (values, files) = arguments.positionals_and_files(&["on | off"])?;
+                let milestone = match values[0].as_str() {
+                    "on" => true,
+                    "off" => false,
+                    other => return Err(arguments.error(format!("milestone levelling is `on` or `off`, got `{}`", other)))
+                };
+
+                Command::Milestone { files, milestone }
+            },
+            "content" => {
+                let arguments = Arguments::parse(rest, &["--changes"], &["--format"], CONTENT_USAGE)?;
+                if arguments.positionals.len() > 1 {
+                    return Err(arguments.error(format!("unexpected argument `{}`", arguments.positionals[1])));
+                }
+
+                Command::Content {
+                    file: arguments.positionals.first().cloned(),
+                    changes: arguments.flag("--changes"),
+                    format: arguments.output_format()?
+                }
+            },
+            "migrate" => {
+                let arguments = Arguments::parse(rest, &["--dry-run"], &[], MIGRATE_USAGE)?;
+                let file = arguments.positionals(&["file"])?.remove(0);
+
+                Command::Migrate { file, dry_run: arguments.flag("--dry-run") }
+            },
+            "schema" => {
+                let arguments = Arguments::parse(rest, &[], &[], SCHEMA_USAGE)?;
+                if arguments.positionals.len() > 1 {
+                    return Err(arguments.error(format!("unexpected argument `{}`", arguments.positionals[1])));
+                }
+
+                Command::Schema { output: arguments.positionals.first().cloned() }
+            },
+            unknown if unknown.starts_with("--") =>
+                return Err(CliError::Usage { message: format!("unknown option {}", unknown), usage: USAGE }),
+            // A bare file name opens the sheet, like it always has.
+            _ => parse_show(&args[1..])?
+        };
+
+        Ok(Config { command, library, content })
+    }
+}
+
+/// "3" -> 3 rounds, "10m" -> 100, "1h" -> 600. `None` for anything else,
+/// including durations too long to count in rounds.
+fn parse_rounds(text: &str) -> Option<u32> {
+    let (amount, unit) = match text.find(|c: char| !c.is_ascii_digit()) {
+        Some(index) => text.split_at(index),
+        None => (text, "")
+    };
+    let amount: u32 = amount.parse().ok()?;
+
+    match unit {
+        "" | "r" => Some(amount),
+        "m" => amount.checked_mul(ROUNDS_PER_MINUTE),
+        "h" => amount.checked_mul(ROUNDS_PER_HOUR),
+        _ => None
+    }
+}
+
+fn parse_show(args: &[String]) -> Result<Command, CliError> {
+    let arguments = Arguments::parse(args, &["--autosave"], &["--format"], SHOW_USAGE)?;
+    if arguments.positionals.len() > 1 {
+        return Err(arguments.error(format!("unexpected argument `{}`", arguments.positionals[1])));
+    }
+    let file = arguments.positionals.first().cloned();
+
+    Ok(Command::Show { file, autosave: arguments.flag("--autosave"), format: arguments.output_format()? })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_rounds_reads_rounds_minutes_and_hours() {
+        assert_eq!(parse_rounds("3"), Some(3));
+        assert_eq!(parse_rounds("3r"), Some(3));
+        assert_eq!(parse_rounds("10m"), Some(100));
+        assert_eq!(parse_rounds("1h"), Some(600));
+    }
+
+    #[test]
+    fn parse_rounds_rejects_other_text() {
+        assert_eq!(parse_rounds(""), None);
+        assert_eq!(parse_rounds("m"), None);
+        assert_eq!(parse_rounds("-3"), None);
+        assert_eq!(parse_rounds("10 m"), None);
+        assert_eq!(parse_rounds("2d"), None);
+    }
+
+    #[test]
+    fn parse_rounds_rejects_durations_that_overflow() {
+        assert_eq!(parse_rounds("4294967295"), Some(u32::MAX));
+        assert_eq!(parse_rounds("4294967296"), None);
+        assert_eq!(parse_rounds("429496730m"), None);
+        assert_eq!(parse_rounds("7158279h"), None);
+    }
+}
@@ -0,0 +1,663 @@
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::character::{character_schema, load_character, roll_d20, save_character, validate};
+use crate::character::{ordinal, Breakdown, Character, Dice, Duration, Finding, Format, LevelAdvance, RollMode, Severity, Stat, Summary, Target};
+use crate::cli::{OutputFormat, Rest, SlotChange};
+use crate::error::SheetError;
+use crate::library::Library;
+use crate::rules::{parse_ability_increases, Entry, HitPointGain, LevelUpChoices, LevelUpPlan, Provenance, Rules, Source, Spell};
+use crate::ui::{create_character, pick_character, MainApp};
+
+fn print_json<T>(value: &T) -> Result<(), SheetError>
+    where
+        T: Serialize
+{
+    let json = serde_json::to_string_pretty(value)
+        .map_err(|e| SheetError::Command(format!("could not write JSON: {}", e)))?;
+
+    // JSON is meant to be piped, and a reader like `head` closing early is not an error.
+    match writeln!(io::stdout(), "{}", json) {
+        Err(e) if e.kind() != io::ErrorKind::BrokenPipe => Err(SheetError::Command(format!("could not write JSON: {}", e))),
+        _ => Ok(())
+    }
+}
+
+pub fn run_list(library: &Library, format: OutputFormat) -> Result<(), SheetError> {
+    let (entries, problems) = library.entries();
+
+    for problem in &problems {
+        eprintln!("{}", problem);
+    }
+
+    if format == OutputFormat::Json {
+        return print_json(&entries);
+    }
+
+    if entries.is_empty() {
+        println!("No characters in {}", library.dir().display());
+        return Ok(());
+    }
+
+    println!("NAME                      RACE                 CLASS           LVL  FILE");
+    for entry in &entries {
+        println!("{:<25} {:<20} {:<15} {:>3}  {}", entry.name, entry.race, entry.class, entry.level, entry.path.display());
+    }
+    Ok(())
+}
+
+/// Shows the library picker, returning the chosen file or `None` on quit.
+pub fn pick_from_library(library: &Library) -> Result<Option<PathBuf>, SheetError> {
+    let (entries, problems) = library.entries();
+    let problems: Vec<String> = problems.iter().map(ToString::to_string).collect();
+
+    if entries.is_empty() {
+        return Err(SheetError::Command(format!(
+            "no characters in {}; open a file with `show <file>` or create one with `new`", library.dir().display())));
+    }
+
+    let title = format!("Characters in {}", library.dir().display());
+    pick_character(&entries, &title, &problems).map_err(SheetError::Terminal)
+}
+
+/// Opens the sheet, or with `--format json` prints everything it would show.
+pub fn run_show(character_path: &Path, autosave: bool, format: OutputFormat, rules: Rules) -> Result<(), SheetError> {
+    let loaded = load_character(character_path)?;
+    let mut character = loaded.character;
+
+    if format == OutputFormat::Json {
+        return print_json(&Summary::new(&character));
+    }
+
+    let mut main_app = MainApp::new ( &mut character, character_path, autosave, rules )
+        .map_err(SheetError::Terminal)?;
+
+    main_app.log_migrations(&loaded.migrations);
+
+    main_app.draw_app().map_err(SheetError::Terminal)
+}
+
+#[derive(Serialize)]
+struct RollResult {
+    check: String,
+    /// "d20" for checks and saves, the notation for plain dice.
+    dice: String,
+    mode: RollMode,
+    rolls: Vec<i16>,
+    bonus: i16,
+    total: i16,
+    /// Paralyzed, stunned, ... characters fail STR and DEX saves without rolling.
+    automatic_failure: bool
+}
+
+impl RollResult {
+
+    fn d20(check: String, bonus: i16, mode: RollMode) -> RollResult {
+        let (kept, rolls) = roll_d20(mode);
+        RollResult { check, dice: "d20".to_owned(), mode, rolls, bonus, total: kept + bonus, automatic_failure: false }
+    }
+
+    fn dice(dice: Dice) -> RollResult {
+        let rolls = dice.roll_each();
+        let total = dice.total(&rolls);
+
+        RollResult {
+            check: dice.to_string(),
+            dice: dice.to_string(),
+            mode: RollMode::Normal,
+            rolls,
+            bonus: dice.modifier,
+            total,
+            automatic_failure: false
+        }
+    }
+
+    fn print(&self) {
+        let mode = match self.mode {
+            RollMode::Normal => "",
+            RollMode::Advantage => " with advantage",
+            RollMode::Disadvantage => " with disadvantage"
+        };
+        let rolls: Vec<String> = self.rolls.iter().map(i16::to_string).collect();
+
+        if self.automatic_failure {
+            println!("{}: automatic failure", self.check);
+        } else if self.dice == "d20" {
+            println!("{}{}: {} (d20 {} {:+})", self.check, mode, self.total, rolls.join(", "), self.bonus);
+        } else {
+            println!("{}: {} ({})", self.check, self.total, rolls.join(", "));
+        }
+    }
+}
+
+/// Rolls a skill, an ability check, a saving throw or plain dice, see `cli::ROLL_USAGE`.
+pub fn run_roll(character_path: &Path, check: &str, mode: RollMode, format: OutputFormat) -> Result<(), SheetError> {
+    let character = load_character(character_path)?.character;
+
+    let result = roll(&character, check, mode)?;
+
+    match format {
+        OutputFormat::Text => {
+            result.print();
+            Ok(())
+        },
+        OutputFormat::Json => print_json(&result)
+    }
+}
+
+fn roll(character: &Character, check: &str, mode: RollMode) -> Result<RollResult, SheetError> {
+    if let Ok(dice) = Dice::parse(check) {
+        return Ok(RollResult::dice(dice));
+    }
+
+    let check = check.replace(['-', '_'], " ");
+    let unknown_check = || SheetError::Command(format!(
+        "`{}` is not a skill, an ability, a saving throw or dice notation", check));
+
+    if let Some(stat) = check.strip_suffix(" save") {
+        let stat = Stat::from_name(stat).ok_or_else(unknown_check)?;
+        let name = format!("{} save", stat.abbreviation());
+        let bonus = character.get_saving_throw_bonus(&stat).ok_or_else(unknown_check)?;
+
+        if character.fails_saving_throw(&stat) {
+            let mode = character.saving_throw_mode(&stat).combine(mode);
+            return Ok(RollResult { check: name, dice: "d20".to_owned(), mode, rolls: Vec::new(), bonus, total: 0, automatic_failure: true });
+        }
+
+        Ok(RollResult::d20(name, bonus, character.saving_throw_mode(&stat).combine(mode)))
+    } else if let Some(skill) = character.find_skill(&check) {
+        Ok(RollResult::d20(skill.name.clone(), character.get_skill_bonus(skill), character.skill_check_mode(skill).combine(mode)))
+    } else {
+        let stat = Stat::from_name(&check).ok_or_else(unknown_check)?;
+        let bonus = character.get_modifier(&stat).ok_or_else(unknown_check)?;
+
+        Ok(RollResult::d20(stat.abbreviation().to_owned(), bonus, character.ability_check_mode().combine(mode)))
+    }
+}
+
+#[derive(Serialize)]
+struct ValidationReport<'a> {
+    path: String,
+    errors: usize,
+    warnings: usize,
+    findings: &'a [Finding]
+}
+
+/// Prints every validation finding, failing if any of them is an error.
+pub fn run_validate(character_path: &Path, format: OutputFormat) -> Result<(), SheetError> {
+    let character = load_character(character_path)?.character;
+    let findings = validate(&character);
+    let errors = findings.iter().filter(|finding| finding.severity == Severity::Error).count();
+
+    match format {
+        OutputFormat::Text => {
+            for finding in &findings {
+                println!("{}", finding);
+            }
+            println!("{}: {} errors, {} warnings", character_path.display(), errors, findings.len() - errors);
+        },
+        OutputFormat::Json => print_json(&ValidationReport {
+            path: character_path.display().to_string(),
+            errors,
+            warnings: findings.len() - errors,
+            findings: &findings
+        })?
+    }
+
+    if errors > 0 {
+        return Err(SheetError::validation(character_path, "character", format!("{} validation errors", errors)));
+    }
+
+    Ok(())
+}
+
+/// Writes a character in the format of `output`'s extension, e.g. JSON to YAML.
+pub fn run_export(character_path: &Path, output: &Path) -> Result<(), SheetError> {
+    let loaded = load_character(character_path)?;
+    let mut character = loaded.character;
+
+    let output_format = Format::from_extension(output).unwrap_or(Format::Json);
+    if output_format != Format::Json {
+        character.set_json_schema(None);
+    }
+
+    save_character(&character, output)
+        .map_err(|e| SheetError::io(output, e))?;
+
+    println!("{} ({}) -> {} ({})", character_path.display(), loaded.format, output.display(), output_format);
+    Ok(())
+}
+
+pub fn run_new(character_path: &Path, name: &str, race: &str, class: &str, hitpoints: u16) -> Result<(), SheetError> {
+    if character_path.exists() {
+        return Err(SheetError::Command(format!("{} already exists", character_path.display())));
+    }
+
+    let character = Character::new(name, race, class, hitpoints);
+    save_character(&character, character_path)
+        .map_err(|e| SheetError::io(character_path, e))?;
+
+    println!("Created {}, a level 1 {} {}, in {}", name, race, class, character_path.display());
+    Ok(())
+}
+
+/// Creates a character with the guided wizard, then opens its sheet.
+pub fn run_new_wizard(character_path: &Path, rules: Rules) -> Result<(), SheetError> {
+    if character_path.exists() {
+        return Err(SheetError::Command(format!("{} already exists", character_path.display())));
+    }
+
+    let character = match create_character(&rules).map_err(SheetError::Terminal)? {
+        Some(character) => character,
+        None => return Ok(())
+    };
+
+    save_character(&character, character_path)
+        .map_err(|e| SheetError::io(character_path, e))?;
+
+    run_show(character_path, false, OutputFormat::Text, rules)
+}
+
+/// One line with what scripts usually need to know after a change, e.g.
+/// "Dandelion: HP 14/17 | slots 1st 3/4, 2nd 2/2 | Poisoned | exhaustion 1".
+fn state_line(character: &Character) -> String {
+    let mut parts = vec![format!("{}: HP {}/{}", character.get_name(),
+        character.get_current_hitpoints(), character.get_effective_max_hitpoints())];
+
+    let slots: Vec<String> = character.get_spell_slots().iter()
+        .map(|slots| format!("{} {}/{}", ordinal(slots.level), slots.current, slots.max))
+        .collect();
+    if !slots.is_empty() {
+        parts.push(format!("slots {}", slots.join(", ")));
+    }
+
+    let conditions: Vec<String> = character.get_conditions().iter().map(ToString::to_string).collect();
+    if !conditions.is_empty() {
+        parts.push(conditions.join(", "));
+    }
+
+    if *character.get_exhaustion() > 0 {
+        parts.push(format!("exhaustion {}", character.get_exhaustion()));
+    }
+
+    let effects: Vec<String> = character.get_effects().iter()
+        .map(|effect| format!("{} ({})", effect.name, effect.duration))
+        .collect();
+    if !effects.is_empty() {
+        parts.push(effects.join(", "));
+    }
+
+    parts.join(" | ")
+}
+
+/// Loads a character, applies `change` and saves it back, printing what
+/// happened and the resulting state.
+fn update_character<F>(character_path: &Path, change: F) -> Result<(), SheetError>
+    where
+        F: FnOnce(&mut Character) -> Result<Vec<String>, String>
+{
+    let mut character = load_character(character_path)?.character;
+
+    let log = change(&mut character)
+        .map_err(|message| SheetError::Command(format!("{}: {}", character_path.display(), message)))?;
+
+    save_character(&character, character_path)
+        .map_err(|e| SheetError::io(character_path, e))?;
+
+    for line in &log {
+        println!("  {}", line);
+    }
+    println!("{}", state_line(&character));
+    Ok(())
+}
+
+/// Applies the same change to every file, e.g. the whole party taking damage.
+/// A file that fails does not stop the others; with a single file its error
+/// is returned as is.
+fn update_characters<F>(files: &[PathBuf], change: F) -> Result<(), SheetError>
+    where
+        F: Fn(&mut Character) -> Result<Vec<String>, String>
+{
+    update_each(files, |file| update_character(file, &change))
+}
+
+/// Runs `update` on every file and reports the failures the way
+/// `update_characters` does.
+fn update_each<F>(files: &[PathBuf], update: F) -> Result<(), SheetError>
+    where
+        F: Fn(&Path) -> Result<(), SheetError>
+{
+    let mut errors: Vec<SheetError> = files.iter()
+        .filter_map(|file| update(file).err())
+        .collect();
+
+    match errors.len() {
+        0 => Ok(()),
+        1 if files.len() == 1 => Err(errors.remove(0)),
+        failed => {
+            for error in &errors {
+                eprintln!("{}", error);
+            }
+            Err(SheetError::Command(format!("{} of {} characters could not be updated", failed, files.len())))
+        }
+    }
+}
+
+/// Asks a question on the terminal and returns the answer, or `None` for an empty line.
+fn ask(question: &str) -> Result<Option<String>, String> {
+    print!("{} ", question);
+    io::stdout().flush().map_err(|e| e.to_string())?;
+
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer).map_err(|e| e.to_string())?;
+    let answer = answer.trim();
+
+    Ok(if answer.is_empty() { None } else { Some(answer.to_owned()) })
+}
+
+/// Asks for abilities or a feat until the answer can be used.
+fn ask_ability_score_improvement(plan: &LevelUpPlan) -> Result<(Vec<Stat>, Option<String>), String> {
+    println!("Level {} brings an Ability Score Improvement: +2 to one ability, +1 to two, or a feat.", plan.level);
+
+    loop {
+        let answer = ask("Abilities (e.g. STR or STR,DEX) or `feat <name>`, empty to cancel:")?
+            .ok_or_else(|| "level up cancelled".to_owned())?;
+
+        if let Some(feat) = answer.strip_prefix("feat ") {
+            return Ok((Vec::new(), Some(feat.trim().to_owned())));
+        }
+        match parse_ability_increases(&answer) {
+            Ok(stats) => return Ok((stats, None)),
+            Err(message) => println!("{}", message)
+        }
+    }
+}
+
+/// Lists the spells on offer and asks for them by number or name until the count is right.
+fn ask_spells(plan: &LevelUpPlan) -> Result<Vec<String>, String> {
+    println!("Level {} learns {} cantrips and {} spells:", plan.level, plan.cantrips, plan.spells);
+    for (index, spell) in plan.spell_options.iter().enumerate() {
+        println!("{:>3}) {} [{}] {}", index + 1, spell.name, spell.level_text(), spell.effect);
+    }
+
+    loop {
+        let answer = ask("Spells by number or name, separated by commas, empty to cancel:")?
+            .ok_or_else(|| "level up cancelled".to_owned())?;
+
+        let names: Vec<String> = answer.split(',')
+            .map(str::trim)
+            .map(|choice| match choice.parse::<usize>() {
+                Ok(number) if number >= 1 && number <= plan.spell_options.len() => plan.spell_options[number - 1].id(),
+                _ => choice.to_owned()
+            })
+            .collect();
+
+        match plan.check_spells(&names) {
+            Ok(names) => return Ok(names),
+            Err(message) => println!("{}", message)
+        }
+    }
+}
+
+/// Levels up from the class progression in the rules. Choices the level
+/// needs but the command line left out are asked for on a terminal.
+/// Classes the rules do not know only take --hp.
+pub fn run_level_up(character_path: &Path, rules: &Rules, hitpoints: HitPointGain, ability_increases: Vec<Stat>,
+                    feat: Option<String>, spells: Vec<String>) -> Result<(), SheetError> {
+    let interactive = termion::is_tty(&io::stdin());
+
+    update_character(character_path, |character| {
+        // Without the class there is no plan to check the choices against, so
+        // they are taken as given.
+        if rules.find_class(character.get_class()).is_none() {
+            let hitpoints = match hitpoints {
+                HitPointGain::Fixed(0) => return Err("a new level gives at least 1 hit point".to_owned()),
+                HitPointGain::Fixed(hitpoints) => hitpoints,
+                _ => return Err(format!("{} is not a class in the rules, so give the hit points with --hp", character.get_class()))
+            };
+            let spells = spells.iter()
+                .map(|reference| rules.find_spell(reference.trim())
+                    .map(Entry::id)
+                    .ok_or_else(|| format!("`{}` is not a spell in the rules", reference.trim())))
+                .collect::<Result<Vec<String>, String>>()?;
+
+            return character.level_up(LevelAdvance {
+                hitpoints,
+                ability_increases,
+                features: feat.map(|name| rules.feat_feature(name)).into_iter().collect(),
+                spells,
+                ..LevelAdvance::default()
+            });
+        }
+
+        let plan = rules.plan_level_up(character)?;
+        let (hitpoints, how) = plan.hitpoints(hitpoints, character)?;
+
+        let (ability_increases, feat) = match (plan.ability_score_improvement, ability_increases.is_empty() && feat.is_none()) {
+            (true, true) if interactive => ask_ability_score_improvement(&plan)?,
+            (true, true) => return Err(format!(
+                "level {} brings an Ability Score Improvement: add --asi <ability>[,<ability>] or --feat <name>", plan.level)),
+            _ => (ability_increases, feat)
+        };
+
+        let spells = match (plan.cantrips + plan.spells, spells.is_empty()) {
+            (0, _) | (_, false) => spells,
+            _ if interactive => ask_spells(&plan)?,
+            _ => return Err(format!("level {} learns {} cantrips and {} spells: add --spells <spell>,...",
+                plan.level, plan.cantrips, plan.spells))
+        };
+
+        let advance = plan.advance(LevelUpChoices { hitpoints, ability_increases, feat, spells })?;
+
+        let mut log = vec![how];
+        log.extend(character.level_up(advance)?);
+        Ok(log)
+    })
+}
+
+/// Damage always rolls the concentration save; the sheet lets players roll their own dice.
+/// The rules are loaded for each character, so the spells of its
+/// `<name>.content` pack are found too.
+pub fn run_damage(files: &[PathBuf], amount: u16, damage_type: Option<&str>, content: &[String]) -> Result<(), SheetError> {
+    update_each(files, |file| {
+        let rules = Rules::load(content, Some(file))?;
+
+        update_character(file, |character| {
+            let (mut log, save_dc) = rules.take_damage(character, amount, damage_type);
+
+            if let Some(save) = save_dc.and_then(|dc| character.roll_concentration_save(dc)) {
+                log.push(rules.describe_concentration_save(&save));
+            }
+            Ok(log)
+        })
+    })
+}
+
+pub fn run_heal(files: &[PathBuf], amount: u16) -> Result<(), SheetError> {
+    update_characters(files, |character| Ok(character.heal(amount)))
+}
+
+pub fn run_rest(files: &[PathBuf], rest: Rest) -> Result<(), SheetError> {
+    update_characters(files, |character| Ok(match rest {
+        Rest::Short => character.short_rest(),
+        Rest::Long => character.long_rest()
+    }))
+}
+
+pub fn run_slot(files: &[PathBuf], change: SlotChange, level: u8) -> Result<(), SheetError> {
+    update_characters(files, |character| {
+        let result = match change {
+            SlotChange::Use => character.use_spell_slot(level),
+            SlotChange::Restore => character.restore_spell_slot(level)
+        };
+
+        result
+            .map(|remaining| vec![format!("{} level spell slots left: {}", ordinal(level), remaining)])
+            .map_err(|e| e.to_string())
+    })
+}
+
+fn find_spell<'a>(rules: &'a Rules, reference: &str) -> Result<&'a Spell, SheetError> {
+    rules.find_spell(reference)
+        .ok_or_else(|| SheetError::Command(format!("`{}` is not a spell in the rules", reference)))
+}
+
+pub fn run_cast(character_path: &Path, rules: &Rules, reference: &str, ritual: bool) -> Result<(), SheetError> {
+    let spell = find_spell(rules, reference)?;
+
+    update_character(character_path, |character| {
+        if let Some(current) = rules.concentration_conflict(character, spell) {
+            eprintln!("Warning: casting {} ends the concentration on {}", spell.name, current);
+        }
+        if ritual { rules.cast_ritual(character, spell) } else { rules.cast_spell(character, spell) }
+    })
+}
+
+pub fn run_prepare(character_path: &Path, rules: &Rules, references: &[String], prepare: bool) -> Result<(), SheetError> {
+    let spells = references.iter().map(|reference| find_spell(rules, reference)).collect::<Result<Vec<_>, _>>()?;
+
+    update_character(character_path, |character| {
+        spells.iter().map(|spell| rules.prepare_spell(character, spell, prepare)).collect()
+    })
+}
+
+pub fn run_time(files: &[PathBuf], rounds: u32) -> Result<(), SheetError> {
+    update_characters(files, |character| {
+        let mut log = vec![format!("{} passed", Duration::Rounds(rounds))];
+        log.extend(character.pass_time(rounds));
+        Ok(log)
+    })
+}
+
+/// Shows where numbers on the sheet come from, see `cli::EXPLAIN_USAGE`.
+pub fn run_explain(character_path: &Path, value: Option<&str>, format: OutputFormat) -> Result<(), SheetError> {
+    let character = load_character(character_path)?.character;
+
+    let targets = match value {
+        Some(value) => {
+            let target = Target::from_name(value)
+                .filter(|target| !matches!(target, Target::Skill(name) if character.find_skill(name).is_none()))
+                .ok_or_else(|| SheetError::Command(format!("`{}` is not a number on the sheet, see `explain --help`", value)))?;
+            vec![target]
+        },
+        None => character.breakdown_targets()
+    };
+    let breakdowns: Vec<Breakdown> = targets.iter().map(|target| character.breakdown(target)).collect();
+
+    if format == OutputFormat::Json {
+        return print_json(&breakdowns);
+    }
+
+    for breakdown in &breakdowns {
+        let lines: Vec<String> = breakdown.lines.iter()
+            .map(|line| format!("{} {}", line.change, line.source))
+            .collect();
+
+        if value.is_some() {
+            println!("{}: {}", breakdown.target.title(), breakdown.value_text());
+            for line in &breakdown.lines {
+                println!("  {:>14}  {}", line.change, line.source);
+            }
+        } else if lines.is_empty() {
+            println!("{}: {}", breakdown.target.title(), breakdown.value_text());
+        } else {
+            println!("{}: {} ({})", breakdown.target.title(), breakdown.value_text(), lines.join(", "));
+        }
+    }
+    Ok(())
+}
+
+pub fn run_experience(files: &[PathBuf], amount: u32) -> Result<(), SheetError> {
+    update_characters(files, |character| character.award_experience(amount))
+}
+
+pub fn run_milestone(files: &[PathBuf], milestone: bool) -> Result<(), SheetError> {
+    update_characters(files, |character| Ok(character.set_milestone(milestone)))
+}
+
+#[derive(Serialize)]
+struct ContentReport {
+    /// "SRD", then the pack directories in the order they were layered.
+    packs: Vec<String>,
+    conflicts: usize,
+    entries: Vec<Provenance>
+}
+
+/// Lists where every rules entry comes from, see `cli::CONTENT_USAGE`.
+pub fn run_content(rules: &Rules, changes: bool, format: OutputFormat) -> Result<(), SheetError> {
+    let entries: Vec<Provenance> = rules.provenance().into_iter()
+        .filter(|entry| !changes || entry.source.file.is_some())
+        .collect();
+    let conflicts = entries.iter().filter(|entry| entry.conflict).count();
+    let packs = std::iter::once("SRD".to_owned()).chain(rules.packs.iter().cloned()).collect();
+
+    if format == OutputFormat::Json {
+        return print_json(&ContentReport { packs, conflicts, entries });
+    }
+
+    println!("Packs: {}", packs.join(", "));
+    println!("ID                                  SOURCE");
+    for entry in &entries {
+        let replaced: Vec<String> = entry.replaced.iter().map(ToString::to_string).collect();
+        if replaced.is_empty() {
+            println!("{:<35} {}", entry.id, entry.source);
+        } else {
+            println!("{:<35} {} (replaces {})", entry.id, entry.source, replaced.join(", "));
+        }
+    }
+
+    let replaced = entries.iter().filter(|entry| !entry.replaced.is_empty()).count();
+    println!("{} entries, {} replaced, {} conflicts", entries.len(), replaced, conflicts);
+    for entry in entries.iter().filter(|entry| entry.conflict) {
+        let sources: Vec<&Source> = entry.replaced.iter().chain(Some(&entry.source)).collect();
+        let clashing: Vec<String> = sources.iter()
+            .filter(|source| sources.iter().filter(|other| other.pack == source.pack).count() > 1)
+            .map(ToString::to_string)
+            .collect();
+        println!("conflict: {} is defined in {}; {} is used", entry.id, clashing.join(" and "), entry.source);
+    }
+    Ok(())
+}
+
+/// Upgrades a character file to the current schema, keeping a `.bak` of the
+/// original. With `dry_run` only the changes that would be made are printed.
+pub fn run_migrate(character_path: &Path, dry_run: bool) -> Result<(), SheetError> {
+    let loaded = load_character(character_path)?;
+
+    if loaded.migrations.is_empty() {
+        println!("{} is already up to date", character_path.display());
+        return Ok(());
+    }
+
+    for migration in &loaded.migrations {
+        println!("{}", migration);
+    }
+
+    if dry_run {
+        println!("dry run: {} not modified", character_path.display());
+        return Ok(());
+    }
+
+    save_character(&loaded.character, character_path)
+        .map_err(|e| SheetError::io(character_path, e))?;
+
+    println!("{} migrated", character_path.display());
+    Ok(())
+}
+
+/// Prints the JSON Schema of character files, or writes it to `output`.
+pub fn run_schema(output: Option<&str>) -> Result<(), SheetError> {
+    let schema = character_schema();
+
+    match output {
+        Some(output) => fs::write(output, schema + "\n")
+            .map_err(|e| SheetError::io(Path::new(output), e)),
+        None => {
+            println!("{}", schema);
+            Ok(())
+        }
+    }
+}
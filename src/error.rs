@@ -0,0 +1,164 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Everything that can go wrong while loading a character and running the app.
+#[derive(Debug)]
+pub enum SheetError {
+    /// The file could not be read or written.
+    Io { path: PathBuf, source: io::Error },
+    /// The file has a syntax error, or does not match the `Character` layout.
+    /// `line` is 0 when the position is unknown.
+    Parse {
+        path: PathBuf,
+        line: usize,
+        column: usize,
+        field: String,
+        message: String,
+        suggestion: Option<String>
+    },
+    /// The file parsed, but describes a character the sheet cannot represent.
+    Validation { path: PathBuf, field: String, message: String },
+    /// A command could not be carried out, e.g. an unknown skill to roll.
+    Command(String),
+    /// The terminal could not be set up or drawn to.
+    Terminal(io::Error)
+}
+
+impl SheetError {
+
+    pub fn io(path: &Path, source: io::Error) -> SheetError {
+        SheetError::Io { path: path.to_path_buf(), source }
+    }
+
+    pub fn parse(path: &Path, error: serde_path_to_error::Error<serde_json::Error>) -> SheetError {
+        let field = error.path().to_string();
+        let inner = error.inner();
+
+        // serde_json appends " at line X column Y" to its messages; the location
+        // is reported separately.
+        let message = inner.to_string();
+        let message = match message.rfind(" at line ") {
+            Some(index) => message[..index].to_owned(),
+            None => message
+        };
+
+        SheetError::parse_at(path, field, message, inner.line(), inner.column())
+    }
+
+    /// A parse error from any format, with the position when it is known.
+    pub fn parse_at(path: &Path, field: String, message: String, line: usize, column: usize) -> SheetError {
+        let suggestion = suggest_fix(&field, &message, line);
+
+        SheetError::Parse { path: path.to_path_buf(), line, column, field, message, suggestion }
+    }
+
+    pub fn validation(path: &Path, field: &str, message: String) -> SheetError {
+        SheetError::Validation { path: path.to_path_buf(), field: field.to_owned(), message }
+    }
+
+    /// The process exit code, following the BSD `sysexits.h` convention for
+    /// bad data (65) and I/O errors (74).
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            SheetError::Parse { .. } | SheetError::Validation { .. } => 65,
+            SheetError::Io { .. } => 74,
+            SheetError::Command(_) | SheetError::Terminal(_) => 1
+        }
+    }
+}
+
+impl fmt::Display for SheetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SheetError::Io { path, source } =>
+                write!(f, "could not access {}: {}", path.display(), source),
+            SheetError::Parse { path, line, column, field, message, suggestion } => {
+                // Errors found after a migration have no position in the original text.
+                if *line == 0 {
+                    write!(f, "{}: ", path.display())?;
+                } else {
+                    write!(f, "{}:{}:{}: ", path.display(), line, column)?;
+                }
+                if field != "." && field != "?" {
+                    write!(f, "in `{}`: ", field)?;
+                }
+                write!(f, "{}", message)?;
+                if let Some(suggestion) = suggestion {
+                    write!(f, "\n  suggestion: {}", suggestion)?;
+                }
+                Ok(())
+            },
+            SheetError::Validation { path, field, message } =>
+                write!(f, "{}: in `{}`: {}", path.display(), field, message),
+            SheetError::Command(message) =>
+                write!(f, "{}", message),
+            SheetError::Terminal(source) =>
+                write!(f, "terminal error: {}", source)
+        }
+    }
+}
+
+impl Error for SheetError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SheetError::Io { source, .. } | SheetError::Terminal(source) => Some(source),
+            _ => None
+        }
+    }
+}
+
+/// Returns the text between each pair of backticks, e.g. the variant names in
+/// "unknown variant `Intelligence`, expected one of `Strength`, `Dexterity`".
+fn backticked(message: &str) -> Vec<&str> {
+    message.split('`').skip(1).step_by(2).collect()
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + if a_char.eq_ignore_ascii_case(b_char) { 0 } else { 1 };
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+fn suggest_fix(field: &str, message: &str, line: usize) -> Option<String> {
+    if message.starts_with("unknown variant") {
+        let names = backticked(message);
+        let (found, expected) = names.split_first()?;
+
+        let closest = expected.iter().min_by_key(|candidate| edit_distance(found, candidate))?;
+        return Some(format!("did you mean `{}`? Expected one of: {}", closest, expected.join(", ")));
+    }
+
+    if let Some(lengths) = message.strip_prefix("invalid length ") {
+        let (found, expected) = lengths.split_once(", expected ")?;
+        return Some(format!("`{}` must be {} but has {} entries", field, expected, found));
+    }
+
+    if message.starts_with("missing field") {
+        let missing = backticked(message).first().copied()?;
+        return Some(format!("add a `{}` entry to the file", missing));
+    }
+
+    if message.starts_with("invalid type") || message.starts_with("invalid value") {
+        return Some(format!("check the value of `{}`", field));
+    }
+
+    if line > 0 && (message.starts_with("EOF") || message.starts_with("expected") || message.starts_with("trailing")) {
+        return Some(format!("look for a missing or extra comma, bracket or quote on or before line {}", line));
+    }
+
+    None
+}
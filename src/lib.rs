@@ -1,42 +1,79 @@
-use std::error::Error;
-use std::fs;
+use std::path::Path;
 
-use crate::character::Character;
-use crate::ui::MainApp;
+use crate::cli::Command;
+use crate::library::Library;
+use crate::rules::Rules;
+
+pub use crate::cli::{CliError, Config};
+pub use crate::error::SheetError;
 
 mod ui;
 mod character;
+mod cli;
+mod commands;
+mod error;
+mod library;
+mod rules;
 
 extern crate serde;
 #[macro_use]
 extern crate serde_derive;
 extern crate serde_json;
 
-pub struct Config {
-    pub character_filename: String
-}
-
-impl Config {
-    pub fn new(args: &[String]) -> Result<Config, &'static str> {
-        if args.len() < 2 {
-            return Err("not enough arguments")
-        }
+pub fn run(config: Config) -> Result<(), SheetError> {
+    let Config { command, library, content } = config;
+    let library = Library::open(library.as_deref());
+    let resolve_all = |files: Vec<String>| -> Vec<_> { files.iter().map(|file| library.resolve(file)).collect() };
+    // Only commands that look things up in the rules load the content packs.
+    let rules = |character: &Path| Rules::load(&content, Some(character));
 
-        let character_filename = args[1].clone();
-
-        Ok(Config { character_filename })
+    match command {
+        Command::List { format } => commands::run_list(&library, format),
+        Command::Show { file, autosave, format } => {
+            let path = match file {
+                Some(file) => library.resolve(&file),
+                None => match commands::pick_from_library(&library)? {
+                    Some(path) => path,
+                    None => return Ok(())
+                }
+            };
+            commands::run_show(&path, autosave, format, rules(&path)?)
+        },
+        Command::Roll { file, check, mode, format } => commands::run_roll(&library.resolve(&file), &check, mode, format),
+        Command::Validate { file, format } => commands::run_validate(&library.resolve(&file), format),
+        Command::Export { file, output } => commands::run_export(&library.resolve(&file), output.as_ref()),
+        Command::New { file, name: Some(name), race, class, hitpoints } =>
+            commands::run_new(&library.path_for_new(&file)?, &name, &race, &class, hitpoints),
+        Command::New { file, name: None, .. } => {
+            let path = library.path_for_new(&file)?;
+            commands::run_new_wizard(&path, rules(&path)?)
+        },
+        Command::LevelUp { file, hitpoints, ability_increases, feat, spells } => {
+            let path = library.resolve(&file);
+            commands::run_level_up(&path, &rules(&path)?, hitpoints, ability_increases, feat, spells)
+        },
+        Command::Damage { files, amount, damage_type } =>
+            commands::run_damage(&resolve_all(files), amount, damage_type.as_deref(), &content),
+        Command::Heal { files, amount } => commands::run_heal(&resolve_all(files), amount),
+        Command::Rest { files, rest } => commands::run_rest(&resolve_all(files), rest),
+        Command::Slot { files, change, level } => commands::run_slot(&resolve_all(files), change, level),
+        Command::Cast { file, spell, ritual } => {
+            let path = library.resolve(&file);
+            commands::run_cast(&path, &rules(&path)?, &spell, ritual)
+        },
+        Command::Prepare { file, spells, prepare } => {
+            let path = library.resolve(&file);
+            commands::run_prepare(&path, &rules(&path)?, &spells, prepare)
+        },
+        Command::Time { files, rounds } => commands::run_time(&resolve_all(files), rounds),
+        Command::Explain { file, value, format } => commands::run_explain(&library.resolve(&file), value.as_deref(), format),
+        Command::Experience { files, amount } => commands::run_experience(&resolve_all(files), amount),
+        Command::Milestone { files, milestone } => commands::run_milestone(&resolve_all(files), milestone),
+        Command::Content { file, changes, format } => {
+            let path = file.map(|file| library.resolve(&file));
+            commands::run_content(&Rules::load(&content, path.as_deref())?, changes, format)
+        },
+        Command::Migrate { file, dry_run } => commands::run_migrate(&library.resolve(&file), dry_run),
+        Command::Schema { output } => commands::run_schema(output.as_deref())
     }
 }
-
-pub fn run(config: Config) -> Result<(), Box<dyn Error>> {
-
-    let dandelion_json = fs::read_to_string(config.character_filename)?;
-
-    let dandelion: Character = serde_json::from_str(&dandelion_json)?;
-
-    let main_app = MainApp::new ( &dandelion )?;
-
-    main_app.draw_app()?;
-
-    Ok(())
-}
\ No newline at end of file
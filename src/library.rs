@@ -0,0 +1,158 @@
+use std::env;
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::character::{load_character, Format};
+use crate::error::SheetError;
+
+/// Overrides where the library lives, like `--library <dir>`.
+const LIBRARY_VARIABLE: &str = "SHEET_LIBRARY";
+
+/// One character file in the library, with what `list` shows about it.
+#[derive(Serialize)]
+pub struct Entry {
+    pub path: PathBuf,
+    pub name: String,
+    pub race: String,
+    pub class: String,
+    pub level: u16
+}
+
+/// The directory `Library::open` picks, given the values of `$SHEET_LIBRARY`,
+/// `$XDG_DATA_HOME` and `$HOME`.
+fn library_dir(dir: Option<&str>, library: Option<OsString>, data_home: Option<OsString>, home: Option<OsString>) -> PathBuf {
+    dir.map(PathBuf::from)
+        .or_else(|| library.map(PathBuf::from))
+        .unwrap_or_else(|| {
+            let data_home = data_home.map(PathBuf::from)
+                .or_else(|| home.map(|home| Path::new(&home).join(".local").join("share")))
+                .unwrap_or_else(|| PathBuf::from("."));
+
+            data_home.join(env!("CARGO_PKG_NAME")).join("characters")
+        })
+}
+
+/// A directory of character files that can be listed and looked up by name,
+/// so `sheet dandelion` works from anywhere.
+pub struct Library {
+    dir: PathBuf
+}
+
+impl Library {
+
+    /// `dir` wins, then `$SHEET_LIBRARY`, then `$XDG_DATA_HOME/terminal_test_1/characters`,
+    /// falling back to `~/.local/share` when XDG_DATA_HOME is not set.
+    pub fn open(dir: Option<&str>) -> Library {
+        let dir = library_dir(dir, env::var_os(LIBRARY_VARIABLE), env::var_os("XDG_DATA_HOME"), env::var_os("HOME"));
+
+        Library { dir }
+    }
+
+    pub fn dir(&self) -> &Path { &self.dir }
+
+    /// Every character file in the library, sorted by name, and the errors of
+    /// the files that failed to load so one broken file does not hide the rest.
+    pub fn entries(&self) -> (Vec<Entry>, Vec<SheetError>) {
+        let mut entries = Vec::new();
+        let mut problems = Vec::new();
+
+        for path in self.files() {
+            match load_character(&path) {
+                Ok(loaded) => entries.push(Entry {
+                    name: loaded.character.get_name().to_owned(),
+                    race: loaded.character.get_race().to_owned(),
+                    class: loaded.character.get_class().to_owned(),
+                    level: *loaded.character.get_level(),
+                    path
+                }),
+                Err(e) => problems.push(e)
+            }
+        }
+
+        entries.sort_by_key(|entry| entry.name.to_lowercase());
+        (entries, problems)
+    }
+
+    /// Turns a command line argument into a file: an existing path is used as
+    /// is, otherwise a library file with that file stem or character name,
+    /// ignoring case. Anything else is returned unchanged so the error names it.
+    pub fn resolve(&self, argument: &str) -> PathBuf {
+        let path = PathBuf::from(argument);
+        if path.exists() || path.components().count() > 1 {
+            return path;
+        }
+
+        let by_stem = self.files().into_iter().find(|file| {
+            file.file_stem().and_then(|stem| stem.to_str())
+                .map(|stem| stem.eq_ignore_ascii_case(argument))
+                .unwrap_or(false)
+        });
+
+        by_stem
+            .or_else(|| self.entries().0.into_iter()
+                .find(|entry| entry.name.eq_ignore_ascii_case(argument))
+                .map(|entry| entry.path))
+            .unwrap_or(path)
+    }
+
+    /// Where `new` writes: a bare name like `dandelion` goes into the library as
+    /// `dandelion.json`, creating the directory; anything that looks like a path is used as is.
+    pub fn path_for_new(&self, argument: &str) -> Result<PathBuf, SheetError> {
+        let path = PathBuf::from(argument);
+        if path.components().count() > 1 || path.extension().is_some() {
+            return Ok(path);
+        }
+
+        fs::create_dir_all(&self.dir)
+            .map_err(|e| SheetError::io(&self.dir, e))?;
+
+        Ok(self.dir.join(argument).with_extension("json"))
+    }
+
+    /// Character files in the library directory; a missing directory is an empty library.
+    fn files(&self) -> Vec<PathBuf> {
+        let mut files: Vec<PathBuf> = fs::read_dir(&self.dir).into_iter()
+            .flatten()
+            .filter_map(|file| file.ok().map(|file| file.path()))
+            .filter(|path| Format::from_extension(path).is_some())
+            .collect();
+        files.sort();
+        files
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DANDELION: &str = include_str!("../resources/characters/dandelion.json");
+
+    #[test]
+    fn library_dir_prefers_the_option_then_the_variable_then_xdg() {
+        let os = |value: &str| Some(OsString::from(value));
+        let characters = |dir: &str| Path::new(dir).join(env!("CARGO_PKG_NAME")).join("characters");
+
+        assert_eq!(library_dir(Some("given"), os("variable"), os("/data"), os("/home/me")), PathBuf::from("given"));
+        assert_eq!(library_dir(None, os("variable"), os("/data"), os("/home/me")), PathBuf::from("variable"));
+        assert_eq!(library_dir(None, None, os("/data"), os("/home/me")), characters("/data"));
+        assert_eq!(library_dir(None, None, None, os("/home/me")), characters("/home/me/.local/share"));
+        assert_eq!(library_dir(None, None, None, None), characters("."));
+    }
+
+    #[test]
+    fn resolve_tries_the_path_then_the_file_stem_then_the_name() {
+        let temp = tempfile::tempdir().unwrap();
+        let library = Library { dir: temp.path().to_owned() };
+        fs::write(temp.path().join("wizard.json"), DANDELION).unwrap();
+        fs::write(temp.path().join("gnome.json"), DANDELION.replace("\"Dandelion\"", "\"Wizard\"")).unwrap();
+
+        // An existing path, or anything with a directory in it, is used as is.
+        assert_eq!(library.resolve("Cargo.toml"), PathBuf::from("Cargo.toml"));
+        assert_eq!(library.resolve("party/wizard.json"), PathBuf::from("party/wizard.json"));
+        // The file stem wins over a character with that name.
+        assert_eq!(library.resolve("WIZARD"), temp.path().join("wizard.json"));
+        assert_eq!(library.resolve("dandelion"), temp.path().join("wizard.json"));
+        assert_eq!(library.resolve("nobody"), PathBuf::from("nobody"));
+    }
+}
@@ -1,20 +1,27 @@
 use std::process;
 use std::env;
 
-use terminal_test_1::Config;
+use terminal_test_1::{CliError, Config};
 
 fn main() {
 
     let args: Vec<String> = env::args().collect();
     let config = Config::new(&args).unwrap_or_else(|err|{
-        println!("Problem parsing arguments: {}", err);
-
-        process::exit(1);
+        match err {
+            CliError::Help(usage) => {
+                println!("{}", usage);
+                process::exit(0);
+            },
+            CliError::Usage { .. } => {
+                eprintln!("Problem parsing arguments: {}", err);
+                process::exit(2);
+            }
+        }
     });
 
     if let Err(e) = terminal_test_1::run(config) {
-        println!("Application Error: {}", e);
+        eprintln!("Application Error: {}", e);
 
-        process::exit(1);
+        process::exit(e.exit_code());
     }
-}
\ No newline at end of file
+}
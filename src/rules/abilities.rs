@@ -0,0 +1,73 @@
+use std::fmt;
+
+/// The scores every character can assign, one to each ability.
+pub const STANDARD_ARRAY: [i16; 6] = [15, 14, 13, 12, 10, 8];
+
+/// Points to spend with point buy, where every score starts at 8.
+pub const POINT_BUY_BUDGET: i16 = 27;
+pub const POINT_BUY_MIN: i16 = 8;
+pub const POINT_BUY_MAX: i16 = 15;
+
+/// The three ways the Player's Handbook offers to come up with ability scores.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Generation {
+    StandardArray,
+    PointBuy,
+    Rolled
+}
+
+impl Generation {
+    pub const ALL: [Generation; 3] = [Generation::StandardArray, Generation::PointBuy, Generation::Rolled];
+
+    pub fn next(self) -> Generation {
+        let index = Generation::ALL.iter().position(|generation| *generation == self).unwrap_or(0);
+        Generation::ALL[(index + 1) % Generation::ALL.len()]
+    }
+}
+
+impl fmt::Display for Generation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Generation::StandardArray => "Standard array",
+            Generation::PointBuy => "Point buy",
+            Generation::Rolled => "4d6 drop lowest"
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+/// What a score costs with point buy: 8 is free, 13 costs 5 and 15 costs 9.
+/// `None` outside of 8 to 15.
+pub fn point_buy_cost(score: i16) -> Option<i16> {
+    match score {
+        8..=13 => Some(score - 8),
+        14 => Some(7),
+        15 => Some(9),
+        _ => None
+    }
+}
+
+/// The points spent on `scores`, or `None` if one cannot be bought.
+pub fn point_buy_total(scores: &[i16]) -> Option<i16> {
+    scores.iter().map(|score| point_buy_cost(*score)).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn point_buy_total_adds_the_cost_of_each_score() {
+        assert_eq!(point_buy_total(&[8; 6]), Some(0));
+        assert_eq!(point_buy_total(&[15, 15, 15, 8, 8, 8]), Some(POINT_BUY_BUDGET));
+        assert_eq!(point_buy_total(&[14, 13, 12, 10, 10, 8]), Some(20));
+        assert_eq!(point_buy_total(&[]), Some(0));
+    }
+
+    #[test]
+    fn point_buy_total_rejects_scores_outside_the_table() {
+        assert_eq!(point_buy_total(&[15, 16, 8, 8, 8, 8]), None);
+        assert_eq!(point_buy_total(&[7, 8, 8, 8, 8, 8]), None);
+    }
+}
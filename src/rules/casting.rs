@@ -0,0 +1,205 @@
+use crate::character::{concentration_save_dc, ordinal, ActiveEffect, Character, ConcentrationSave, Condition, Duration};
+use crate::rules::{Entry, Preparation, Rules, Spell, Spellcasting};
+
+impl Rules {
+
+    /// The name of the spell `reference` points to, or the reference itself
+    /// for spells missing from the rules.
+    pub fn spell_name<'a>(&'a self, reference: &'a str) -> &'a str {
+        self.find_spell(reference).map(|spell| spell.name.as_str()).unwrap_or(reference)
+    }
+
+    /// The spell whose concentration casting `spell` would end, by name.
+    pub fn concentration_conflict<'a>(&'a self, character: &'a Character, spell: &Spell) -> Option<&'a str> {
+        let current = character.get_concentration()?;
+
+        if spell.concentration && !spell.matches(current) { Some(self.spell_name(current)) } else { None }
+    }
+
+    fn spellcasting_of(&self, character: &Character) -> Option<&Spellcasting> {
+        self.find_class(character.get_class())?.spellcasting.as_ref()
+    }
+
+    /// How many spells `character` can prepare; `None` for classes that cast
+    /// every spell they know, and classes missing from the rules.
+    pub fn preparation_limit(&self, character: &Character) -> Option<usize> {
+        let spellcasting = self.spellcasting_of(character).filter(|spellcasting| spellcasting.preparation != Preparation::Known)?;
+        let modifier = character.get_modifier(&spellcasting.ability).unwrap_or(0);
+
+        Some(spellcasting.preparation_limit(*character.get_level(), modifier))
+    }
+
+    /// Whether `spell` is ready to cast with a slot: cantrips always are,
+    /// other spells once prepared by classes that prepare them.
+    pub fn is_ready(&self, character: &Character, spell: &Spell) -> bool {
+        spell.level == 0
+            || self.preparation_limit(character).is_none()
+            || character.is_prepared(&spell.id())
+    }
+
+    /// Prepares or unprepares `spell`, keeping to the number the class can prepare.
+    pub fn prepare_spell(&self, character: &mut Character, spell: &Spell, prepare: bool) -> Result<String, String> {
+        let class = character.get_class().to_owned();
+        let spellcasting = self.spellcasting_of(character)
+            .ok_or_else(|| format!("{} does not prepare spells", class))?;
+        let limit = self.preparation_limit(character)
+            .ok_or_else(|| format!("{} knows their spells and casts them without preparing", character.get_name()))?;
+
+        if prepare {
+            if spell.level == 0 {
+                return Err(format!("{} is a cantrip, which is always ready", spell.name));
+            }
+            let known = character.get_spells().iter().any(|known| spell.matches(known));
+            match spellcasting.preparation {
+                Preparation::Spellbook if !known => return Err(format!("{} is not in the spellbook", spell.name)),
+                Preparation::ClassList if !spell.classes.iter().any(|name| name.eq_ignore_ascii_case(&class)) =>
+                    return Err(format!("{} is not a {} spell", spell.name, class)),
+                _ => {}
+            }
+            let max_level = character.get_spell_slots().iter().map(|slots| slots.level).max().unwrap_or(0);
+            if spell.level > max_level {
+                return Err(format!("{} needs a {} level spell slot", spell.name, ordinal(spell.level)));
+            }
+        }
+
+        character.set_prepared(&spell.id(), prepare, limit)?;
+
+        let done = if prepare { "Prepared" } else { "Unprepared" };
+        Ok(format!("{} {} ({}/{} prepared)", done, spell.name, character.get_prepared_spells().len(), limit))
+    }
+
+    /// Checks that `character` has room to learn `spell`: classes that know
+    /// their spells stop at the number their level allows, and every class
+    /// at its number of cantrips.
+    pub fn check_can_learn(&self, character: &Character, spell: &Spell) -> Result<(), String> {
+        let spellcasting = match self.spellcasting_of(character) {
+            Some(spellcasting) => spellcasting,
+            None => return Ok(())
+        };
+        let level = *character.get_level();
+        let known = character.get_spells().iter()
+            .filter_map(|reference| self.find_spell(reference))
+            .filter(|known| (known.level == 0) == (spell.level == 0))
+            .count();
+
+        let limit = if spell.level == 0 {
+            Some(spellcasting.cantrips_known_at(level))
+        } else if spellcasting.preparation == Preparation::Known {
+            spellcasting.spells_known_at(level)
+        } else {
+            None
+        };
+
+        match limit {
+            Some(limit) if known >= limit => Err(format!("{} already knows {} {}, the most a level {} {} can",
+                character.get_name(), limit, if spell.level == 0 { "cantrips" } else { "spells" }, level, character.get_class())),
+            _ => Ok(())
+        }
+    }
+
+    /// Casts `spell` with the lowest spell slot that fits, starts concentrating
+    /// on it if it needs concentration and starts its effects on the caster.
+    /// Cantrips take no slot, and classes that prepare spells need it prepared.
+    pub fn cast_spell(&self, character: &mut Character, spell: &Spell) -> Result<Vec<String>, String> {
+        if !self.is_ready(character, spell) {
+            return Err(format!("{} is not prepared", spell.name));
+        }
+
+        let mut log = Vec::new();
+
+        if spell.level == 0 {
+            log.push(format!("Cast {}", spell.name));
+        } else {
+            let (level, remaining) = character.use_lowest_spell_slot(spell.level).map_err(|e| e.to_string())?;
+            log.push(format!("Cast {} with a {} level slot ({} left)", spell.name, ordinal(level), remaining));
+        }
+
+        self.start_effects(character, spell, &mut log);
+        Ok(log)
+    }
+
+    /// Casts a ritual spell without a slot, taking 10 minutes longer. Wizards
+    /// cast any ritual in their spellbook, other classes with ritual casting
+    /// only rituals they have prepared or know.
+    pub fn cast_ritual(&self, character: &mut Character, spell: &Spell) -> Result<Vec<String>, String> {
+        let spellcasting = self.spellcasting_of(character)
+            .ok_or_else(|| format!("{} is not a spellcasting class in the rules", character.get_class()))?;
+
+        if !spell.ritual {
+            return Err(format!("{} is not a ritual", spell.name));
+        }
+        if !spellcasting.ritual_casting {
+            return Err(format!("a {} cannot cast rituals", character.get_class()));
+        }
+        let available = match spellcasting.preparation {
+            Preparation::ClassList => character.is_prepared(&spell.id()),
+            Preparation::Spellbook | Preparation::Known => character.get_spells().iter().any(|known| spell.matches(known))
+        };
+        if !available {
+            let wanted = if spellcasting.preparation == Preparation::ClassList { "prepared" } else { "known" };
+            return Err(format!("{} must be {} to cast it as a ritual", spell.name, wanted));
+        }
+
+        let mut log = vec![format!("Cast {} as a ritual, taking 10 minutes longer", spell.name)];
+        self.start_effects(character, spell, &mut log);
+        Ok(log)
+    }
+
+    fn start_effects(&self, character: &mut Character, spell: &Spell, log: &mut Vec<String>) {
+        if spell.concentration {
+            if let Some(previous) = character.start_concentration(&spell.id()) {
+                log.push(format!("Stopped concentrating on {}", self.spell_name(&previous)));
+            }
+            log.push(format!("Concentrating on {}", spell.name));
+        }
+
+        if let (false, Some(duration)) = (spell.modifiers.is_empty(), Duration::from_text(&spell.duration)) {
+            log.push(character.add_effect(ActiveEffect {
+                name: spell.name.clone(),
+                spell: Some(spell.id()),
+                concentration: spell.concentration,
+                modifiers: spell.modifiers.clone(),
+                duration
+            }));
+        }
+    }
+
+    /// Applies damage, halved for a character resistant to `damage_type`. A
+    /// character knocked unconscious stops concentrating; one who stays up
+    /// must make a Constitution save, whose DC is returned.
+    pub fn take_damage(&self, character: &mut Character, amount: u16, damage_type: Option<&str>) -> (Vec<String>, Option<u16>) {
+        let mut log = Vec::new();
+
+        let resistance = damage_type.and_then(|damage_type| Some((damage_type, character.resistance_to(damage_type)?)));
+        let amount = match resistance {
+            Some((damage_type, source)) => {
+                log.push(format!("Resistant to {} damage ({}): {} -> {}", damage_type, source, amount, amount / 2));
+                amount / 2
+            },
+            None => amount
+        };
+        log.extend(character.take_damage(amount));
+
+        let spell = match character.get_concentration() {
+            Some(spell) => self.spell_name(spell).to_owned(),
+            None => return (log, None)
+        };
+
+        if character.has_condition(Condition::Unconscious) {
+            character.end_concentration();
+            log.push(format!("Lost concentration on {}", spell));
+            return (log, None);
+        }
+
+        (log, Some(concentration_save_dc(amount)))
+    }
+
+    /// "CON save DC 12 for Bless: 15 (d20 13 +2), concentration kept".
+    pub fn describe_concentration_save(&self, save: &ConcentrationSave) -> String {
+        let rolls: Vec<String> = save.rolls.iter().map(i16::to_string).collect();
+        let outcome = if save.kept { "concentration kept" } else { "concentration lost" };
+
+        format!("CON save DC {} for {}: {} (d20 {} {:+}), {}",
+            save.dc, self.spell_name(&save.spell), save.total, rolls.join(", "), save.bonus, outcome)
+    }
+}
@@ -0,0 +1,118 @@
+use crate::rules::{Rules, Spell};
+
+/// How well `query` matches `text` when its letters appear in order, ignoring
+/// case and spaces, e.g. "mgmis" in "Magic Missile". `None` if they do not;
+/// otherwise higher for letters that start words or follow each other.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut start = 0;
+    let mut previous: Option<usize> = None;
+
+    for wanted in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let index = (start..text.len()).find(|index| text[*index] == wanted)?;
+
+        score += 1;
+        if index == 0 || !text[index - 1].is_alphanumeric() {
+            score += 3;
+        }
+        match previous {
+            Some(previous) if previous + 1 == index => score += 5,
+            Some(_) => score -= (index - start).min(3) as i32,
+            None => {}
+        }
+
+        previous = Some(index);
+        start = index + 1;
+    }
+
+    Some(score)
+}
+
+/// The search and filters of the compendium. Every field left empty lets all
+/// spells through.
+#[derive(Default)]
+pub struct SpellFilter {
+    pub query: String,
+    pub class: Option<String>,
+    pub level: Option<u8>,
+    pub school: Option<String>,
+    /// Only rituals, or only spells that are not.
+    pub ritual: Option<bool>,
+    /// Only concentration spells, or only spells without it.
+    pub concentration: Option<bool>
+}
+
+impl SpellFilter {
+
+    fn allows(&self, spell: &Spell) -> bool {
+        self.class.as_ref().is_none_or(|class| spell.classes.iter().any(|name| name.eq_ignore_ascii_case(class)))
+            && self.level.is_none_or(|level| spell.level == level)
+            && self.school.as_ref().is_none_or(|school| spell.school.eq_ignore_ascii_case(school))
+            && self.ritual.is_none_or(|ritual| spell.ritual == ritual)
+            && self.concentration.is_none_or(|concentration| spell.concentration == concentration)
+    }
+}
+
+impl Rules {
+
+    /// The spells `filter` lets through, best match for the search first, then
+    /// by level and name.
+    pub fn search_spells(&self, filter: &SpellFilter) -> Vec<&Spell> {
+        let mut spells: Vec<(i32, &Spell)> = self.spells.iter()
+            .filter(|spell| filter.allows(spell))
+            .filter_map(|spell| fuzzy_score(&filter.query, &spell.name).map(|score| (score, spell)))
+            .collect();
+
+        spells.sort_by(|(a_score, a), (b_score, b)| b_score.cmp(a_score)
+            .then_with(|| a.level.cmp(&b.level))
+            .then_with(|| a.name.cmp(&b.name)));
+        spells.into_iter().map(|(_, spell)| spell).collect()
+    }
+
+    /// Every class that has spells, in name order, for the class filter to step through.
+    pub fn spell_classes(&self) -> Vec<&str> {
+        let mut classes: Vec<&str> = self.spells.iter()
+            .flat_map(|spell| spell.classes.iter().map(String::as_str))
+            .collect();
+        classes.sort_unstable();
+        classes.dedup();
+        classes
+    }
+
+    /// Every school of magic with spells, in name order.
+    pub fn spell_schools(&self) -> Vec<&str> {
+        let mut schools: Vec<&str> = self.spells.iter().map(|spell| spell.school.as_str()).collect();
+        schools.sort_unstable();
+        schools.dedup();
+        schools
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fuzzy_score_needs_the_letters_in_order() {
+        assert!(fuzzy_score("mgmis", "Magic Missile").is_some());
+        assert!(fuzzy_score("MAGIC missile", "Magic Missile").is_some());
+        assert_eq!(fuzzy_score("simg", "Magic Missile"), None);
+        assert_eq!(fuzzy_score("x", "Magic Missile"), None);
+    }
+
+    #[test]
+    fn fuzzy_score_lets_everything_through_an_empty_query() {
+        assert_eq!(fuzzy_score("", "Shield"), Some(0));
+        assert_eq!(fuzzy_score("   ", "Shield"), Some(0));
+    }
+
+    #[test]
+    fn fuzzy_score_prefers_word_starts_and_runs_of_letters() {
+        let exact = fuzzy_score("shield", "Shield").unwrap();
+        let scattered = fuzzy_score("shield", "Shillelagh and Heal Dim").unwrap_or(i32::MIN);
+        assert!(exact > scattered);
+
+        assert!(fuzzy_score("fire", "Fire Bolt").unwrap() > fuzzy_score("fire", "Faerie Fire").unwrap());
+    }
+}
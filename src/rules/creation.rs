@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+
+use crate::character::{AbilityRoll, Character, FeatureSource, Item, NewCharacter, SpellSlots, Stat};
+use crate::rules::{Background, Class, EquipmentPack, Race, Subrace};
+
+/// Everything a player picks for a new character, taken from one set of rules.
+pub struct Choices<'a> {
+    pub name: String,
+    pub race: &'a Race,
+    pub subrace: Option<&'a Subrace>,
+    pub class: &'a Class,
+    pub background: &'a Background,
+    pub equipment_pack: Option<&'a EquipmentPack>,
+    /// Ability scores before racial bonuses.
+    pub base_scores: HashMap<Stat, i16>,
+    /// The 4d6 rolls behind `base_scores`, if they were rolled.
+    pub ability_rolls: Vec<AbilityRoll>,
+    /// The skills chosen from the class list.
+    pub skills: Vec<String>,
+    /// Spell IDs, e.g. "spell:fire-bolt".
+    pub spells: Vec<String>
+}
+
+impl Choices<'_> {
+
+    pub fn racial_bonus(&self, stat: Stat) -> i16 {
+        let race = self.race.ability_bonuses.get(&stat).copied().unwrap_or(0);
+        let subrace = self.subrace.and_then(|subrace| subrace.ability_bonuses.get(&stat)).copied().unwrap_or(0);
+
+        race + subrace
+    }
+
+    /// Ability scores with racial bonuses applied.
+    pub fn ability_scores(&self) -> HashMap<Stat, i16> {
+        Stat::ALL.iter()
+            .map(|stat| (*stat, self.base_scores.get(stat).copied().unwrap_or(10) + self.racial_bonus(*stat)))
+            .collect()
+    }
+
+    /// Skills the race and background already give, which the class choice cannot repeat.
+    pub fn granted_skills(&self) -> Vec<&str> {
+        self.race.skills.iter().chain(self.background.skills.iter())
+            .map(|skill| skill.as_str())
+            .collect()
+    }
+
+    /// The race and the subrace joined the way the sheet shows them, e.g. "Rock Gnome".
+    pub fn race_name(&self) -> String {
+        match self.subrace {
+            Some(subrace) => subrace.name.clone(),
+            None => self.race.name.clone()
+        }
+    }
+
+    /// Class gear, then the equipment pack, then background gear. Items that
+    /// appear more than once are merged into one stack.
+    fn inventory(&self) -> Vec<Item> {
+        let templates = self.class.equipment.iter()
+            .chain(self.equipment_pack.iter().flat_map(|pack| pack.items.iter()))
+            .chain(self.background.equipment.iter());
+
+        let mut inventory: Vec<Item> = Vec::new();
+        for template in templates {
+            match inventory.iter_mut().find(|item| item.name.eq_ignore_ascii_case(&template.name)) {
+                Some(item) => item.quantity += template.quantity,
+                None => inventory.push(template.to_item())
+            }
+        }
+
+        inventory
+    }
+
+    /// Builds the level 1 character, with every derived value filled in.
+    pub fn build(&self) -> Character {
+        let race_features = self.race.features.iter()
+            .chain(self.subrace.iter().flat_map(|subrace| subrace.features.iter()))
+            .map(|feature| feature.to_feature(FeatureSource::Race));
+        let class_features = self.class.features.iter()
+            .map(|feature| feature.to_feature(FeatureSource::Class));
+        let background_features = self.background.features.iter()
+            .map(|feature| feature.to_feature(FeatureSource::Background));
+
+        let spellcasting = self.class.spellcasting.as_ref().filter(|spellcasting| spellcasting.first_level <= 1);
+        let spell_slots = spellcasting
+            .map(|spellcasting| spellcasting.slots_at(1).into_iter()
+                .map(|(level, max)| SpellSlots { level, max, current: max })
+                .collect())
+            .unwrap_or_default();
+
+        let mut proficiencies = self.race.proficiencies.clone();
+        for other in self.subrace.iter().map(|subrace| &subrace.proficiencies)
+            .chain(vec![&self.class.proficiencies, &self.background.proficiencies]) {
+            proficiencies.merge(other);
+        }
+
+        let skill_proficiencies = self.granted_skills().into_iter()
+            .map(str::to_owned)
+            .chain(self.skills.iter().cloned())
+            .collect();
+
+        Character::create(NewCharacter {
+            name: self.name.trim().to_owned(),
+            race: self.race_name(),
+            class: self.class.name.clone(),
+            background: self.background.name.clone(),
+            stats: self.ability_scores(),
+            ability_rolls: self.ability_rolls.clone(),
+            hitpoints: self.class.hit_die + self.subrace.map(|subrace| subrace.hitpoints_per_level).unwrap_or(0),
+            walking_speed_in_ft: self.subrace.and_then(|subrace| subrace.speed).unwrap_or(self.race.speed),
+            saving_throws: self.class.saving_throws.clone(),
+            skill_proficiencies,
+            proficiencies,
+            spellcasting_ability: spellcasting.map(|spellcasting| spellcasting.ability),
+            spell_slots,
+            spells: self.spells.clone(),
+            features: race_features.chain(class_features).chain(background_features).collect(),
+            inventory: self.inventory()
+        })
+    }
+}
@@ -0,0 +1,301 @@
+use std::collections::HashMap;
+
+use crate::character::{Feature, FeatureSource, Item, Modifier, Proficiencies, Recharge, Stat, Uses};
+
+pub use self::abilities::{point_buy_cost, point_buy_total, Generation};
+pub use self::abilities::{POINT_BUY_BUDGET, POINT_BUY_MAX, POINT_BUY_MIN, STANDARD_ARRAY};
+pub use self::compendium::SpellFilter;
+pub use self::creation::Choices;
+pub use self::pack::{slug, Entry, Provenance, Source};
+pub use self::progression::{parse_ability_increases, HitPointGain, LevelUpChoices, LevelUpPlan};
+pub use self::spell::Spell;
+
+mod abilities;
+mod casting;
+mod compendium;
+mod creation;
+mod pack;
+mod progression;
+mod spell;
+
+/// The System Reference Document content every build ships with.
+const SRD: &str = include_str!("../../resources/rules/srd.json");
+
+fn default_quantity() -> u16 { 1 }
+
+fn default_first_level() -> u16 { 1 }
+
+fn default_preparation() -> Preparation { Preparation::Known }
+
+fn default_ability_score_improvements() -> Vec<u16> { vec![4, 8, 12, 16, 19] }
+
+/// A limited use as the rules describe it: every use is available at the start.
+#[derive(Deserialize)]
+pub struct UsesTemplate {
+    pub max: u16,
+    pub recharge: Recharge
+}
+
+/// A racial trait, class feature, background feature or feat.
+#[derive(Deserialize)]
+pub struct FeatureTemplate {
+    /// Only used by feats, see `Entry`.
+    #[serde(default)]
+    pub id: Option<String>,
+    pub name: String,
+    pub description: String,
+    #[serde(default)]
+    pub uses: Option<UsesTemplate>,
+    #[serde(default)]
+    pub modifiers: Vec<Modifier>
+}
+
+impl FeatureTemplate {
+    pub fn to_feature(&self, source: FeatureSource) -> Feature {
+        Feature {
+            name: self.name.clone(),
+            source,
+            description: self.description.clone(),
+            uses: self.uses.as_ref().map(|uses| Uses { max: uses.max, current: uses.max, recharge: uses.recharge }),
+            modifiers: self.modifiers.clone()
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ItemTemplate {
+    pub name: String,
+    #[serde(default = "default_quantity")]
+    pub quantity: u16
+}
+
+impl ItemTemplate {
+    pub fn to_item(&self) -> Item {
+        Item {
+            name: self.name.clone(),
+            quantity: self.quantity,
+            requires_attunement: false,
+            attuned: false,
+            charges: None,
+            modifiers: Vec::new()
+        }
+    }
+}
+
+/// What an item is, for the inventory to show next to its name.
+#[derive(Deserialize)]
+pub struct ItemDefinition {
+    #[serde(default)]
+    pub id: Option<String>,
+    pub name: String,
+    /// E.g. "Simple melee weapon" or "Ring, rare (requires attunement)".
+    pub category: String,
+    /// A few words on what it does, e.g. "1d6 bludgeoning; versatile (1d8)".
+    #[serde(default)]
+    pub description: String
+}
+
+#[derive(Deserialize)]
+pub struct Race {
+    #[serde(default)]
+    pub id: Option<String>,
+    pub name: String,
+    pub ability_bonuses: HashMap<Stat, i16>,
+    pub speed: u16,
+    /// Skill proficiencies every member of the race has, e.g. Perception for elves.
+    #[serde(default)]
+    pub skills: Vec<String>,
+    /// Languages, and weapons for races like dwarves.
+    #[serde(default)]
+    pub proficiencies: Proficiencies,
+    #[serde(default)]
+    pub features: Vec<FeatureTemplate>,
+    #[serde(default)]
+    pub subraces: Vec<Subrace>
+}
+
+#[derive(Deserialize)]
+pub struct Subrace {
+    pub name: String,
+    pub ability_bonuses: HashMap<Stat, i16>,
+    /// Replaces the speed of the race, e.g. 35 feet for wood elves.
+    #[serde(default)]
+    pub speed: Option<u16>,
+    #[serde(default)]
+    pub hitpoints_per_level: u16,
+    /// Added to those of the race, e.g. the armor of mountain dwarves.
+    #[serde(default)]
+    pub proficiencies: Proficiencies,
+    #[serde(default)]
+    pub features: Vec<FeatureTemplate>
+}
+
+/// How a class gains spell slots: full casters like wizards, half casters
+/// like paladins and the short rest pact magic of warlocks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub enum Caster {
+    Full,
+    Half,
+    Pact
+}
+
+/// Which spells a class can cast on a given day.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub enum Preparation {
+    /// Every spell known is ready, e.g. sorcerers. `spells_known` caps how many.
+    Known,
+    /// Prepares spells from the spellbook after a long rest, e.g. wizards.
+    Spellbook,
+    /// Prepares spells from the whole class list after a long rest, e.g. clerics.
+    ClassList
+}
+
+#[derive(Deserialize)]
+pub struct Spellcasting {
+    pub ability: Stat,
+    pub caster: Caster,
+    /// The class level spellcasting starts at.
+    #[serde(default = "default_first_level")]
+    pub first_level: u16,
+    /// Cantrips known at the first spellcasting level.
+    #[serde(default)]
+    pub cantrips: usize,
+    /// 1st level spells known, or written in the spellbook, at the first
+    /// spellcasting level. 0 for classes that prepare from their whole list.
+    #[serde(default)]
+    pub spells: usize,
+    /// Levels after the first that bring one more cantrip.
+    #[serde(default)]
+    pub cantrips_at: Vec<u16>,
+    /// Spells learned, or copied into the spellbook, on every later level.
+    #[serde(default)]
+    pub spells_per_level: usize,
+    #[serde(default = "default_preparation")]
+    pub preparation: Preparation,
+    /// Leveled spells a `Known` caster can know at each class level from 1st
+    /// to 20th. Empty for no limit.
+    #[serde(default)]
+    pub spells_known: Vec<usize>,
+    /// Whether ritual spells can be cast as rituals, without a spell slot.
+    #[serde(default)]
+    pub ritual_casting: bool
+}
+
+/// The features a class gains at one level after the first.
+#[derive(Deserialize)]
+pub struct ClassLevel {
+    pub level: u16,
+    pub features: Vec<FeatureTemplate>
+}
+
+#[derive(Deserialize)]
+pub struct Class {
+    #[serde(default)]
+    pub id: Option<String>,
+    pub name: String,
+    pub hit_die: u16,
+    pub saving_throws: Vec<Stat>,
+    /// How many of `skills` a new character of the class chooses.
+    pub skill_choices: usize,
+    pub skills: Vec<String>,
+    /// Armor, weapons and tools the class is trained with.
+    #[serde(default)]
+    pub proficiencies: Proficiencies,
+    #[serde(default)]
+    pub spellcasting: Option<Spellcasting>,
+    #[serde(default)]
+    pub features: Vec<FeatureTemplate>,
+    /// Names of the equipment packs the class chooses from.
+    #[serde(default)]
+    pub equipment_packs: Vec<String>,
+    #[serde(default)]
+    pub equipment: Vec<ItemTemplate>,
+    /// Features by level. A feature with the name of one the character
+    /// already has replaces it, e.g. Rage with more uses.
+    #[serde(default)]
+    pub progression: Vec<ClassLevel>,
+    #[serde(default = "default_ability_score_improvements")]
+    pub ability_score_improvements: Vec<u16>
+}
+
+#[derive(Deserialize)]
+pub struct Background {
+    #[serde(default)]
+    pub id: Option<String>,
+    pub name: String,
+    pub skills: Vec<String>,
+    #[serde(default)]
+    pub proficiencies: Proficiencies,
+    #[serde(default)]
+    pub features: Vec<FeatureTemplate>,
+    #[serde(default)]
+    pub equipment: Vec<ItemTemplate>
+}
+
+#[derive(Deserialize)]
+pub struct EquipmentPack {
+    #[serde(default)]
+    pub id: Option<String>,
+    pub name: String,
+    pub items: Vec<ItemTemplate>
+}
+
+/// Races, classes, backgrounds, feats, items and spells to build characters
+/// from. Every section may be missing from a content pack file.
+#[derive(Default, Deserialize)]
+#[serde(default)]
+pub struct Rules {
+    pub races: Vec<Race>,
+    pub classes: Vec<Class>,
+    pub backgrounds: Vec<Background>,
+    pub feats: Vec<FeatureTemplate>,
+    pub items: Vec<ItemDefinition>,
+    pub equipment_packs: Vec<EquipmentPack>,
+    pub spells: Vec<Spell>,
+    /// Every definition of each ID, from the SRD on. The last one is in use.
+    #[serde(skip)]
+    pub sources: HashMap<String, Vec<Source>>,
+    /// The pack directories loaded on top of the SRD, in order.
+    #[serde(skip)]
+    pub packs: Vec<String>
+}
+
+impl Rules {
+
+    pub fn find_equipment_pack(&self, reference: &str) -> Option<&EquipmentPack> {
+        self.equipment_packs.iter().find(|pack| pack.matches(reference))
+    }
+
+    /// Looks a feat up by ID, e.g. "feat:war-caster", or by name.
+    pub fn find_feat(&self, reference: &str) -> Option<&FeatureTemplate> {
+        self.feats.iter().find(|feat| feat.matches(reference))
+    }
+
+    /// The feature a feat gives; a bare one named after it for feats the
+    /// rules do not have.
+    pub fn feat_feature(&self, name: String) -> Feature {
+        match self.find_feat(&name) {
+            Some(feat) => feat.to_feature(FeatureSource::Feat),
+            None => Feature { name, source: FeatureSource::Feat, description: String::new(), uses: None, modifiers: Vec::new() }
+        }
+    }
+
+    pub fn find_item(&self, reference: &str) -> Option<&ItemDefinition> {
+        self.items.iter().find(|item| item.matches(reference))
+    }
+
+    /// Looks a spell up by ID, e.g. "spell:fire-bolt", or by name.
+    pub fn find_spell(&self, reference: &str) -> Option<&Spell> {
+        self.spells.iter().find(|spell| spell.matches(reference))
+    }
+
+    /// The spells of `class` up to `max_level`, cantrips first, then by name.
+    pub fn class_spells(&self, class: &str, max_level: u8) -> Vec<&Spell> {
+        let mut spells: Vec<&Spell> = self.spells.iter()
+            .filter(|spell| spell.level <= max_level && spell.classes.iter().any(|name| name.eq_ignore_ascii_case(class)))
+            .collect();
+
+        spells.sort_by(|a, b| a.level.cmp(&b.level).then_with(|| a.name.cmp(&b.name)));
+        spells
+    }
+}
@@ -0,0 +1,311 @@
+use std::env;
+use std::ffi::OsStr;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::character::Format;
+use crate::error::SheetError;
+use crate::rules::{Background, Class, EquipmentPack, FeatureTemplate, ItemDefinition, Race, Rules, Spell, SRD};
+
+/// Directories of content packs, separated like `$PATH`, used when no
+/// `--content <dir>` is given.
+const CONTENT_VARIABLE: &str = "SHEET_CONTENT";
+
+/// The extension of the pack directory next to a character file that only
+/// that character uses, e.g. `dandelion.content` for `dandelion.json`.
+const LOCAL_PACK_EXTENSION: &str = "content";
+
+/// The part of an ID after the kind: lower case words joined by dashes,
+/// e.g. "Tinker's Tools" -> "tinkers-tools".
+pub fn slug(name: &str) -> String {
+    name.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric() && c != '\'')
+        .map(|word| word.replace('\'', ""))
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// A definition in a content pack that characters can reference by ID,
+/// e.g. "spell:fire-bolt".
+pub trait Entry {
+    /// The prefix of the IDs, e.g. "spell".
+    const KIND: &'static str;
+
+    fn name(&self) -> &str;
+
+    /// The slug given in the pack, for entries whose ID should not follow the name.
+    fn explicit_slug(&self) -> Option<&str>;
+
+    fn id(&self) -> String {
+        let slug = self.explicit_slug().map(str::to_owned).unwrap_or_else(|| slug(self.name()));
+        format!("{}:{}", Self::KIND, slug)
+    }
+
+    /// Whether `reference` is the ID of this entry, or its name.
+    fn matches(&self, reference: &str) -> bool {
+        self.id().eq_ignore_ascii_case(reference) || self.name().eq_ignore_ascii_case(reference)
+    }
+}
+
+macro_rules! entry {
+    ($type:ty, $kind:expr) => {
+        impl Entry for $type {
+            const KIND: &'static str = $kind;
+
+            fn name(&self) -> &str { &self.name }
+
+            fn explicit_slug(&self) -> Option<&str> { self.id.as_deref() }
+        }
+    };
+}
+
+entry!(Race, "race");
+entry!(Class, "class");
+entry!(Background, "background");
+entry!(FeatureTemplate, "feat");
+entry!(ItemDefinition, "item");
+entry!(Spell, "spell");
+entry!(EquipmentPack, "equipment-pack");
+
+/// Where an entry was defined.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Source {
+    /// "SRD", or the directory of the pack.
+    pub pack: String,
+    /// The file in the pack directory; `None` for the SRD.
+    pub file: Option<String>
+}
+
+impl Source {
+    fn srd() -> Source {
+        Source { pack: "SRD".to_owned(), file: None }
+    }
+
+    fn file(dir: &Path, file: &Path) -> Source {
+        Source {
+            pack: dir.display().to_string(),
+            file: file.file_name().map(|name| name.to_string_lossy().into_owned())
+        }
+    }
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.file {
+            Some(file) => write!(f, "{}", Path::new(&self.pack).join(file).display()),
+            None => write!(f, "{}", self.pack)
+        }
+    }
+}
+
+/// Where the entry with one ID came from, for the `content` command.
+#[derive(Serialize)]
+pub struct Provenance {
+    pub id: String,
+    /// The definition in use.
+    pub source: Source,
+    /// The definitions it replaced, from the first pack on.
+    pub replaced: Vec<Source>,
+    /// Whether one pack defines the ID more than once, which leaves only the
+    /// order of its files to decide.
+    pub conflict: bool
+}
+
+/// Puts `additions` after `entries`; an addition with the ID of an entry
+/// already there takes its place instead.
+fn merge<T: Entry>(entries: &mut Vec<T>, additions: Vec<T>) {
+    for addition in additions {
+        let id = addition.id();
+        match entries.iter().position(|entry| entry.id() == id) {
+            Some(index) => entries[index] = addition,
+            None => entries.push(addition)
+        }
+    }
+}
+
+/// The files of a pack directory that hold content, in name order.
+fn pack_files(dir: &Path) -> Result<Vec<PathBuf>, SheetError> {
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)
+        .map_err(|e| SheetError::io(dir, e))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| matches!(Format::from_extension(path), Some(Format::Json) | Some(Format::Yaml)))
+        .collect();
+
+    files.sort();
+    Ok(files)
+}
+
+/// Reads one JSON or YAML file of a pack. Every section is optional, so a
+/// file can hold only spells, or a single race.
+fn load_pack_file(path: &Path) -> Result<Rules, SheetError> {
+    let text = fs::read_to_string(path)
+        .map_err(|e| SheetError::io(path, e))?;
+
+    match Format::from_extension(path) {
+        Some(Format::Yaml) => {
+            let deserializer = serde_yaml::Deserializer::from_str(&text);
+            serde_path_to_error::deserialize(deserializer).map_err(|e| {
+                let field = e.path().to_string();
+                let inner = e.into_inner();
+                let (line, column) = inner.location().map(|location| (location.line(), location.column())).unwrap_or((0, 0));
+                SheetError::parse_at(path, field, inner.to_string(), line, column)
+            })
+        },
+        _ => {
+            let deserializer = &mut serde_json::Deserializer::from_str(&text);
+            serde_path_to_error::deserialize(deserializer).map_err(|e| SheetError::parse(path, e))
+        }
+    }
+}
+
+impl Rules {
+
+    /// The rules built into the binary.
+    pub fn srd() -> Rules {
+        let srd: Rules = serde_json::from_str(SRD).expect("the built-in SRD rules are valid");
+        let mut rules = Rules::default();
+        rules.extend(srd, Source::srd());
+        rules
+    }
+
+    /// The IDs of every entry, section by section in file order.
+    fn ids(&self) -> Vec<String> {
+        let mut ids = Vec::new();
+        ids.extend(self.races.iter().map(Entry::id));
+        ids.extend(self.classes.iter().map(Entry::id));
+        ids.extend(self.backgrounds.iter().map(Entry::id));
+        ids.extend(self.feats.iter().map(Entry::id));
+        ids.extend(self.items.iter().map(Entry::id));
+        ids.extend(self.equipment_packs.iter().map(Entry::id));
+        ids.extend(self.spells.iter().map(Entry::id));
+        ids
+    }
+
+    /// Adds the content of `other`, read from `source`, which replaces
+    /// entries with the same ID.
+    fn extend(&mut self, other: Rules, source: Source) {
+        for id in other.ids() {
+            self.sources.entry(id).or_default().push(source.clone());
+        }
+
+        merge(&mut self.races, other.races);
+        merge(&mut self.classes, other.classes);
+        merge(&mut self.backgrounds, other.backgrounds);
+        merge(&mut self.feats, other.feats);
+        merge(&mut self.items, other.items);
+        merge(&mut self.equipment_packs, other.equipment_packs);
+        merge(&mut self.spells, other.spells);
+    }
+
+    /// The SRD with the packs on top, each one replacing entries of the ones
+    /// before: the `dirs` given, else those in `$SHEET_CONTENT`, then the
+    /// pack next to `character` if there is one.
+    pub fn load(dirs: &[String], character: Option<&Path>) -> Result<Rules, SheetError> {
+        let mut layers: Vec<PathBuf> = if dirs.is_empty() {
+            env::var_os(CONTENT_VARIABLE).map(|dirs| split_content_dirs(&dirs)).unwrap_or_default()
+        } else {
+            dirs.iter().map(PathBuf::from).collect()
+        };
+        layers.extend(character.map(local_pack).filter(|dir| dir.is_dir()));
+
+        let mut rules = Rules::srd();
+        for dir in &layers {
+            for file in pack_files(dir)? {
+                let content = load_pack_file(&file)?;
+                rules.extend(content, Source::file(dir, &file));
+            }
+            rules.packs.push(dir.display().to_string());
+        }
+
+        Ok(rules)
+    }
+
+    /// Where each entry came from, in the order `ids` lists them.
+    pub fn provenance(&self) -> Vec<Provenance> {
+        self.ids().into_iter()
+            .filter_map(|id| {
+                let sources = self.sources.get(&id)?;
+                let (source, replaced) = sources.split_last()?;
+                let conflict = sources.iter().enumerate()
+                    .any(|(index, a)| sources[index + 1..].iter().any(|b| a.pack == b.pack));
+
+                Some(Provenance { id, source: source.clone(), replaced: replaced.to_vec(), conflict })
+            })
+            .collect()
+    }
+}
+
+/// The directories in a `$SHEET_CONTENT` value. Empty entries, as in an empty
+/// value or a doubled separator, are left out rather than read as a path.
+fn split_content_dirs(dirs: &OsStr) -> Vec<PathBuf> {
+    env::split_paths(dirs)
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .collect()
+}
+
+/// The pack only the character in `path` uses.
+fn local_pack(path: &Path) -> PathBuf {
+    path.with_extension(LOCAL_PACK_EXTENSION)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_pack(dir: &Path, file: &str, text: &str) -> String {
+        fs::create_dir_all(dir).unwrap();
+        fs::write(dir.join(file), text).unwrap();
+        dir.display().to_string()
+    }
+
+    fn spell(name: &str, effect: &str) -> String {
+        format!(r#"{{ "name": "{}", "level": 1, "school": "Evocation", "casting_time": "1 action", "range": "Self",
+            "components": "V", "duration": "Instantaneous", "classes": ["Wizard"], "effect": "{}", "description": "" }}"#,
+            name, effect)
+    }
+
+    #[test]
+    fn split_content_dirs_skips_empty_entries() {
+        assert!(split_content_dirs(OsStr::new("")).is_empty());
+        assert_eq!(split_content_dirs(OsStr::new("a::b:")), vec![PathBuf::from("a"), PathBuf::from("b")]);
+    }
+
+    #[test]
+    fn later_packs_replace_entries_with_the_same_id() {
+        let temp = tempfile::tempdir().unwrap();
+        let first = write_pack(&temp.path().join("first"), "spells.json",
+            &format!(r#"{{ "spells": [{}, {}] }}"#, spell("Shield", "first"), spell("Homebrew Bolt", "first")));
+        let second = write_pack(&temp.path().join("second"), "spells.json",
+            &format!(r#"{{ "spells": [{}] }}"#, spell("Homebrew Bolt", "second")));
+        let character = temp.path().join("hero.json");
+        write_pack(&temp.path().join("hero.content"), "spells.yaml", "spells: []\n");
+
+        let rules = Rules::load(&[first.clone(), second.clone()], Some(&character)).unwrap();
+
+        assert_eq!(rules.packs, vec![first, second, temp.path().join("hero.content").display().to_string()]);
+        assert_eq!(rules.find_spell("spell:shield").unwrap().effect, "first");
+        assert_eq!(rules.find_spell("spell:homebrew-bolt").unwrap().effect, "second");
+
+        let provenance = rules.provenance();
+        let bolt = provenance.iter().find(|entry| entry.id == "spell:homebrew-bolt").unwrap();
+        assert_eq!(bolt.replaced.len(), 1);
+        assert!(!bolt.conflict);
+        let shield = provenance.iter().find(|entry| entry.id == "spell:shield").unwrap();
+        assert_eq!(shield.replaced.len(), 1);
+    }
+
+    #[test]
+    fn one_pack_defining_an_id_twice_is_a_conflict() {
+        let temp = tempfile::tempdir().unwrap();
+        let pack = temp.path().join("pack");
+        write_pack(&pack, "a.json", &format!(r#"{{ "spells": [{}] }}"#, spell("Homebrew Bolt", "a")));
+        let pack = write_pack(&pack, "b.json", &format!(r#"{{ "spells": [{}] }}"#, spell("Homebrew Bolt", "b")));
+
+        let rules = Rules::load(&[pack], None).unwrap();
+
+        assert_eq!(rules.find_spell("spell:homebrew-bolt").unwrap().effect, "b");
+        assert!(rules.provenance().iter().find(|entry| entry.id == "spell:homebrew-bolt").unwrap().conflict);
+    }
+}
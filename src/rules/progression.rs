@@ -0,0 +1,327 @@
+use std::convert::TryFrom;
+
+use crate::character::{Character, Dice, FeatureSource, LevelAdvance, Stat};
+use crate::rules::{Caster, Class, Entry, FeatureTemplate, Rules, Spell, Spellcasting};
+
+/// Spell slots of a full caster by class level, from 1st to 9th level spells.
+/// Half casters use the row for half their level, rounded up.
+const FULL_CASTER_SLOTS: [[u16; 9]; 20] = [
+    [2, 0, 0, 0, 0, 0, 0, 0, 0],
+    [3, 0, 0, 0, 0, 0, 0, 0, 0],
+    [4, 2, 0, 0, 0, 0, 0, 0, 0],
+    [4, 3, 0, 0, 0, 0, 0, 0, 0],
+    [4, 3, 2, 0, 0, 0, 0, 0, 0],
+    [4, 3, 3, 0, 0, 0, 0, 0, 0],
+    [4, 3, 3, 1, 0, 0, 0, 0, 0],
+    [4, 3, 3, 2, 0, 0, 0, 0, 0],
+    [4, 3, 3, 3, 1, 0, 0, 0, 0],
+    [4, 3, 3, 3, 2, 0, 0, 0, 0],
+    [4, 3, 3, 3, 2, 1, 0, 0, 0],
+    [4, 3, 3, 3, 2, 1, 0, 0, 0],
+    [4, 3, 3, 3, 2, 1, 1, 0, 0],
+    [4, 3, 3, 3, 2, 1, 1, 0, 0],
+    [4, 3, 3, 3, 2, 1, 1, 1, 0],
+    [4, 3, 3, 3, 2, 1, 1, 1, 0],
+    [4, 3, 3, 3, 2, 1, 1, 1, 1],
+    [4, 3, 3, 3, 3, 1, 1, 1, 1],
+    [4, 3, 3, 3, 3, 2, 1, 1, 1],
+    [4, 3, 3, 3, 3, 2, 2, 1, 1]
+];
+
+/// How the hit points of a new level are found.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HitPointGain {
+    /// The fixed value the Player's Handbook offers: half the hit die plus one.
+    Average,
+    Roll,
+    /// Exactly this many, Constitution modifier included.
+    Fixed(u16)
+}
+
+/// Reads an Ability Score Improvement typed by a user: one ability for +2,
+/// e.g. `STR`, or two for +1 each, e.g. `STR,DEX`.
+pub fn parse_ability_increases(text: &str) -> Result<Vec<Stat>, String> {
+    let stats = text.split(',')
+        .map(|name| Stat::from_name(name).ok_or_else(|| format!("`{}` is not an ability", name.trim())))
+        .collect::<Result<Vec<Stat>, String>>()?;
+
+    match stats.as_slice() {
+        [stat] => Ok(vec![*stat, *stat]),
+        [_, _] => Ok(stats),
+        _ => Err(format!("an Ability Score Improvement is one or two abilities, got `{}`", text))
+    }
+}
+
+impl Spellcasting {
+
+    /// Spell slots per spell level at a class level, e.g. `[(1, 4), (2, 2)]`
+    /// for a 3rd level wizard. Pact magic slots are all of one level.
+    pub fn slots_at(&self, level: u16) -> Vec<(u8, u16)> {
+        if level < self.first_level || level == 0 {
+            return Vec::new();
+        }
+        let level = level.min(20);
+
+        let row = match self.caster {
+            Caster::Full => FULL_CASTER_SLOTS[level as usize - 1],
+            Caster::Half => FULL_CASTER_SLOTS[(level as usize).div_ceil(2) - 1],
+            Caster::Pact => {
+                let slots = match level {
+                    1 => 1,
+                    2..=10 => 2,
+                    11..=16 => 3,
+                    _ => 4
+                };
+                return vec![(level.div_ceil(2).min(5) as u8, slots)];
+            }
+        };
+
+        row.iter().enumerate()
+            .filter(|(_, slots)| **slots > 0)
+            .map(|(index, slots)| (index as u8 + 1, *slots))
+            .collect()
+    }
+
+    /// Cantrips and spells learned on reaching a class level.
+    pub fn new_spells_at(&self, level: u16) -> (usize, usize) {
+        if level < self.first_level {
+            (0, 0)
+        } else if level == self.first_level {
+            (self.cantrips, self.spells)
+        } else {
+            (if self.cantrips_at.contains(&level) { 1 } else { 0 }, self.spells_per_level)
+        }
+    }
+
+    /// Cantrips known at a class level.
+    pub fn cantrips_known_at(&self, level: u16) -> usize {
+        if level < self.first_level {
+            return 0;
+        }
+        self.cantrips + self.cantrips_at.iter().filter(|at| **at <= level).count()
+    }
+
+    /// The most leveled spells a `Known` caster can know at a class level,
+    /// `None` if the class has no such limit.
+    pub fn spells_known_at(&self, level: u16) -> Option<usize> {
+        if self.spells_known.is_empty() {
+            return None;
+        }
+        let index = (level.clamp(1, 20) as usize - 1).min(self.spells_known.len() - 1);
+        Some(self.spells_known[index])
+    }
+
+    /// Spells a preparing class can prepare: the spellcasting ability modifier
+    /// plus the class level, or half of it for half casters. At least one.
+    pub fn preparation_limit(&self, level: u16, modifier: i16) -> usize {
+        let level = if self.caster == Caster::Half { level / 2 } else { level };
+        modifier.saturating_add(i16::try_from(level).unwrap_or(i16::MAX)).max(1) as usize
+    }
+}
+
+impl Class {
+
+    /// The features the class progression lists for `level`.
+    pub fn features_at(&self, level: u16) -> impl Iterator<Item = &FeatureTemplate> {
+        self.progression.iter()
+            .filter(move |class_level| class_level.level == level)
+            .flat_map(|class_level| class_level.features.iter())
+    }
+
+    /// Half the hit die plus one, e.g. 5 for a d8.
+    pub fn average_hitpoints(&self) -> u16 {
+        self.hit_die / 2 + 1
+    }
+}
+
+/// What reaching the next level offers a character, before the player chooses.
+pub struct LevelUpPlan<'a> {
+    rules: &'a Rules,
+    class: &'a Class,
+    /// The level being reached.
+    pub level: u16,
+    pub ability_score_improvement: bool,
+    /// Cantrips to learn, never more than there are to choose from.
+    pub cantrips: usize,
+    /// Leveled spells to learn, never more than there are to choose from.
+    pub spells: usize,
+    /// Spells of the class the character can learn at the new level and does not know yet.
+    pub spell_options: Vec<&'a Spell>
+}
+
+/// The choices a player made for a new level.
+pub struct LevelUpChoices {
+    /// Hit points to add, already worked out with `LevelUpPlan::hitpoints`.
+    pub hitpoints: u16,
+    pub ability_increases: Vec<Stat>,
+    pub feat: Option<String>,
+    pub spells: Vec<String>
+}
+
+impl Rules {
+
+    pub fn find_class(&self, reference: &str) -> Option<&Class> {
+        self.classes.iter().find(|class| class.matches(reference))
+    }
+
+    /// Plans the next level of `character`, whose class must be in the rules.
+    pub fn plan_level_up(&self, character: &Character) -> Result<LevelUpPlan<'_>, String> {
+        let class = self.find_class(character.get_class())
+            .ok_or_else(|| format!("{} is not a class in the rules", character.get_class()))?;
+        let level = character.get_level() + 1;
+        if level > 20 {
+            return Err(format!("{} is already level {}", character.get_name(), character.get_level()));
+        }
+
+        let (cantrips, spells, spell_options) = match &class.spellcasting {
+            Some(spellcasting) => {
+                let (cantrips, spells) = spellcasting.new_spells_at(level);
+                let max_level = spellcasting.slots_at(level).iter().map(|(level, _)| *level).max().unwrap_or(0);
+                let options: Vec<&Spell> = self.class_spells(&class.name, max_level).into_iter()
+                    .filter(|spell| !character.get_spells().iter().any(|known| spell.matches(known)))
+                    .filter(|spell| if spell.level == 0 { cantrips > 0 } else { spells > 0 })
+                    .collect();
+
+                let available_cantrips = options.iter().filter(|spell| spell.level == 0).count();
+                (cantrips.min(available_cantrips), spells.min(options.len() - available_cantrips), options)
+            },
+            None => (0, 0, Vec::new())
+        };
+
+        Ok(LevelUpPlan {
+            rules: self,
+            class,
+            level,
+            ability_score_improvement: class.ability_score_improvements.contains(&level),
+            cantrips,
+            spells,
+            spell_options
+        })
+    }
+}
+
+impl LevelUpPlan<'_> {
+
+    /// The hit points `gain` gives `character`, and a line saying how they
+    /// came about. Never less than 1, and fixed hit points never more than a
+    /// roll of the hit die could give.
+    pub fn hitpoints(&self, gain: HitPointGain, character: &Character) -> Result<(u16, String), String> {
+        let constitution = character.get_modifier(&Stat::Constitution).unwrap_or(0);
+        let with_constitution = |die: u16| (die as i16 + constitution).max(1) as u16;
+        let die = match gain {
+            HitPointGain::Fixed(0) => return Err("a new level gives at least 1 hit point".to_owned()),
+            HitPointGain::Average => self.class.average_hitpoints(),
+            HitPointGain::Roll => Dice { count: 1, sides: self.class.hit_die, modifier: 0 }.roll() as u16,
+            HitPointGain::Fixed(hitpoints) if hitpoints > with_constitution(self.class.hit_die) =>
+                return Err(format!("{} hit points is more than a d{} with CON {:+} can give ({})",
+                    hitpoints, self.class.hit_die, constitution, with_constitution(self.class.hit_die))),
+            HitPointGain::Fixed(hitpoints) => return Ok((hitpoints, format!("Gained {} hit points", hitpoints)))
+        };
+        let hitpoints = with_constitution(die);
+        let how = if gain == HitPointGain::Roll { "rolled" } else { "average" };
+
+        Ok((hitpoints, format!("d{} {} {}, CON {:+}: {} hit points", self.class.hit_die, how, die, constitution, hitpoints)))
+    }
+
+    /// Checks spells chosen by ID or name against the options and the number
+    /// to learn, returning their IDs.
+    pub fn check_spells(&self, references: &[String]) -> Result<Vec<String>, String> {
+        let mut chosen: Vec<&Spell> = Vec::new();
+        for reference in references {
+            let spell = self.spell_options.iter()
+                .find(|spell| spell.matches(reference.trim()))
+                .ok_or_else(|| format!("{} cannot learn `{}` at level {}", self.class.name, reference.trim(), self.level))?;
+            if !chosen.iter().any(|known| known.id() == spell.id()) {
+                chosen.push(spell);
+            }
+        }
+
+        let cantrips = chosen.iter().filter(|spell| spell.level == 0).count();
+        let spells = chosen.len() - cantrips;
+        if cantrips != self.cantrips || spells != self.spells {
+            return Err(format!("level {} learns {} cantrips and {} spells, got {} and {}",
+                self.level, self.cantrips, self.spells, cantrips, spells));
+        }
+
+        Ok(chosen.into_iter().map(Entry::id).collect())
+    }
+
+    /// Turns the choices into the changes for `Character::level_up`.
+    pub fn advance(&self, choices: LevelUpChoices) -> Result<LevelAdvance, String> {
+        match (self.ability_score_improvement, choices.ability_increases.is_empty(), &choices.feat) {
+            (true, true, None) => return Err(format!(
+                "level {} brings an Ability Score Improvement: choose abilities or a feat", self.level)),
+            (true, false, Some(_)) => return Err("choose an Ability Score Improvement or a feat, not both".to_owned()),
+            (false, false, _) | (false, _, Some(_)) => return Err(format!(
+                "level {} brings no Ability Score Improvement", self.level)),
+            _ => {}
+        }
+
+        let spells = self.check_spells(&choices.spells)?;
+
+        let class_features = self.class.features_at(self.level)
+            .map(|feature| feature.to_feature(FeatureSource::Class));
+        let feat = choices.feat.map(|name| self.rules.feat_feature(name));
+
+        let spellcasting = self.class.spellcasting.as_ref().filter(|spellcasting| self.level >= spellcasting.first_level);
+
+        Ok(LevelAdvance {
+            hitpoints: choices.hitpoints,
+            ability_increases: choices.ability_increases,
+            features: class_features.chain(feat).collect(),
+            spellcasting_ability: spellcasting.map(|spellcasting| spellcasting.ability),
+            spell_slots: spellcasting.map(|spellcasting| spellcasting.slots_at(self.level)),
+            spells
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The preparation limit of an SRD class.
+    fn limit(class: &str, level: u16, modifier: i16) -> usize {
+        let rules = Rules::srd();
+        let spellcasting = rules.find_class(class).and_then(|class| class.spellcasting.as_ref()).unwrap();
+
+        spellcasting.preparation_limit(level, modifier)
+    }
+
+    fn dandelion() -> Character {
+        serde_json::from_str(include_str!("../../resources/characters/dandelion.json")).unwrap()
+    }
+
+    #[test]
+    fn fixed_hitpoints_are_between_one_and_the_hit_die() {
+        let rules = Rules::srd();
+        let character = dandelion();
+        let plan = rules.plan_level_up(&character).unwrap();
+
+        assert!(plan.hitpoints(HitPointGain::Fixed(0), &character).is_err());
+        assert_eq!(plan.hitpoints(HitPointGain::Fixed(1), &character).unwrap().0, 1);
+        assert_eq!(plan.hitpoints(HitPointGain::Fixed(7), &character).unwrap().0, 7);
+        assert!(plan.hitpoints(HitPointGain::Fixed(8), &character).is_err());
+    }
+
+    #[test]
+    fn preparation_limit_adds_the_class_level_to_the_modifier() {
+        assert_eq!(limit("Cleric", 1, 3), 4);
+        assert_eq!(limit("Wizard", 5, 4), 9);
+    }
+
+    #[test]
+    fn preparation_limit_halves_the_level_of_half_casters() {
+        assert_eq!(limit("Paladin", 5, 2), 4);
+    }
+
+    #[test]
+    fn preparation_limit_is_at_least_one() {
+        assert_eq!(limit("Cleric", 1, -5), 1);
+    }
+
+    #[test]
+    fn preparation_limit_does_not_overflow() {
+        assert_eq!(limit("Cleric", u16::MAX, i16::MAX), i16::MAX as usize);
+    }
+}
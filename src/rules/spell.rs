@@ -0,0 +1,132 @@
+use crate::character::{Modifier, Stat};
+
+#[derive(Deserialize)]
+pub struct Spell {
+    #[serde(default)]
+    pub id: Option<String>,
+    pub name: String,
+    /// 0 for cantrips.
+    pub level: u8,
+    pub school: String,
+    pub casting_time: String,
+    pub range: String,
+    pub components: String,
+    pub duration: String,
+    #[serde(default)]
+    pub concentration: bool,
+    #[serde(default)]
+    pub ritual: bool,
+    pub classes: Vec<String>,
+    /// Whether the spell needs a spell attack roll.
+    #[serde(default)]
+    pub attack: bool,
+    /// The saving throw targets make against the spell, if any.
+    #[serde(default)]
+    pub save: Option<Stat>,
+    /// A few words for the spell list, e.g. "1d10 fire" or "Utility".
+    pub effect: String,
+    pub description: String,
+    /// What the spell does to the caster for its duration, e.g. +5 armor
+    /// class for Shield.
+    #[serde(default)]
+    pub modifiers: Vec<Modifier>
+}
+
+impl Spell {
+
+    /// "C" for cantrips, the spell level otherwise.
+    pub fn level_text(&self) -> String {
+        if self.level == 0 { "C".to_owned() } else { self.level.to_string() }
+    }
+
+    /// The casting time the way the sheet abbreviates it: "1 action" -> "1A",
+    /// "1 bonus action" -> "1BA", "1 reaction" -> "1R", "10 minutes" -> "10m".
+    pub fn short_casting_time(&self) -> String {
+        let mut words = self.casting_time.split_whitespace();
+        let amount = words.next().unwrap_or("");
+        let unit = match words.collect::<Vec<_>>().join(" ").as_str() {
+            "action" | "actions" => "A",
+            "bonus action" => "BA",
+            "reaction" => "R",
+            "minute" | "minutes" => "m",
+            "hour" | "hours" => "h",
+            _ => return self.casting_time.clone()
+        };
+
+        format!("{}{}", amount, unit)
+    }
+
+    /// "120 feet" -> "120ft", "Self (15-foot cone)" -> "Self", "Touch" stays.
+    pub fn short_range(&self) -> String {
+        let range = self.range.split(" (").next().unwrap_or(&self.range);
+        range.replace(" feet", "ft")
+    }
+
+    /// "1 minute" -> "1m", "8 hours" -> "8h", "10 days" -> "10d". Empty for
+    /// instantaneous spells.
+    pub fn short_duration(&self) -> String {
+        let mut words = self.duration.split_whitespace();
+        let amount = words.next().unwrap_or("");
+
+        match words.next() {
+            Some(unit) if amount.chars().all(|c| c.is_ascii_digit()) => {
+                format!("{}{}", amount, unit.chars().next().unwrap_or_default())
+            }
+            _ if self.duration.eq_ignore_ascii_case("instantaneous") => String::new(),
+            _ => self.duration.clone()
+        }
+    }
+
+    /// Concentration, ritual, duration and components, e.g. "Conc, D: 1m, V/S/M".
+    pub fn notes(&self) -> String {
+        let mut notes = Vec::new();
+
+        if self.concentration {
+            notes.push("Conc".to_owned());
+        }
+        if self.ritual {
+            notes.push("Ritual".to_owned());
+        }
+
+        let duration = self.short_duration();
+        if !duration.is_empty() {
+            notes.push(format!("D: {}", duration));
+        }
+
+        notes.push(self.components.replace(", ", "/"));
+        notes.join(", ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spell(duration: &str) -> Spell {
+        serde_json::from_value(serde_json::json!({
+            "name": "Test",
+            "level": 1,
+            "school": "Evocation",
+            "casting_time": "1 action",
+            "range": "Self",
+            "components": "V, S",
+            "duration": duration,
+            "classes": [],
+            "effect": "Utility",
+            "description": ""
+        })).unwrap()
+    }
+
+    #[test]
+    fn short_duration_keeps_the_first_letter_of_the_unit() {
+        assert_eq!(spell("1 minute").short_duration(), "1m");
+        assert_eq!(spell("8 hours").short_duration(), "8h");
+        assert_eq!(spell("Instantaneous").short_duration(), "");
+        assert_eq!(spell("Until dispelled").short_duration(), "Until dispelled");
+    }
+
+    #[test]
+    fn short_duration_handles_units_starting_with_non_ascii_letters() {
+        assert_eq!(spell("10 éons").short_duration(), "10é");
+    }
+}
@@ -1,5 +1,9 @@
 use std::io;
+use std::path::{Path, PathBuf};
+use termion::event::Key;
+use termion::input::TermRead;
 use termion::raw::IntoRawMode;
+use termion::screen::AlternateScreen;
 use tui::{Frame, Terminal};
 use tui::backend::{Backend, TermionBackend};
 use tui::layout::{Layout, Constraint, Direction, Rect, Alignment};
@@ -7,6 +11,9 @@ use tui::widgets::{Widget, Block, Borders, Text, Paragraph, Table, Row, Tabs};
 use tui::style::{Color, Modifier, Style};
 
 use crate::character;
+use crate::character::{Condition, RollMode, Severity, Stat};
+use crate::rules::{Entry, Rules, Spell, SpellFilter};
+use crate::ui::portrait::{ColorMode, Portrait, PortraitWidget};
 
 fn render_paragraph<B>(f: &mut Frame<B>, text: &[Text], layout: &Rect, alignment: &Alignment)
     where
@@ -18,6 +25,14 @@ fn render_paragraph<B>(f: &mut Frame<B>, text: &[Text], layout: &Rect, alignment
         .render(f, *layout);
 }
 
+fn roll_mode_marker(mode: RollMode) -> &'static str {
+    match mode {
+        RollMode::Normal => "",
+        RollMode::Advantage => "▲",
+        RollMode::Disadvantage => "▼"
+    }
+}
+
 fn create_layout(parent: &Rect, direction: Direction, percentages: &[u16], margin: u16) -> Vec<Rect>
 {
     let constraints: Vec<Constraint> = percentages.iter()
@@ -30,14 +45,105 @@ fn create_layout(parent: &Rect, direction: Direction, percentages: &[u16], margi
         .split(*parent)
 }
 
+/// The value after `current` in `options`, for filters that step through their
+/// options on every key press; after the last one the filter is off again.
+fn next_option<T: Clone + PartialEq>(current: &Option<T>, options: &[T]) -> Option<T> {
+    match current.as_ref().and_then(|current| options.iter().position(|option| option == current)) {
+        Some(index) => options.get(index + 1).cloned(),
+        None => options.first().cloned()
+    }
+}
+
+const TAB_TITLES: [&str; 6] = ["Actions", "Spells", "Equipment", "Features & Traits", "Description", "Compendium"];
+const SPELLS_TAB: usize = 1;
+const EQUIPMENT_TAB: usize = 2;
+const FEATURES_TAB: usize = 3;
+const DESCRIPTION_TAB: usize = 4;
+const COMPENDIUM_TAB: usize = 5;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum AmountChange {
+    Damage,
+    Heal,
+    Experience
+}
+
+impl AmountChange {
+    /// Digits the prompt accepts: hit points stay small, experience does not.
+    fn max_digits(self) -> usize {
+        if self == AmountChange::Experience { 6 } else { 4 }
+    }
+}
+
+enum InputMode {
+    Normal,
+    ConditionPicker { selected: usize },
+    /// Typing the amount of damage, healing or experience, applied on Enter.
+    AmountPrompt { change: AmountChange, amount: String },
+    /// Typing in the compendium search, which filters the list on every key.
+    CompendiumSearch,
+    /// Casting the spell with this ID would end the current concentration.
+    ConfirmCast { spell: String, ritual: bool },
+    /// Damage calls for a Constitution save to keep concentrating.
+    ConcentrationSave { dc: u16 },
+    /// Shows where the selected number on the sheet comes from.
+    Breakdown { selected: usize }
+}
+
 pub struct MainApp<'a> {
-    character: &'a character::Character
+    character: &'a mut character::Character,
+    character_path: PathBuf,
+    autosave: bool,
+    unsaved_changes: bool,
+    confirm_quit: bool,
+    input_mode: InputMode,
+    selected_tab: usize,
+    selected_rows: [usize; TAB_TITLES.len()],
+    description_scroll: u16,
+    portrait: Option<Portrait>,
+    color_mode: ColorMode,
+    rules: Rules,
+    compendium: SpellFilter,
+    log: Vec<String>
 }
 
 impl MainApp<'_> {
 
-    pub fn new(character: &character::Character) -> Result<MainApp, io::Error> {
-        Ok(MainApp { character })
+    pub fn new<'a>(character: &'a mut character::Character, character_path: &Path, autosave: bool, rules: Rules) -> Result<MainApp<'a>, io::Error> {
+        let mut log = Vec::new();
+
+        let findings = character::validate(character);
+        if let Some(first) = findings.first() {
+            let errors = findings.iter().filter(|finding| finding.severity == Severity::Error).count();
+            log.push(format!("Validation: {} errors, {} warnings. First: {}{}",
+                errors, findings.len() - errors, first,
+                if findings.len() > 1 { " (run `validate` for the full report)" } else { "" }));
+        }
+
+        let portrait = character.get_portrait().and_then(|portrait| {
+            let portrait_path = character_path.parent().unwrap_or_else(|| Path::new("")).join(portrait);
+
+            Portrait::load(&portrait_path)
+                .map_err(|e| log.push(format!("Could not load portrait: {}", e)))
+                .ok()
+        });
+
+        Ok(MainApp {
+            character,
+            character_path: character_path.to_path_buf(),
+            autosave,
+            unsaved_changes: false,
+            confirm_quit: false,
+            input_mode: InputMode::Normal,
+            selected_tab: SPELLS_TAB,
+            selected_rows: [0; TAB_TITLES.len()],
+            description_scroll: 0,
+            portrait,
+            color_mode: ColorMode::detect(),
+            rules,
+            compendium: SpellFilter::default(),
+            log
+        })
     }
 
     fn draw_all_layout<B>(&self, f: &mut Frame<B>)
@@ -67,20 +173,109 @@ impl MainApp<'_> {
 
         let inner_layout = create_layout(&layout_chunk, Direction::Horizontal, &[10, 30, 30, 40], 1);
 
-        let name = "\n".to_owned() + &self.character.get_name() + "\n";
-        let race_class_lvl = self.character.get_race().to_owned() + " " + &self.character.get_class() + " Lvl " + &self.character.get_level().to_string() + "\n";
-        let text = [
+        let name = "\n".to_owned() + self.character.get_name() + "\n";
+        let race_class_lvl = self.character.get_race().to_owned() + " " + self.character.get_class() + " Lvl " + &self.character.get_level().to_string() + "\n";
+        let mut text = vec![
             Text::styled(name, Style::default().fg(Color::White).modifier(Modifier::BOLD)),
             Text::styled(race_class_lvl, Style::default())
         ];
+        text.extend(self.experience_progress());
 
         render_paragraph(f, &text, &inner_layout[1], &Alignment::Left);
 
         self.draw_picture(f, inner_layout[0]);
 
+        self.draw_condition_badges(f, inner_layout[2]);
+
         self.draw_rests(f, inner_layout[3]);
     }
 
+    /// Experience towards the next level with a bar, e.g. "XP 1200/2700 [███░░░░░░░]",
+    /// or a note when the campaign uses milestones, followed by the keys.
+    fn experience_progress(&self) -> Vec<Text<'static>> {
+        let key = Style::default().modifier(Modifier::UNDERLINED);
+
+        if self.character.uses_milestones() {
+            return vec![
+                Text::raw("Milestone levelling | "),
+                Text::styled("M", key),
+                Text::raw("ilestones off")
+            ];
+        }
+
+        let points = *self.character.get_experience_points();
+        let progress = match self.character.get_experience_for_next_level() {
+            None => Text::raw(format!("XP {} (max level)", points)),
+            Some(next) if self.character.can_level_up() => Text::styled(format!("XP {}/{} LEVEL UP", points, next),
+                Style::default().fg(Color::Yellow).modifier(Modifier::BOLD)),
+            Some(next) => {
+                const BAR_WIDTH: u32 = 10;
+                let start = character::experience_for_level(*self.character.get_level()).unwrap_or(0);
+                let filled = (points.saturating_sub(start) * BAR_WIDTH / (next - start).max(1)).min(BAR_WIDTH) as usize;
+                let bar = "█".repeat(filled) + &"░".repeat(BAR_WIDTH as usize - filled);
+
+                Text::raw(format!("XP {}/{} [{}]", points, next, bar))
+            }
+        };
+
+        vec![
+            progress,
+            Text::raw("\nAward e"),
+            Text::styled("x", key),
+            Text::raw("p | "),
+            Text::styled("M", key),
+            Text::raw("ilestones")
+        ]
+    }
+
+    fn draw_condition_badges<B>(&self, f: &mut Frame<B>, layout_chunk: Rect)
+        where
+            B: Backend
+    {
+        let badge_style = Style::default().fg(Color::Black).bg(Color::Yellow).modifier(Modifier::BOLD);
+        let danger_style = Style::default().fg(Color::White).bg(Color::Red).modifier(Modifier::BOLD);
+
+        let mut badges = vec![Text::raw("\n")];
+
+        if self.character.is_dead() {
+            badges.push(Text::styled(" DEAD ", danger_style));
+            badges.push(Text::raw(" "));
+        }
+
+        let exhaustion = *self.character.get_exhaustion();
+        if exhaustion > 0 {
+            badges.push(Text::styled(format!(" EXHAUSTION {} ", exhaustion), danger_style));
+            badges.push(Text::raw(" "));
+        }
+
+        for condition in self.character.get_conditions() {
+            badges.push(Text::styled(format!(" {} ", condition.to_string().to_uppercase()), badge_style));
+            badges.push(Text::raw(" "));
+        }
+
+        if let Some(spell) = self.character.get_concentration() {
+            let concentration_style = Style::default().fg(Color::Black).bg(Color::Cyan).modifier(Modifier::BOLD);
+            badges.push(Text::styled(format!(" CONCENTRATING: {} ", self.rules.spell_name(spell).to_uppercase()), concentration_style));
+            badges.push(Text::raw(" "));
+        }
+
+        let effect_style = Style::default().fg(Color::Black).bg(Color::Green).modifier(Modifier::BOLD);
+        for effect in self.character.get_effects() {
+            badges.push(Text::styled(format!(" {} ({}) ", effect.name.to_uppercase(), effect.duration), effect_style));
+            badges.push(Text::raw(" "));
+        }
+
+        badges.push(Text::raw("\n"));
+        badges.push(Text::styled("C", Style::default().modifier(Modifier::UNDERLINED)));
+        badges.push(Text::styled("onditions | ", Style::default()));
+        badges.push(Text::styled("E", Style::default().modifier(Modifier::UNDERLINED)));
+        badges.push(Text::styled("xhaustion +/- | ", Style::default()));
+        badges.push(Text::styled("N", Style::default().modifier(Modifier::UNDERLINED)));
+        badges.push(Text::styled("ext round", Style::default()));
+
+        render_paragraph(f, &badges, &layout_chunk, &Alignment::Left);
+    }
+
     fn draw_picture<B>(&self, f: &mut Frame<B>, layout_chunk: Rect)
         where
             B: Backend
@@ -88,6 +283,13 @@ impl MainApp<'_> {
         Block::default()
             .borders(Borders::ALL)
             .render(f, layout_chunk);
+
+        if let Some(portrait) = &self.portrait {
+            let inner_layout = create_layout(&layout_chunk, Direction::Vertical, &[100], 1);
+
+            PortraitWidget::new(portrait, self.color_mode)
+                .render(f, inner_layout[0]);
+        }
     }
 
     fn draw_rests<B>(&self, f: &mut Frame<B>, layout_chunk: Rect)
@@ -158,33 +360,49 @@ impl MainApp<'_> {
         where
             B: Backend
     {
-        let stats_text = [
-            Text::styled("STR: ", Style::default().modifier(Modifier::BOLD)),
-            Text::styled("11 (+0) | ", Style::default()),
-            Text::styled("DEX: ", Style::default().modifier(Modifier::BOLD)),
-            Text::styled("17 (+3) | ", Style::default()),
-            Text::styled("CON: ", Style::default().modifier(Modifier::BOLD)),
-            Text::styled("13 (+1) | ", Style::default()),
-            Text::styled("INT: ", Style::default().modifier(Modifier::BOLD)),
-            Text::styled("19 (+4) | ", Style::default()),
-            Text::styled("WIS: ", Style::default().modifier(Modifier::BOLD)),
-            Text::styled("12 (+1) | ", Style::default()),
-            Text::styled("CHA: ", Style::default().modifier(Modifier::BOLD)),
-            Text::styled("11 (+0)", Style::default()),
+        let mut stats_text: Vec<Text> = Stat::ALL.iter().enumerate()
+            .flat_map(|(index, stat)| {
+                let score = match (self.character.get_stat(stat), self.character.get_modifier(stat)) {
+                    (Some(score), Some(modifier)) => format!("{} ({:+})", score, modifier),
+                    _ => "-".to_owned()
+                };
+                let separator = if index + 1 < Stat::ALL.len() { " | " } else { "" };
+
+                vec![
+                    Text::styled(format!("{}: ", stat.abbreviation()), Style::default().modifier(Modifier::BOLD)),
+                    Text::styled(score + separator, Style::default()),
+                ]
+            })
+            .collect();
+
+        stats_text.extend(vec![
             Text::raw("\n"),
             Text::styled("Armor class: ", Style::default().modifier(Modifier::BOLD)),
             Text::styled(self.character.get_armor_class().to_string() + " | ", Style::default()),
             Text::styled("Initiative: ", Style::default().modifier(Modifier::BOLD)),
-            Text::styled("+".to_owned() + &self.character.get_initiative().to_string() + " | ", Style::default()),
+            Text::styled(format!("{:+} | ", self.character.get_initiative()), Style::default()),
             Text::styled("Proficiency bonus: ", Style::default().modifier(Modifier::BOLD)),
             Text::styled("+".to_owned() + &self.character.get_proficiency_bonus().to_string() + " | ", Style::default()),
             Text::styled("Walking speed: ", Style::default().modifier(Modifier::BOLD)),
-            Text::styled(self.character.get_walking_speed_in_ft().to_string() + " ft", Style::default()),
-        ];
+            Text::styled(self.speed_text() + "\n", Style::default()),
+            Text::styled("W", Style::default().modifier(Modifier::UNDERLINED)),
+            Text::styled("hy these numbers", Style::default()),
+        ]);
 
         render_paragraph(f, &stats_text, &layout_chunk, &Alignment::Left);
     }
 
+    fn speed_text(&self) -> String {
+        let speed = self.character.get_speed();
+        let base_speed = *self.character.get_walking_speed_in_ft();
+
+        if speed == base_speed {
+            format!("{} ft", speed)
+        } else {
+            format!("{} ft (base {} ft)", speed, base_speed)
+        }
+    }
+
     fn draw_hitpoints<B>(&self, f: &mut Frame<B>, layout_chunk: Rect)
         where
             B: Backend
@@ -193,7 +411,7 @@ impl MainApp<'_> {
             Text::styled("Hit Points: ", Style::default().modifier(Modifier::BOLD)),
             Text::styled(self.character.get_current_hitpoints().to_string() + " ", Style::default()),
             Text::styled("/ ", Style::default().modifier(Modifier::BOLD)),
-            Text::styled(self.character.get_max_hitpoints().to_string() + " \n", Style::default()),
+            Text::styled(self.max_hitpoints_text() + " \n", Style::default()),
             Text::styled("H", Style::default().modifier(Modifier::UNDERLINED)),
             Text::styled("eal | ", Style::default()),
             Text::styled("D", Style::default().modifier(Modifier::UNDERLINED)),
@@ -203,6 +421,17 @@ impl MainApp<'_> {
         render_paragraph(f, &hp_text, &layout_chunk, &Alignment::Right);
     }
 
+    fn max_hitpoints_text(&self) -> String {
+        let max_hitpoints = self.character.get_effective_max_hitpoints();
+        let base_max_hitpoints = *self.character.get_max_hitpoints();
+
+        if max_hitpoints == base_max_hitpoints {
+            max_hitpoints.to_string()
+        } else {
+            format!("{} (base {})", max_hitpoints, base_max_hitpoints)
+        }
+    }
+
     // ####### END PLAYER STATS ########
 
     // ####### MAIN ########
@@ -244,30 +473,33 @@ impl MainApp<'_> {
         let inner_layout = create_layout(
             &layout_chunk, Direction::Vertical, &[80, 20], 2);
 
-        let saving_throws = [
-            Text::styled("( ) STR: ", Style::default().modifier(Modifier::BOLD)),
-            Text::styled("+0 | ", Style::default()),
-            Text::styled("( ) DEX: ", Style::default().modifier(Modifier::BOLD)),
-            Text::styled("+3 \n", Style::default()),
-            Text::styled("( ) CON: ", Style::default().modifier(Modifier::BOLD)),
-            Text::styled("+1 | ", Style::default()),
-            Text::styled("(*) INT: ", Style::default().modifier(Modifier::BOLD)),
-            Text::styled("+6 \n", Style::default()),
-            Text::styled("(*) WIS: ", Style::default().modifier(Modifier::BOLD)),
-            Text::styled("+3 | ", Style::default()),
-            Text::styled("( ) CHA: ", Style::default().modifier(Modifier::BOLD)),
-            Text::styled("+0", Style::default()),
-        ];
+        let saving_throws: Vec<Text> = Stat::ALL.iter().enumerate()
+            .flat_map(|(index, stat)| {
+                let proficiency = if self.character.has_saving_throw_proficiency(stat) { "(*)" } else { "( )" };
+                let bonus = self.character.get_saving_throw_bonus(stat)
+                    .map(|bonus| format!("{:+}", bonus))
+                    .unwrap_or_else(|| "?".to_owned());
+                let marker = if self.character.fails_saving_throw(stat) {
+                    "✗"
+                } else {
+                    roll_mode_marker(self.character.saving_throw_mode(stat))
+                };
+                let separator = if index % 2 == 0 { " | " } else { " \n" };
+
+                vec![
+                    Text::styled(format!("{} {}: ", proficiency, stat.abbreviation()), Style::default().modifier(Modifier::BOLD)),
+                    Text::styled(format!("{}{}{}", bonus, marker, separator), Style::default()),
+                ]
+            })
+            .collect();
 
         render_paragraph(f, &saving_throws, &inner_layout[0], &Alignment::Center);
 
-        let advantages = [
-            Text::styled("Advantage on ", Style::default()),
-            Text::styled("INT WIS CHA ", Style::default().modifier(Modifier::BOLD)),
-            Text::styled("against Magic", Style::default()),
-        ];
+        let notes: Vec<Text> = self.character.conditional_save_notes().into_iter()
+            .map(|note| Text::styled(note + "\n", Style::default()))
+            .collect();
 
-        render_paragraph(f, &advantages, &inner_layout[1], &Alignment::Left);
+        render_paragraph(f, &notes, &inner_layout[1], &Alignment::Left);
     }
 
     fn draw_proficiencies<B>(&self, f: &mut Frame<B>, layout_chunk: Rect)
@@ -282,17 +514,22 @@ impl MainApp<'_> {
         let inner_layout = create_layout(
             &layout_chunk, Direction::Vertical, &[100], 2);
 
-        let proficiencies = [
-            Text::styled("ARMOR \n", Style::default().modifier(Modifier::BOLD).fg(Color::White)),
-            Text::styled("None \n\n", Style::default()),
-            Text::styled("WEAPONS \n", Style::default().modifier(Modifier::BOLD).fg(Color::White)),
-            Text::styled("Crossbow, Light, Dagger, Dart, Quarterstaff, Sling \n\n", Style::default()),
-            Text::styled("TOOLS \n", Style::default().modifier(Modifier::BOLD).fg(Color::White)),
-            Text::styled("Tinker's Tools \n\n", Style::default()),
-            Text::styled("LANGUAGES \n", Style::default().modifier(Modifier::BOLD).fg(Color::White)),
-            Text::styled("Common, Dwarvish, Elvish, Gnomish", Style::default()),
+        let character_proficiencies = self.character.get_proficiencies();
+        let sections = [
+            ("ARMOR", &character_proficiencies.armor),
+            ("WEAPONS", &character_proficiencies.weapons),
+            ("TOOLS", &character_proficiencies.tools),
+            ("LANGUAGES", &character_proficiencies.languages)
         ];
 
+        let proficiencies: Vec<Text> = sections.iter()
+            .filter(|(_, entries)| !entries.is_empty())
+            .flat_map(|(title, entries)| vec![
+                Text::styled(format!("{} \n", title), Style::default().modifier(Modifier::BOLD).fg(Color::White)),
+                Text::styled(format!("{} \n\n", entries.join(", ")), Style::default()),
+            ])
+            .collect();
+
         render_paragraph(f, &proficiencies, &inner_layout[0], &Alignment::Left);
     }
 
@@ -320,31 +557,23 @@ impl MainApp<'_> {
         let inner_layout = create_layout(
             &layout_chunk, Direction::Vertical, &[100], 1);
 
+        let rows: Vec<Vec<String>> = self.character.get_skills().iter()
+            .map(|skill| {
+                let proficiency = if skill.has_proficiency { " ⭐️ " } else { "   " };
+                let roll_mode = roll_mode_marker(self.character.skill_check_mode(skill));
+                let bonus = format!("{:+}{}", self.character.get_skill_bonus(skill), roll_mode);
+
+                vec![proficiency.to_owned(), skill.stat.abbreviation().to_owned(), skill.name.clone(), bonus]
+            })
+            .collect();
+
         let row_style = Style::default().fg(Color::White);
         Table::new(
-            ["Prof", "Mod", "Skill", "Bonus"].into_iter(),
-            vec![
-                Row::StyledData(["   ", "DEX", "Acrobatics", "+3"].into_iter(), row_style),
-                Row::StyledData(["   ", "DEX", "Animal Handling", "+1"].into_iter(), row_style),
-                Row::StyledData([" ⭐️ ", "DEX", "Arcana", "+6"].into_iter(), row_style),
-                Row::StyledData(["   ", "DEX", "Athletics", "+0"].into_iter(), row_style),
-                Row::StyledData(["   ", "DEX", "Deception", "+0"].into_iter(), row_style),
-                Row::StyledData([" ⭐️ ", "DEX", "History", "+6"].into_iter(), row_style),
-                Row::StyledData(["   ", "DEX", "Insight", "+1"].into_iter(), row_style),
-                Row::StyledData(["   ", "DEX", "Intimidation", "+0"].into_iter(), row_style),
-                Row::StyledData([" ⭐️ ", "DEX", "Investigation", "+6"].into_iter(), row_style),
-                Row::StyledData(["   ", "DEX", "Medicine", "+1"].into_iter(), row_style),
-                Row::StyledData(["   ", "DEX", "Nature", "+4"].into_iter(), row_style),
-                Row::StyledData(["   ", "DEX", "Perception", "+1"].into_iter(), row_style),
-                Row::StyledData(["   ", "DEX", "Performance", "+0"].into_iter(), row_style),
-                Row::StyledData(["   ", "DEX", "Religion", "+4"].into_iter(), row_style),
-                Row::StyledData(["   ", "DEX", "Sleight of Hand", "+3"].into_iter(), row_style),
-                Row::StyledData([" ⭐️ ", "DEX", "Stealth", "+5"].into_iter(), row_style),
-                Row::StyledData(["   ", "DEX", "Survival", "+1"].into_iter(), row_style),
-            ].into_iter()
+            ["Prof", "Mod", "Skill", "Bonus"].iter(),
+            rows.iter().map(|row| Row::StyledData(row.iter(), row_style))
         )
             .header_style(Style::default().fg(Color::Yellow))
-            .widths(&[4, 3, 15, 4])
+            .widths(&[4, 3, 15, 5])
             .style(Style::default().fg(Color::White))
             .column_spacing(2)
             .render(f, inner_layout[0]);
@@ -362,17 +591,21 @@ impl MainApp<'_> {
         let inner_layout = create_layout(
             &layout_chunk, Direction::Vertical, &[80, 20], 1);
 
-        let senses = [
-            Text::styled("11   Passive WIS (Perception) \n", Style::default().fg(Color::White)),
-            Text::styled("16   Passive INT (Investigation) \n", Style::default().fg(Color::White)),
-            Text::styled("11   Passive WIS (Insight) \n", Style::default().fg(Color::White)),
-        ];
+        let senses: Vec<Text> = character::PASSIVE_SKILLS.iter()
+            .filter_map(|name| {
+                let skill = self.character.find_skill(name)?;
+                let score = self.character.get_passive_score(name)?;
+                Some(Text::styled(format!("{:<4} Passive {} ({}) \n", score, skill.stat.abbreviation(), skill.name),
+                    Style::default().fg(Color::White)))
+            })
+            .collect();
 
         render_paragraph(f, &senses, &inner_layout[0], &Alignment::Left);
 
-        let darkvision = [
-            Text::styled("Darkvision 60 ft. ", Style::default()),
-        ];
+        let darkvision: Vec<Text> = match self.character.get_darkvision() {
+            0 => Vec::new(),
+            range => vec![Text::styled(format!("Darkvision {} ft. ", range), Style::default())]
+        };
 
         render_paragraph(f, &darkvision, &inner_layout[1], &Alignment::Center);
     }
@@ -389,13 +622,132 @@ impl MainApp<'_> {
             &layout_chunk, Direction::Vertical, &[10, 90], 1);
 
         Tabs::default()
-            .titles(&["Actions", "Spells", "Equipment", "Features & Traits", "Description"])
+            .titles(&TAB_TITLES)
             .style(Style::default().fg(Color::White))
             .highlight_style(Style::default().fg(Color::Yellow))
-            .select(1)
+            .select(self.selected_tab)
             .render(f, inner_layout[0]);
 
-        self.draw_spells_tab(f, inner_layout[1]);
+        if let InputMode::ConditionPicker { selected } = self.input_mode {
+            self.draw_condition_picker(f, inner_layout[1], selected);
+            return;
+        }
+        if let InputMode::Breakdown { selected } = self.input_mode {
+            self.draw_breakdowns(f, inner_layout[1], selected);
+            return;
+        }
+
+        match self.selected_tab {
+            SPELLS_TAB => self.draw_spells_tab(f, inner_layout[1]),
+            EQUIPMENT_TAB => self.draw_equipment_tab(f, inner_layout[1]),
+            FEATURES_TAB => self.draw_features_tab(f, inner_layout[1]),
+            DESCRIPTION_TAB => self.draw_description_tab(f, inner_layout[1]),
+            COMPENDIUM_TAB => self.draw_compendium_tab(f, inner_layout[1]),
+            _ => {}
+        }
+    }
+
+    fn draw_condition_picker<B>(&self, f: &mut Frame<B>, layout_chunk: Rect, selected: usize)
+        where
+            B: Backend
+    {
+        Block::default()
+            .title("Conditions (Enter to toggle, Esc to close)")
+            .borders(Borders::ALL)
+            .render(f, layout_chunk);
+
+        let inner_layout = create_layout(
+            &layout_chunk, Direction::Vertical, &[100], 2);
+
+        let row_style = Style::default().fg(Color::White);
+        let selected_style = Style::default().fg(Color::Yellow).modifier(Modifier::BOLD);
+
+        let rows: Vec<[String; 2]> = Condition::ALL.iter()
+            .map(|condition| {
+                let active = if self.character.has_condition(*condition) { "[x]" } else { "[ ]" };
+                [active.to_owned(), condition.to_string()]
+            })
+            .collect();
+
+        Table::new(
+            ["", "Condition"].iter(),
+            rows.iter().enumerate().map(|(index, row)| {
+                let style = if index == selected { selected_style } else { row_style };
+                Row::StyledData(row.iter(), style)
+            })
+        )
+            .header_style(Style::default().fg(Color::Yellow))
+            .widths(&[3, 20])
+            .style(Style::default().fg(Color::White))
+            .column_spacing(2)
+            .render(f, inner_layout[0]);
+    }
+
+    fn draw_breakdowns<B>(&self, f: &mut Frame<B>, layout_chunk: Rect, selected: usize)
+        where
+            B: Backend
+    {
+        Block::default()
+            .title("Why these numbers (Up/Down to choose, Esc to close)")
+            .borders(Borders::ALL)
+            .render(f, layout_chunk);
+
+        let inner_layout = create_layout(
+            &layout_chunk, Direction::Horizontal, &[40, 60], 2);
+
+        let breakdowns: Vec<character::Breakdown> = self.character.breakdown_targets().iter()
+            .map(|target| self.character.breakdown(target))
+            .collect();
+
+        let row_style = Style::default().fg(Color::White);
+        let selected_style = Style::default().fg(Color::Yellow).modifier(Modifier::BOLD);
+
+        let rows: Vec<[String; 2]> = breakdowns.iter()
+            .map(|breakdown| [breakdown.target.title(), breakdown.value_text()])
+            .collect();
+
+        // Keeps the selected row in view on short terminals.
+        let visible = inner_layout[0].height.saturating_sub(2).max(1) as usize;
+        let skipped = (selected + 1).saturating_sub(visible);
+
+        Table::new(
+            ["Value", "Total"].iter(),
+            rows.iter().enumerate().skip(skipped).map(|(index, row)| {
+                let style = if index == selected { selected_style } else { row_style };
+                Row::StyledData(row.iter(), style)
+            })
+        )
+            .header_style(Style::default().fg(Color::Yellow))
+            .widths(&[18, 12])
+            .style(Style::default().fg(Color::White))
+            .column_spacing(2)
+            .render(f, inner_layout[0]);
+
+        let bold = Style::default().modifier(Modifier::BOLD);
+        let mut details = Vec::new();
+
+        if let Some(breakdown) = breakdowns.get(selected) {
+            details.push(Text::styled(format!("{}: {}\n\n", breakdown.target.title(), breakdown.value_text()), bold));
+            if breakdown.lines.is_empty() {
+                details.push(Text::raw("Nothing changes it\n"));
+            }
+            for line in &breakdown.lines {
+                details.push(Text::raw(format!("{:<14}  {}\n", line.change, line.source)));
+            }
+        }
+
+        details.push(Text::styled("\nActive effects\n", bold));
+        if self.character.get_effects().is_empty() {
+            details.push(Text::raw("None\n"));
+        }
+        for effect in self.character.get_effects() {
+            let modifiers: Vec<String> = effect.modifiers.iter().map(ToString::to_string).collect();
+            details.push(Text::raw(format!("{} ({}): {}\n", effect.name, effect.duration, modifiers.join(", "))));
+        }
+
+        Paragraph::new(details.iter())
+            .wrap(true)
+            .render(f, inner_layout[1]);
     }
 
     fn draw_spells_tab<B>(&self, f: &mut Frame<B>, layout_chunk: Rect)
@@ -403,7 +755,7 @@ impl MainApp<'_> {
             B: Backend
     {
         let inner_layout = create_layout(
-            &layout_chunk, Direction::Vertical, &[10, 90], 2);
+            &layout_chunk, Direction::Vertical, &[15, 85], 2);
 
         self.draw_modifiers(f, inner_layout[0]);
 
@@ -414,24 +766,292 @@ impl MainApp<'_> {
         where
             B: Backend
     {
+        let spellcasting_modifier = self.character.get_spellcasting_ability()
+            .and_then(|stat| self.character.get_modifier(&stat))
+            .map(|modifier| format!("{:+}", modifier))
+            .unwrap_or_else(|| "-".to_owned());
+        let spell_attack = self.character.get_spell_attack_bonus()
+            .map(|bonus| format!("{:+}{}", bonus, roll_mode_marker(self.character.attack_roll_mode())))
+            .unwrap_or_else(|| "-".to_owned());
+        let save_dc = self.character.get_spell_save_dc()
+            .map(|dc| dc.to_string())
+            .unwrap_or_else(|| "-".to_owned());
+
         let modifiers_text = [
             Text::styled("Modifier: ", Style::default().modifier(Modifier::BOLD)),
-            Text::styled("+4   ", Style::default()),
+            Text::styled(spellcasting_modifier + "   ", Style::default()),
             Text::styled("Spell Attack: ", Style::default().modifier(Modifier::BOLD)),
-            Text::styled("+6   ", Style::default()),
+            Text::styled(spell_attack + "   ", Style::default()),
             Text::styled("Save DC: ", Style::default().modifier(Modifier::BOLD)),
-            Text::styled("14 ", Style::default()),
+            Text::styled(save_dc + " \n", Style::default()),
+            Text::styled("Slots: ", Style::default().modifier(Modifier::BOLD)),
+            Text::styled(self.spell_slots_text() + "   ", Style::default()),
+            Text::styled(self.spells_ready_text() + "\n", Style::default()),
+            Text::styled("Enter", Style::default().modifier(Modifier::UNDERLINED)),
+            Text::styled(" to cast | ", Style::default()),
+            Text::styled("R", Style::default().modifier(Modifier::UNDERLINED)),
+            Text::styled("itual | ", Style::default()),
+            Text::styled("P", Style::default().modifier(Modifier::UNDERLINED)),
+            Text::styled("repare | ", Style::default()),
+            Text::styled("B", Style::default().modifier(Modifier::UNDERLINED)),
+            Text::styled("reak concentration", Style::default()),
         ];
 
         render_paragraph(f, &modifiers_text, &layout_chunk, &Alignment::Center);
     }
 
+    /// "1st ●●●○  2nd ●●  (1-9 to use a slot)", one dot per slot, filled while available.
+    fn spell_slots_text(&self) -> String {
+        let slots: Vec<String> = self.character.get_spell_slots().iter()
+            .map(|slots| {
+                let available = "●".repeat(slots.current as usize);
+                let spent = "○".repeat(slots.max.saturating_sub(slots.current) as usize);
+                format!("{} {}{}", character::ordinal(slots.level), available, spent)
+            })
+            .collect();
+
+        if slots.is_empty() {
+            "None".to_owned()
+        } else {
+            slots.join("  ") + "  (1-9 to use a slot)"
+        }
+    }
+
+    /// "Prepared: 6/7" for classes that prepare spells, "Known: 4/6" for
+    /// classes with a limit on the spells they know.
+    fn spells_ready_text(&self) -> String {
+        if let Some(limit) = self.rules.preparation_limit(self.character) {
+            return format!("Prepared: {}/{}", self.character.get_prepared_spells().len(), limit);
+        }
+
+        let known = self.known_spells().iter().filter(|(spell, _)| spell.is_some_and(|spell| spell.level > 0)).count();
+        match self.rules.find_class(self.character.get_class())
+            .and_then(|class| class.spellcasting.as_ref())
+            .and_then(|spellcasting| spellcasting.spells_known_at(*self.character.get_level())) {
+            Some(limit) => format!("Known: {}/{}", known, limit),
+            None => String::new()
+        }
+    }
+
     fn draw_spells_list<B>(&self, f: &mut Frame<B>, layout_chunk: Rect)
         where
             B: Backend
     {
         Block::default()
-            .title("draw_spells_list")
+            .title("Spells")
+            .borders(Borders::ALL)
+            .render(f, layout_chunk);
+
+        let inner_layout = create_layout(
+            &layout_chunk, Direction::Vertical, &[100], 2);
+
+        let spells = self.known_spells();
+        let prepares = self.rules.preparation_limit(self.character).is_some();
+
+        let rows: Vec<Vec<String>> = spells.iter()
+            .map(|(spell, name)| match spell {
+                Some(spell) => vec![
+                    match (prepares, spell.level, self.character.is_prepared(&spell.id())) {
+                        (false, _, _) | (_, 0, _) => String::new(),
+                        (true, _, true) => "●".to_owned(),
+                        (true, _, false) => "○".to_owned()
+                    },
+                    spell.level_text(),
+                    spell.name.clone(),
+                    spell.short_casting_time(),
+                    spell.short_range(),
+                    self.spell_hit_or_dc(spell),
+                    spell.effect.clone(),
+                    spell.notes()
+                ],
+                None => vec![String::new(), "?".to_owned(), (*name).clone(), String::new(), String::new(), String::new(),
+                    String::new(), "Not in the rules".to_owned()]
+            })
+            .collect();
+
+        let row_style = Style::default().fg(Color::White);
+        let concentration_style = Style::default().fg(Color::Cyan);
+        let unprepared_style = Style::default().fg(Color::DarkGray);
+        let selected_style = Style::default().fg(Color::Yellow).modifier(Modifier::BOLD);
+        let selected = self.selected_rows[SPELLS_TAB];
+        let concentration = self.character.get_concentration();
+
+        // tui 0.5 does not scroll tables, so the list starts late enough to show the selection.
+        let visible = inner_layout[0].height.saturating_sub(2) as usize;
+        let offset = (selected + 1).saturating_sub(visible);
+
+        Table::new(
+            ["Prep", "Lvl", "Name", "Time", "Range", "HIT/DC", "Effect", "Notes"].iter(),
+            rows.iter().zip(spells.iter()).enumerate().skip(offset).map(|(index, (row, (spell, reference)))| {
+                let style = if index == selected {
+                    selected_style
+                } else if concentration.is_some_and(|spell| spell.eq_ignore_ascii_case(reference)) {
+                    concentration_style
+                } else if spell.is_some_and(|spell| !self.rules.is_ready(self.character, spell)) {
+                    unprepared_style
+                } else {
+                    row_style
+                };
+                Row::StyledData(row.iter(), style)
+            })
+        )
+            .header_style(Style::default().fg(Color::Yellow))
+            .widths(&[4, 3, 18, 4, 6, 6, 14, 20])
+            .style(Style::default().fg(Color::White))
+            .column_spacing(2)
+            .render(f, inner_layout[0]);
+    }
+
+    /// The spells the character knows, then prepared spells from the class
+    /// list they do not, looked up in the rules, lowest level first; spells
+    /// missing from the rules come last.
+    fn known_spells(&self) -> Vec<(Option<&Spell>, &String)> {
+        let known = self.character.get_spells();
+        let prepared = self.character.get_prepared_spells().iter()
+            .filter(|id| !known.iter().any(|known| known.eq_ignore_ascii_case(id)));
+
+        let mut spells: Vec<(Option<&Spell>, &String)> = known.iter().chain(prepared)
+            .map(|reference| (self.rules.find_spell(reference), reference))
+            .collect();
+        spells.sort_by_key(|(spell, _)| spell.map(|spell| spell.level).unwrap_or(u8::MAX));
+        spells
+    }
+
+    /// "+6" for spell attacks, "DEX 14" for spells with a saving throw, "-" otherwise.
+    fn spell_hit_or_dc(&self, spell: &Spell) -> String {
+        match (spell.attack, spell.save, self.character.get_spell_attack_bonus(), self.character.get_spell_save_dc()) {
+            (true, _, Some(bonus), _) => format!("{:+}", bonus),
+            (_, Some(save), _, Some(dc)) => format!("{} {}", save.abbreviation(), dc),
+            _ => "-".to_owned()
+        }
+    }
+
+    fn draw_equipment_tab<B>(&self, f: &mut Frame<B>, layout_chunk: Rect)
+        where
+            B: Backend
+    {
+        let inner_layout = create_layout(
+            &layout_chunk, Direction::Vertical, &[10, 65, 25], 2);
+
+        let attunement_text = [
+            Text::styled("Attuned: ", Style::default().modifier(Modifier::BOLD)),
+            Text::styled(format!("{}/{}   ", self.character.get_attuned_count(), character::MAX_ATTUNED_ITEMS), Style::default()),
+            Text::styled("A", Style::default().modifier(Modifier::UNDERLINED)),
+            Text::styled("ttune | ", Style::default()),
+            Text::styled("U", Style::default().modifier(Modifier::UNDERLINED)),
+            Text::styled("se charge", Style::default()),
+        ];
+
+        render_paragraph(f, &attunement_text, &inner_layout[0], &Alignment::Center);
+
+        self.draw_inventory(f, inner_layout[1]);
+
+        self.draw_item_description(f, inner_layout[2]);
+    }
+
+    /// What the content packs say about the selected item.
+    fn draw_item_description<B>(&self, f: &mut Frame<B>, layout_chunk: Rect)
+        where
+            B: Backend
+    {
+        let item = self.character.get_inventory().get(self.selected_rows[EQUIPMENT_TAB]);
+
+        Block::default()
+            .title(item.map(|item| item.name.as_str()).unwrap_or("Description"))
+            .borders(Borders::ALL)
+            .render(f, layout_chunk);
+
+        let inner_layout = create_layout(
+            &layout_chunk, Direction::Vertical, &[100], 1);
+
+        let description = match item.map(|item| self.rules.find_item(&item.name)) {
+            Some(Some(definition)) => vec![
+                Text::styled(format!("{}\n", definition.category), Style::default().modifier(Modifier::ITALIC)),
+                Text::styled(format!("{}\n", definition.description), Style::default()),
+                Text::styled(definition.id(), Style::default().fg(Color::Gray))
+            ],
+            Some(None) => vec![Text::styled("Not in the rules", Style::default().fg(Color::Gray))],
+            None => Vec::new()
+        };
+
+        render_paragraph(f, &description, &inner_layout[0], &Alignment::Left);
+    }
+
+    fn draw_inventory<B>(&self, f: &mut Frame<B>, layout_chunk: Rect)
+        where
+            B: Backend
+    {
+        Block::default()
+            .title("Inventory")
+            .borders(Borders::ALL)
+            .render(f, layout_chunk);
+
+        let inner_layout = create_layout(
+            &layout_chunk, Direction::Vertical, &[100], 2);
+
+        let row_style = Style::default().fg(Color::White);
+        let selected_style = Style::default().fg(Color::Yellow).modifier(Modifier::BOLD);
+
+        let rows: Vec<Vec<String>> = self.character.get_inventory().iter()
+            .map(|item| {
+                let attunement = match (item.requires_attunement, item.attuned) {
+                    (true, true) => " ⭐️ ",
+                    (true, false) => " ( )",
+                    _ => "    "
+                };
+                let (charges, recharge) = match &item.charges {
+                    Some(charges) => (
+                        format!("{}/{}", charges.current, charges.max),
+                        charges.recharge.map(|recharge| recharge.to_string()).unwrap_or_default()
+                    ),
+                    None => ("-".to_owned(), String::new())
+                };
+
+                vec![attunement.to_owned(), item.name.clone(), item.quantity.to_string(), charges, recharge]
+            })
+            .collect();
+
+        Table::new(
+            ["Att", "Name", "Qty", "Charges", "Recharge"].iter(),
+            rows.iter().enumerate().map(|(index, row)| {
+                let style = if index == self.selected_rows[EQUIPMENT_TAB] { selected_style } else { row_style };
+                Row::StyledData(row.iter(), style)
+            })
+        )
+            .header_style(Style::default().fg(Color::Yellow))
+            .widths(&[4, 24, 3, 7, 26])
+            .style(Style::default().fg(Color::White))
+            .column_spacing(2)
+            .render(f, inner_layout[0]);
+    }
+
+    fn draw_features_tab<B>(&self, f: &mut Frame<B>, layout_chunk: Rect)
+        where
+            B: Backend
+    {
+        let inner_layout = create_layout(
+            &layout_chunk, Direction::Vertical, &[10, 55, 35], 2);
+
+        let hint_text = [
+            Text::styled("U", Style::default().modifier(Modifier::UNDERLINED)),
+            Text::styled("se | ", Style::default()),
+            Text::styled("R", Style::default().modifier(Modifier::UNDERLINED)),
+            Text::styled("estore use", Style::default()),
+        ];
+
+        render_paragraph(f, &hint_text, &inner_layout[0], &Alignment::Center);
+
+        self.draw_features_list(f, inner_layout[1]);
+
+        self.draw_feature_description(f, inner_layout[2]);
+    }
+
+    fn draw_features_list<B>(&self, f: &mut Frame<B>, layout_chunk: Rect)
+        where
+            B: Backend
+    {
+        Block::default()
+            .title("Features & Traits")
             .borders(Borders::ALL)
             .render(f, layout_chunk);
 
@@ -439,28 +1059,282 @@ impl MainApp<'_> {
             &layout_chunk, Direction::Vertical, &[100], 2);
 
         let row_style = Style::default().fg(Color::White);
+        let selected_style = Style::default().fg(Color::Yellow).modifier(Modifier::BOLD);
+
+        let rows: Vec<Vec<String>> = self.character.get_features().iter()
+            .map(|feature| {
+                let (uses, recharge) = match &feature.uses {
+                    Some(uses) => (
+                        "[x]".repeat(uses.max.saturating_sub(uses.current) as usize) + &"[ ]".repeat(uses.current as usize),
+                        uses.recharge.to_string()
+                    ),
+                    None => ("-".to_owned(), String::new())
+                };
+
+                vec![feature.source.to_string(), feature.name.clone(), uses, recharge]
+            })
+            .collect();
+
         Table::new(
-            ["Lvl", "Name", "Time", "Range", "HIT/DC", "Effect", "Notes"].into_iter(),
-            vec![
-                Row::StyledData(["C", "Fire Bolt", "1A", "120ft", "+6", "1d10 🔥", "V/S"].into_iter(), row_style),
-                Row::StyledData(["C", "Mage Hand", "1A", "30ft", "-", "Utility", "D: 1m, V/S"].into_iter(), row_style),
-                Row::StyledData(["C", "Prestidigitation", "1A", "10ft", "-", "Utility", "D: 1m, V/S"].into_iter(), row_style),
-                Row::StyledData(["1", "Burning Hands", "1A", "Self", "DEX 14", "3d6 🔥", "15ft cone, V/S"].into_iter(), row_style),
-                Row::StyledData(["1", "Find Familiar", "1h", "10ft", "-", "Summoning", "V/S/M"].into_iter(), row_style),
-                Row::StyledData(["1", "Identify", "1m", "Touch", "-", "Detection", "V/S/M"].into_iter(), row_style),
-                Row::StyledData(["1", "Illusory Script", "1m", "Touch", "-", "Communication", "D: 10d, S/M"].into_iter(), row_style),
-                Row::StyledData(["1", "Mage Armor", "1A", "Touch", "-", "Buff*", "D: 8h, V/S/M"].into_iter(), row_style),
-                Row::StyledData(["1", "Magic Missile", "1A", "120ft", "-", "1d4+1 ☄", "V/S"].into_iter(), row_style),
-                Row::StyledData(["2", "Burning Hands", "1A", "Self", "DEX 14", "4d6 🔥", "15ft cone, V/S"].into_iter(), row_style),
-                Row::StyledData(["2", "Magic Missile", "1A", "120ft", "-", "1d4+1 ☄", "Count: +1, V/S"].into_iter(), row_style),
-            ].into_iter()
+            ["Source", "Name", "Uses", "Recharge"].iter(),
+            rows.iter().enumerate().map(|(index, row)| {
+                let style = if index == self.selected_rows[FEATURES_TAB] { selected_style } else { row_style };
+                Row::StyledData(row.iter(), style)
+            })
         )
             .header_style(Style::default().fg(Color::Yellow))
-            .widths(&[2, 20, 3, 6, 6, 8, 15])
+            .widths(&[10, 22, 12, 26])
             .style(Style::default().fg(Color::White))
             .column_spacing(2)
             .render(f, inner_layout[0]);
     }
+
+    fn draw_feature_description<B>(&self, f: &mut Frame<B>, layout_chunk: Rect)
+        where
+            B: Backend
+    {
+        let feature = self.character.get_features().get(self.selected_rows[FEATURES_TAB]);
+
+        Block::default()
+            .title(feature.map(|feature| feature.name.as_str()).unwrap_or("Description"))
+            .borders(Borders::ALL)
+            .render(f, layout_chunk);
+
+        let inner_layout = create_layout(
+            &layout_chunk, Direction::Vertical, &[100], 1);
+
+        let description = [
+            Text::styled(feature.map(|feature| feature.description.as_str()).unwrap_or(""), Style::default()),
+        ];
+
+        render_paragraph(f, &description, &inner_layout[0], &Alignment::Left);
+    }
+
+    fn draw_description_tab<B>(&self, f: &mut Frame<B>, layout_chunk: Rect)
+        where
+            B: Backend
+    {
+        let inner_layout = create_layout(
+            &layout_chunk, Direction::Vertical, &[20, 80], 2);
+
+        self.draw_appearance(f, inner_layout[0]);
+
+        self.draw_story(f, inner_layout[1]);
+    }
+
+    fn draw_appearance<B>(&self, f: &mut Frame<B>, layout_chunk: Rect)
+        where
+            B: Backend
+    {
+        let description = self.character.get_description();
+
+        let appearance_text = [
+            Text::styled("Background: ", Style::default().modifier(Modifier::BOLD)),
+            Text::styled(description.background.clone() + " | ", Style::default()),
+            Text::styled("Alignment: ", Style::default().modifier(Modifier::BOLD)),
+            Text::styled(description.alignment.clone() + "\n", Style::default()),
+            Text::styled("Age: ", Style::default().modifier(Modifier::BOLD)),
+            Text::styled(description.age.clone() + " | ", Style::default()),
+            Text::styled("Height: ", Style::default().modifier(Modifier::BOLD)),
+            Text::styled(description.height.clone() + " | ", Style::default()),
+            Text::styled("Weight: ", Style::default().modifier(Modifier::BOLD)),
+            Text::styled(description.weight.clone() + "\n", Style::default()),
+            Text::styled("Eyes: ", Style::default().modifier(Modifier::BOLD)),
+            Text::styled(description.eyes.clone() + " | ", Style::default()),
+            Text::styled("Skin: ", Style::default().modifier(Modifier::BOLD)),
+            Text::styled(description.skin.clone() + " | ", Style::default()),
+            Text::styled("Hair: ", Style::default().modifier(Modifier::BOLD)),
+            Text::styled(description.hair.clone(), Style::default()),
+        ];
+
+        render_paragraph(f, &appearance_text, &layout_chunk, &Alignment::Center);
+    }
+
+    fn story_text(&self) -> Vec<Text<'_>> {
+        let description = self.character.get_description();
+
+        let sections = [
+            ("PERSONALITY TRAITS", &description.personality_traits),
+            ("IDEALS", &description.ideals),
+            ("BONDS", &description.bonds),
+            ("FLAWS", &description.flaws),
+            ("BACKSTORY", &description.backstory),
+            ("ALLIES & ORGANIZATIONS", &description.allies_and_organizations),
+        ];
+
+        sections.iter()
+            .filter(|(_, text)| !text.is_empty())
+            .flat_map(|(title, text)| vec![
+                Text::styled(title.to_string() + " \n", Style::default().modifier(Modifier::BOLD).fg(Color::White)),
+                Text::styled(text.to_string() + " \n\n", Style::default()),
+            ])
+            .collect()
+    }
+
+    fn draw_story<B>(&self, f: &mut Frame<B>, layout_chunk: Rect)
+        where
+            B: Backend
+    {
+        Block::default()
+            .title("Story (scroll with ↑/↓)")
+            .borders(Borders::ALL)
+            .render(f, layout_chunk);
+
+        let inner_layout = create_layout(
+            &layout_chunk, Direction::Vertical, &[100], 2);
+
+        let story = self.story_text();
+
+        Paragraph::new(story.iter())
+            .alignment(Alignment::Left)
+            .wrap(true)
+            .scroll(self.description_scroll)
+            .render(f, inner_layout[0]);
+    }
+
+    fn draw_compendium_tab<B>(&self, f: &mut Frame<B>, layout_chunk: Rect)
+        where
+            B: Backend
+    {
+        let inner_layout = create_layout(
+            &layout_chunk, Direction::Vertical, &[10, 90], 2);
+
+        self.draw_compendium_filters(f, inner_layout[0]);
+
+        let columns = create_layout(
+            &inner_layout[1], Direction::Horizontal, &[55, 45], 0);
+        let spells = self.rules.search_spells(&self.compendium);
+
+        self.draw_compendium_list(f, columns[0], &spells);
+
+        self.draw_spell_details(f, columns[1], spells.get(self.selected_rows[COMPENDIUM_TAB]).copied());
+    }
+
+    fn draw_compendium_filters<B>(&self, f: &mut Frame<B>, layout_chunk: Rect)
+        where
+            B: Backend
+    {
+        let filter = &self.compendium;
+        let any = || "any".to_owned();
+        let only_or_no = |value: Option<bool>| match value {
+            Some(true) => "only".to_owned(),
+            Some(false) => "no".to_owned(),
+            None => any()
+        };
+        let cursor = if let InputMode::CompendiumSearch = self.input_mode { "_" } else { "" };
+        let bold = Style::default().modifier(Modifier::BOLD);
+        let underlined = Style::default().modifier(Modifier::UNDERLINED);
+
+        let filters_text = [
+            Text::styled("Search: ", bold),
+            Text::styled(format!("{}{}   ", filter.query, cursor), Style::default().fg(Color::Yellow)),
+            Text::styled("C", underlined),
+            Text::styled(format!("lass: {}   ", filter.class.clone().unwrap_or_else(any)), Style::default()),
+            Text::styled("L", underlined),
+            Text::styled(format!("evel: {}   ", filter.level.map(|level| level.to_string()).unwrap_or_else(any)), Style::default()),
+            Text::styled("S", underlined),
+            Text::styled(format!("chool: {}   ", filter.school.clone().unwrap_or_else(any)), Style::default()),
+            Text::styled("R", underlined),
+            Text::styled(format!("itual: {}   ", only_or_no(filter.ritual)), Style::default()),
+            Text::styled("c", Style::default()),
+            Text::styled("O", underlined),
+            Text::styled(format!("ncentration: {}\n", only_or_no(filter.concentration)), Style::default()),
+            Text::styled("/ to search | ", Style::default()),
+            Text::styled("A", underlined),
+            Text::styled("dd to spellbook", Style::default()),
+        ];
+
+        render_paragraph(f, &filters_text, &layout_chunk, &Alignment::Center);
+    }
+
+    fn draw_compendium_list<B>(&self, f: &mut Frame<B>, layout_chunk: Rect, spells: &[&Spell])
+        where
+            B: Backend
+    {
+        let selected = self.selected_rows[COMPENDIUM_TAB];
+        let rows: Vec<Vec<String>> = spells.iter()
+            .map(|spell| {
+                let known = self.character.get_spells().iter().any(|known| spell.matches(known));
+                vec![
+                    if known { "⭐️".to_owned() } else { String::new() },
+                    spell.level_text(),
+                    spell.name.clone(),
+                    spell.school.clone(),
+                    spell.notes()
+                ]
+            })
+            .collect();
+
+        let row_style = Style::default().fg(Color::White);
+        let selected_style = Style::default().fg(Color::Yellow).modifier(Modifier::BOLD);
+
+        // tui 0.5 does not scroll tables, so the list starts late enough to show the selection.
+        let visible = layout_chunk.height.saturating_sub(4) as usize;
+        let offset = (selected + 1).saturating_sub(visible);
+
+        Table::new(
+            ["", "Lvl", "Name", "School", "Notes"].iter(),
+            rows.iter().enumerate().skip(offset).map(|(index, row)| {
+                let style = if index == selected { selected_style } else { row_style };
+                Row::StyledData(row.iter(), style)
+            })
+        )
+            .block(Block::default().title(&format!("Spells ({})", spells.len())).borders(Borders::ALL))
+            .header_style(Style::default().fg(Color::Yellow))
+            .widths(&[3, 3, 22, 13, 20])
+            .style(Style::default().fg(Color::White))
+            .column_spacing(2)
+            .render(f, layout_chunk);
+    }
+
+    /// Everything the rules say about `spell`, and the pack it comes from.
+    fn draw_spell_details<B>(&self, f: &mut Frame<B>, layout_chunk: Rect, spell: Option<&Spell>)
+        where
+            B: Backend
+    {
+        let block = Block::default()
+            .title(spell.map(|spell| spell.name.as_str()).unwrap_or("No spell matches"))
+            .borders(Borders::ALL);
+
+        let spell = match spell {
+            Some(spell) => spell,
+            None => {
+                Paragraph::new([].iter()).block(block).render(f, layout_chunk);
+                return;
+            }
+        };
+
+        let level = if spell.level == 0 {
+            format!("{} cantrip", spell.school)
+        } else {
+            format!("{} level {}", character::ordinal(spell.level), spell.school.to_lowercase())
+        };
+        let ritual = if spell.ritual { " (ritual)" } else { "" };
+        let duration = if spell.concentration { format!("Concentration, {}", spell.duration) } else { spell.duration.clone() };
+        let source = self.rules.sources.get(&spell.id())
+            .and_then(|sources| sources.last())
+            .map(|source| format!(" from {}", source))
+            .unwrap_or_default();
+        let bold = Style::default().modifier(Modifier::BOLD);
+
+        let details = [
+            Text::styled(format!("{}{}\n\n", level, ritual), Style::default().modifier(Modifier::ITALIC)),
+            Text::styled("Casting time: ", bold),
+            Text::styled(format!("{}\n", spell.casting_time), Style::default()),
+            Text::styled("Range: ", bold),
+            Text::styled(format!("{}\n", spell.range), Style::default()),
+            Text::styled("Components: ", bold),
+            Text::styled(format!("{}\n", spell.components), Style::default()),
+            Text::styled("Duration: ", bold),
+            Text::styled(format!("{}\n", duration), Style::default()),
+            Text::styled("Classes: ", bold),
+            Text::styled(format!("{}\n\n", spell.classes.join(", ")), Style::default()),
+            Text::styled(format!("{}\n\n", spell.description), Style::default()),
+            Text::styled(format!("{}{}", spell.id(), source), Style::default().fg(Color::Gray))
+        ];
+
+        Paragraph::new(details.iter()).block(block).wrap(true).render(f, layout_chunk);
+    }
     // ####### END MAIN ########
 
     // ####### FOOTER ########
@@ -468,22 +1342,579 @@ impl MainApp<'_> {
         where
             B: Backend
     {
+        let title = match (self.autosave, self.unsaved_changes) {
+            (true, _) => "Log (autosave on)",
+            (false, true) => "Log (unsaved changes, Ctrl-S to save)",
+            (false, false) => "Log"
+        };
+
         Block::default()
-            .title("draw_footer")
+            .title(title)
             .borders(Borders::ALL)
             .render(f, layout_chunk);
+
+        let inner_layout = create_layout(
+            &layout_chunk, Direction::Vertical, &[100], 1);
+
+        let last_entry = match &self.input_mode {
+            InputMode::AmountPrompt { change, amount } => format!("{}: {}_ (Enter to apply, Esc to cancel)",
+                match change {
+                    AmountChange::Damage => "Damage",
+                    AmountChange::Heal => "Heal",
+                    AmountChange::Experience => "Award XP"
+                }, amount),
+            InputMode::CompendiumSearch => "Type to search spells, Up/Down to choose, Enter to finish, Esc to clear".to_owned(),
+            InputMode::ConfirmCast { spell, .. } => format!("Casting {} ends the concentration on {}: Enter to cast anyway, Esc to cancel",
+                self.rules.spell_name(spell), self.rules.spell_name(self.character.get_concentration().unwrap_or_default())),
+            InputMode::ConcentrationSave { dc } => format!("CON save DC {} to keep concentrating on {}: (r)oll, (s)ucceeded or (f)ailed",
+                dc, self.rules.spell_name(self.character.get_concentration().unwrap_or_default())),
+            InputMode::Breakdown { .. } => "Up/Down to choose a number, Esc or w to close".to_owned(),
+            _ => self.log.last().cloned().unwrap_or_default()
+        };
+        let footer = [
+            Text::styled(last_entry, Style::default().fg(Color::White)),
+        ];
+
+        render_paragraph(f, &footer, &inner_layout[0], &Alignment::Left);
     }
     // ####### END FOOTER ########
 
-    pub fn draw_app(&self) -> Result<(), io::Error> {
+    /// Reports an upgrade of an older file; it is written in the new format on the next save.
+    pub fn log_migrations(&mut self, migrations: &[String]) {
+        if migrations.is_empty() {
+            return;
+        }
+
+        self.log.push(format!("Upgraded to schema version {} ({})",
+            character::CURRENT_SCHEMA_VERSION, migrations.join(", ")));
+        self.mark_changed();
+    }
+
+    // ####### INPUT ########
+    fn save(&mut self) {
+        match character::save_character(self.character, &self.character_path) {
+            Ok(()) => {
+                self.unsaved_changes = false;
+                if !self.autosave {
+                    self.log.push(format!("Saved to {}", self.character_path.display()));
+                }
+            },
+            Err(e) => self.log.push(format!("Could not save {}: {}", self.character_path.display(), e))
+        }
+    }
+
+    /// Called after anything on the character changes.
+    fn mark_changed(&mut self) {
+        self.unsaved_changes = true;
+
+        if self.autosave {
+            self.save();
+        }
+    }
+
+    fn log_rest(&mut self, rest: &str, changes: Vec<String>) {
+        if changes.is_empty() {
+            self.log.push(format!("{}: nothing to recover", rest));
+        } else {
+            self.log.push(format!("{}: {}", rest, changes.join(", ")));
+            self.mark_changed();
+        }
+    }
+
+    fn selected_row_count(&self) -> usize {
+        match self.selected_tab {
+            SPELLS_TAB => self.known_spells().len(),
+            EQUIPMENT_TAB => self.character.get_inventory().len(),
+            FEATURES_TAB => self.character.get_features().len(),
+            COMPENDIUM_TAB => self.rules.search_spells(&self.compendium).len(),
+            _ => 0
+        }
+    }
+
+    fn select_previous_row(&mut self) {
+        let row = &mut self.selected_rows[self.selected_tab];
+        *row = row.saturating_sub(1);
+    }
+
+    fn select_next_row(&mut self) {
+        let last = self.selected_row_count().saturating_sub(1);
+        let row = &mut self.selected_rows[self.selected_tab];
+        *row = (*row + 1).min(last);
+    }
+
+    fn scroll_description(&mut self, lines: i16) {
+        // Wrapped line count depends on the terminal width, so the raw line
+        // count is used as a generous upper bound.
+        let max_scroll = self.story_text().iter()
+            .map(|text| match text {
+                Text::Raw(text) | Text::Styled(text, _) => text.lines().count() as u16
+            })
+            .sum::<u16>();
+
+        let scroll = self.description_scroll as i16 + lines;
+        self.description_scroll = scroll.max(0).min(max_scroll as i16) as u16;
+    }
+
+    fn selected_item_name(&self) -> Option<String> {
+        self.character.get_inventory().get(self.selected_rows[EQUIPMENT_TAB])
+            .map(|item| item.name.clone())
+    }
+
+    fn selected_feature_name(&self) -> Option<String> {
+        self.character.get_features().get(self.selected_rows[FEATURES_TAB])
+            .map(|feature| feature.name.clone())
+    }
+
+    fn toggle_attunement(&mut self) {
+        let name = match self.selected_item_name() {
+            Some(name) => name,
+            None => return
+        };
+
+        let attuned = self.character.get_inventory()[self.selected_rows[EQUIPMENT_TAB]].attuned;
+        let result = if attuned {
+            self.character.end_attunement(&name).map(|_| format!("No longer attuned to {}", name))
+        } else {
+            self.character.attune(&name).map(|_| format!("Attuned to {}", name))
+        };
+
+        match result {
+            Ok(message) => {
+                self.log.push(message);
+                self.mark_changed();
+            },
+            Err(e) => self.log.push(e.to_string())
+        }
+    }
+
+    fn use_charge(&mut self) {
+        let name = match self.selected_item_name() {
+            Some(name) => name,
+            None => return
+        };
+
+        match self.character.use_charges(&name, 1) {
+            Ok(remaining) => {
+                self.log.push(format!("Used a charge of {} ({} left)", name, remaining));
+                self.mark_changed();
+            },
+            Err(e) => self.log.push(e.to_string())
+        }
+    }
+
+    fn use_feature(&mut self) {
+        let name = match self.selected_feature_name() {
+            Some(name) => name,
+            None => return
+        };
+
+        match self.character.use_feature(&name) {
+            Ok(remaining) => {
+                self.log.push(format!("Used {} ({} left)", name, remaining));
+                self.mark_changed();
+            },
+            Err(e) => self.log.push(e.to_string())
+        }
+    }
+
+    fn restore_feature_use(&mut self) {
+        let name = match self.selected_feature_name() {
+            Some(name) => name,
+            None => return
+        };
+
+        match self.character.restore_feature_use(&name) {
+            Ok(remaining) => {
+                self.log.push(format!("Restored a use of {} ({} left)", name, remaining));
+                self.mark_changed();
+            },
+            Err(e) => self.log.push(e.to_string())
+        }
+    }
+
+    fn change_exhaustion(&mut self, delta: i8) {
+        let level = (*self.character.get_exhaustion() as i8 + delta).max(0) as u8;
+
+        match self.character.set_exhaustion(level) {
+            Ok(()) => {
+                self.log.push(format!("Exhaustion level {}", level));
+                self.mark_changed();
+            },
+            Err(e) => self.log.push(e)
+        }
+    }
+
+    fn use_spell_slot(&mut self, level: u8) {
+        match self.character.use_spell_slot(level) {
+            Ok(remaining) => {
+                self.log.push(format!("Used a {} level spell slot ({} left)", character::ordinal(level), remaining));
+                self.mark_changed();
+            },
+            Err(e) => self.log.push(e.to_string())
+        }
+    }
+
+    /// Casts the selected spell, with a slot or as a ritual, asking first if
+    /// that ends the concentration on another one.
+    fn cast_selected_spell(&mut self, confirmed: bool, ritual: bool) {
+        let reference = match self.known_spells().get(self.selected_rows[SPELLS_TAB]) {
+            Some((_, reference)) => (*reference).clone(),
+            None => return
+        };
+        let spell = match self.rules.find_spell(&reference) {
+            Some(spell) => spell,
+            None => {
+                self.log.push(format!("{} is not in the rules", reference));
+                return;
+            }
+        };
+
+        if !confirmed && self.rules.concentration_conflict(self.character, spell).is_some() {
+            self.input_mode = InputMode::ConfirmCast { spell: reference, ritual };
+            return;
+        }
+
+        let result = if ritual { self.rules.cast_ritual(self.character, spell) } else { self.rules.cast_spell(self.character, spell) };
+        match result {
+            Ok(changes) => {
+                self.log.push(changes.join(", "));
+                self.mark_changed();
+            },
+            Err(e) => self.log.push(e)
+        }
+    }
+
+    /// Prepares the spell selected in the spells tab or the compendium, or
+    /// unprepares it if it is prepared.
+    fn toggle_prepared(&mut self) {
+        let reference = if self.selected_tab == COMPENDIUM_TAB {
+            self.rules.search_spells(&self.compendium).get(self.selected_rows[COMPENDIUM_TAB]).map(|spell| spell.id())
+        } else {
+            self.known_spells().get(self.selected_rows[SPELLS_TAB]).map(|(_, reference)| (*reference).clone())
+        };
+        let rules = &self.rules;
+        let spell = match reference.and_then(|reference| rules.find_spell(&reference)) {
+            Some(spell) => spell,
+            None => return
+        };
+
+        let prepare = !self.character.is_prepared(&spell.id());
+        match self.rules.prepare_spell(self.character, spell, prepare) {
+            Ok(message) => {
+                self.log.push(message);
+                self.mark_changed();
+            },
+            Err(e) => self.log.push(e)
+        }
+    }
+
+    fn break_concentration(&mut self) {
+        match self.character.end_concentration() {
+            Some(spell) => {
+                self.log.push(format!("Stopped concentrating on {}", self.rules.spell_name(&spell)));
+                self.mark_changed();
+            },
+            None => self.log.push("Not concentrating on a spell".to_owned())
+        }
+    }
+
+    fn handle_confirm_cast_key(&mut self, key: Key, ritual: bool) {
+        self.input_mode = InputMode::Normal;
+
+        match key {
+            Key::Char('\n') | Key::Char('y') => self.cast_selected_spell(true, ritual),
+            _ => self.log.push("Cast cancelled".to_owned())
+        }
+    }
+
+    /// The player rolls their own die and says how it went, or lets the sheet roll.
+    fn handle_concentration_save_key(&mut self, key: Key, dc: u16) {
+        let spell = self.rules.spell_name(self.character.get_concentration().unwrap_or_default()).to_owned();
+        let message = match key {
+            Key::Char('r') => match self.character.roll_concentration_save(dc) {
+                Some(save) => self.rules.describe_concentration_save(&save),
+                None => String::new()
+            },
+            Key::Char('s') => format!("Kept concentration on {}", spell),
+            Key::Char('f') => {
+                self.character.end_concentration();
+                format!("Lost concentration on {}", spell)
+            },
+            _ => return
+        };
+
+        self.input_mode = InputMode::Normal;
+        self.log.push(message);
+        self.mark_changed();
+    }
+
+    /// Adds the spell selected in the compendium to the character's spells.
+    fn add_compendium_spell(&mut self) {
+        let spell = match self.rules.search_spells(&self.compendium).get(self.selected_rows[COMPENDIUM_TAB]) {
+            Some(spell) => *spell,
+            None => return
+        };
+        let (id, name) = (spell.id(), spell.name.clone());
+        let class_spell = spell.classes.iter().any(|name| name.eq_ignore_ascii_case(self.character.get_class()));
+
+        if let Err(e) = self.rules.check_can_learn(self.character, spell) {
+            self.log.push(format!("Cannot add {}: {}", name, e));
+            return;
+        }
+
+        match self.character.add_spell(&id) {
+            Ok(()) => {
+                let note = if class_spell { String::new() } else { format!(" (not a {} spell)", self.character.get_class()) };
+                self.log.push(format!("Added {} to the spellbook{}", name, note));
+                self.mark_changed();
+            },
+            Err(_) => self.log.push(format!("{} already knows {}", self.character.get_name(), name))
+        }
+    }
+
+    /// Changes a compendium filter, starting the list over at the top.
+    fn change_compendium_filter(&mut self, key: char) {
+        let filter = &mut self.compendium;
+        match key {
+            'C' => {
+                let classes: Vec<String> = self.rules.spell_classes().into_iter().map(str::to_owned).collect();
+                filter.class = next_option(&filter.class, &classes);
+            },
+            'L' => filter.level = next_option(&filter.level, &(0..=character::MAX_SPELL_LEVEL).collect::<Vec<u8>>()),
+            'S' => {
+                let schools: Vec<String> = self.rules.spell_schools().into_iter().map(str::to_owned).collect();
+                filter.school = next_option(&filter.school, &schools);
+            },
+            'R' => filter.ritual = next_option(&filter.ritual, &[true, false]),
+            'O' => filter.concentration = next_option(&filter.concentration, &[true, false]),
+            _ => return
+        }
+
+        self.selected_rows[COMPENDIUM_TAB] = 0;
+    }
+
+    fn handle_compendium_search_key(&mut self, key: Key) {
+        match key {
+            Key::Char('\n') => self.input_mode = InputMode::Normal,
+            Key::Esc => {
+                self.compendium.query.clear();
+                self.input_mode = InputMode::Normal;
+            },
+            Key::Up => self.select_previous_row(),
+            Key::Down => self.select_next_row(),
+            Key::Backspace => {
+                self.compendium.query.pop();
+                self.selected_rows[COMPENDIUM_TAB] = 0;
+            },
+            Key::Char(c) => {
+                self.compendium.query.push(c);
+                self.selected_rows[COMPENDIUM_TAB] = 0;
+            },
+            _ => {}
+        }
+    }
+
+    fn handle_amount_prompt_key(&mut self, key: Key, change: AmountChange, mut amount: String) {
+        match key {
+            Key::Esc => {
+                self.input_mode = InputMode::Normal;
+                return;
+            },
+            Key::Char(digit) if digit.is_ascii_digit() && amount.len() < change.max_digits() => amount.push(digit),
+            Key::Backspace => { amount.pop(); },
+            Key::Char('\n') => {
+                self.input_mode = InputMode::Normal;
+
+                let mut save_dc = None;
+                let changes = match (change, amount.parse::<u32>()) {
+                    (AmountChange::Damage, Ok(amount)) => {
+                        let (changes, dc) = self.rules.take_damage(self.character, amount as u16, None);
+                        save_dc = dc;
+                        Ok(changes)
+                    },
+                    (AmountChange::Heal, Ok(amount)) => Ok(self.character.heal(amount as u16)),
+                    (AmountChange::Experience, Ok(amount)) => self.character.award_experience(amount),
+                    (_, Err(_)) => return
+                };
+                match changes {
+                    Ok(changes) => {
+                        self.log.push(changes.join(", "));
+                        self.mark_changed();
+                    },
+                    Err(message) => self.log.push(message)
+                }
+                if let Some(dc) = save_dc {
+                    self.input_mode = InputMode::ConcentrationSave { dc };
+                }
+                return;
+            },
+            _ => {}
+        }
+
+        self.input_mode = InputMode::AmountPrompt { change, amount };
+    }
+
+    fn handle_condition_picker_key(&mut self, key: Key, selected: usize) {
+        match key {
+            Key::Esc | Key::Char('c') => self.input_mode = InputMode::Normal,
+            Key::Up => self.input_mode = InputMode::ConditionPicker { selected: selected.saturating_sub(1) },
+            Key::Down => self.input_mode = InputMode::ConditionPicker {
+                selected: (selected + 1).min(Condition::ALL.len() - 1)
+            },
+            Key::Char('\n') | Key::Char(' ') => {
+                let condition = Condition::ALL[selected];
+                let message = if self.character.toggle_condition(condition) {
+                    format!("{} now {}", self.character.get_name(), condition.to_string().to_lowercase())
+                } else {
+                    format!("{} no longer {}", self.character.get_name(), condition.to_string().to_lowercase())
+                };
+                self.log.push(message);
+                self.mark_changed();
+            },
+            _ => {}
+        }
+    }
+
+    fn handle_breakdown_key(&mut self, key: Key, selected: usize) {
+        let count = self.character.breakdown_targets().len();
+
+        match key {
+            Key::Esc | Key::Char('w') => self.input_mode = InputMode::Normal,
+            Key::Up => self.input_mode = InputMode::Breakdown { selected: selected.saturating_sub(1) },
+            Key::Down => self.input_mode = InputMode::Breakdown { selected: (selected + 1).min(count.saturating_sub(1)) },
+            _ => {}
+        }
+    }
+
+    fn pass_round(&mut self) {
+        let mut log = vec!["1 round passed".to_owned()];
+        log.extend(self.character.pass_time(1));
+        self.log.push(log.join(", "));
+        self.mark_changed();
+    }
+
+    /// Applies a key press. Returns false when the app should quit.
+    fn handle_key(&mut self, key: Key) -> bool {
+        match &self.input_mode {
+            InputMode::ConditionPicker { selected } => {
+                let selected = *selected;
+                self.handle_condition_picker_key(key, selected);
+                return true;
+            },
+            InputMode::AmountPrompt { change, amount } => {
+                let (change, amount) = (*change, amount.clone());
+                self.handle_amount_prompt_key(key, change, amount);
+                return true;
+            },
+            InputMode::CompendiumSearch => {
+                self.handle_compendium_search_key(key);
+                return true;
+            },
+            InputMode::ConfirmCast { ritual, .. } => {
+                let ritual = *ritual;
+                self.handle_confirm_cast_key(key, ritual);
+                return true;
+            },
+            InputMode::ConcentrationSave { dc } => {
+                let dc = *dc;
+                self.handle_concentration_save_key(key, dc);
+                return true;
+            },
+            InputMode::Breakdown { selected } => {
+                let selected = *selected;
+                self.handle_breakdown_key(key, selected);
+                return true;
+            },
+            InputMode::Normal => {}
+        }
+
+        let confirm_quit = self.confirm_quit;
+        self.confirm_quit = false;
+
+        match key {
+            Key::Char('q') | Key::Esc if self.unsaved_changes && !confirm_quit => {
+                self.log.push("There are unsaved changes: press q again to quit anyway, Ctrl-S to save".to_owned());
+                self.confirm_quit = true;
+            },
+            Key::Char('q') | Key::Esc => return false,
+            Key::Ctrl('s') => self.save(),
+            Key::Char('c') => self.input_mode = InputMode::ConditionPicker { selected: 0 },
+            Key::Char('d') => self.input_mode = InputMode::AmountPrompt { change: AmountChange::Damage, amount: String::new() },
+            Key::Char('h') => self.input_mode = InputMode::AmountPrompt { change: AmountChange::Heal, amount: String::new() },
+            Key::Char('x') => self.input_mode = InputMode::AmountPrompt { change: AmountChange::Experience, amount: String::new() },
+            Key::Char('m') => {
+                let milestone = !self.character.uses_milestones();
+                let changes = self.character.set_milestone(milestone);
+                self.log.push(changes.join(", "));
+                self.mark_changed();
+            },
+            Key::Char('w') => self.input_mode = InputMode::Breakdown { selected: 0 },
+            Key::Char('n') => self.pass_round(),
+            Key::Char('e') => self.change_exhaustion(1),
+            Key::Char('E') => self.change_exhaustion(-1),
+            Key::Char('s') => {
+                let changes = self.character.short_rest();
+                self.log_rest("Short rest", changes);
+            },
+            Key::Char('l') => {
+                let changes = self.character.long_rest();
+                self.log_rest("Long rest", changes);
+            },
+            Key::Right | Key::Char('\t') => {
+                self.selected_tab = (self.selected_tab + 1) % TAB_TITLES.len();
+            },
+            Key::Left => {
+                self.selected_tab = (self.selected_tab + TAB_TITLES.len() - 1) % TAB_TITLES.len();
+            },
+            Key::Up if self.selected_tab == DESCRIPTION_TAB => self.scroll_description(-1),
+            Key::Down if self.selected_tab == DESCRIPTION_TAB => self.scroll_description(1),
+            Key::PageUp if self.selected_tab == DESCRIPTION_TAB => self.scroll_description(-10),
+            Key::PageDown if self.selected_tab == DESCRIPTION_TAB => self.scroll_description(10),
+            Key::Up => self.select_previous_row(),
+            Key::Down => self.select_next_row(),
+            Key::Char('a') if self.selected_tab == EQUIPMENT_TAB => self.toggle_attunement(),
+            Key::Char('u') if self.selected_tab == EQUIPMENT_TAB => self.use_charge(),
+            Key::Char('u') if self.selected_tab == FEATURES_TAB => self.use_feature(),
+            Key::Char('r') if self.selected_tab == FEATURES_TAB => self.restore_feature_use(),
+            Key::Char(digit @ '1'..='9') if self.selected_tab == SPELLS_TAB =>
+                self.use_spell_slot(digit as u8 - b'0'),
+            Key::Char('\n') if self.selected_tab == SPELLS_TAB => self.cast_selected_spell(false, false),
+            Key::Char('r') if self.selected_tab == SPELLS_TAB => self.cast_selected_spell(false, true),
+            Key::Char('p') if self.selected_tab == SPELLS_TAB || self.selected_tab == COMPENDIUM_TAB => self.toggle_prepared(),
+            Key::Char('b') if self.selected_tab == SPELLS_TAB => self.break_concentration(),
+            Key::Char('/') if self.selected_tab == COMPENDIUM_TAB => self.input_mode = InputMode::CompendiumSearch,
+            Key::Char('a') | Key::Char('\n') if self.selected_tab == COMPENDIUM_TAB => self.add_compendium_spell(),
+            Key::Char(filter @ ('C' | 'L' | 'S' | 'R' | 'O')) if self.selected_tab == COMPENDIUM_TAB =>
+                self.change_compendium_filter(filter),
+            _ => {}
+        }
+
+        true
+    }
+    // ####### END INPUT ########
+
+    pub fn draw_app(&mut self) -> Result<(), io::Error> {
 
         let stdout = io::stdout().into_raw_mode()?;
+        let stdout = AlternateScreen::from(stdout);
         let backend = TermionBackend::new(stdout);
         let mut terminal = Terminal::new(backend)?;
+        terminal.hide_cursor()?;
         terminal.clear()?;
 
-        terminal.draw(|mut frame| {
-            self.draw_all_layout(&mut frame);
-        })
+        let mut keys = io::stdin().keys();
+
+        loop {
+            terminal.draw(|mut frame| {
+                self.draw_all_layout(&mut frame);
+            })?;
+
+            match keys.next() {
+                Some(key) => if !self.handle_key(key?) { break; },
+                None => break
+            }
+        }
+
+        terminal.show_cursor()
     }
 }
\ No newline at end of file
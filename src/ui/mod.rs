@@ -1,3 +1,8 @@
 pub use main_app::MainApp;
+pub use picker::pick_character;
+pub use wizard::create_character;
 
-mod main_app;
\ No newline at end of file
+mod main_app;
+mod picker;
+mod portrait;
+mod wizard;
@@ -0,0 +1,88 @@
+use std::io;
+use std::path::PathBuf;
+use termion::event::Key;
+use termion::input::TermRead;
+use termion::raw::IntoRawMode;
+use termion::screen::AlternateScreen;
+use tui::Terminal;
+use tui::backend::TermionBackend;
+use tui::layout::{Constraint, Direction, Layout};
+use tui::widgets::{Widget, Block, Borders, Paragraph, Text, Table, Row};
+use tui::style::{Color, Modifier, Style};
+
+use crate::library::Entry;
+
+/// The screen shown when the app starts without a character: a list of the
+/// library to choose from. Returns the chosen file, or `None` on quit.
+pub fn pick_character(entries: &[Entry], title: &str, problems: &[String]) -> Result<Option<PathBuf>, io::Error> {
+    let stdout = io::stdout().into_raw_mode()?;
+    let stdout = AlternateScreen::from(stdout);
+    let backend = TermionBackend::new(stdout);
+    let mut terminal = Terminal::new(backend)?;
+    terminal.hide_cursor()?;
+    terminal.clear()?;
+
+    let rows: Vec<[String; 5]> = entries.iter()
+        .map(|entry| [
+            entry.name.clone(),
+            entry.race.clone(),
+            entry.class.clone(),
+            entry.level.to_string(),
+            entry.path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default()
+        ])
+        .collect();
+
+    let mut selected = 0;
+    let mut keys = io::stdin().keys();
+
+    let picked = loop {
+        terminal.draw(|mut f| {
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .margin(1)
+                .constraints([Constraint::Min(3), Constraint::Length(4)].as_ref())
+                .split(f.size());
+
+            let row_style = Style::default().fg(Color::White);
+            let selected_style = Style::default().fg(Color::Yellow).modifier(Modifier::BOLD);
+
+            Table::new(
+                ["Name", "Race", "Class", "Lvl", "File"].iter(),
+                rows.iter().enumerate().map(|(index, row)| {
+                    let style = if index == selected { selected_style } else { row_style };
+                    Row::StyledData(row.iter(), style)
+                })
+            )
+                .block(Block::default().title(title).borders(Borders::ALL))
+                .header_style(Style::default().fg(Color::Yellow))
+                .widths(&[25, 20, 15, 4, 30])
+                .column_spacing(2)
+                .render(&mut f, chunks[0]);
+
+            let mut help = vec![Text::raw("Up/Down to choose, Enter to open, q to quit\n")];
+            if !problems.is_empty() {
+                help.push(Text::styled(format!("{} file(s) could not be read: {}", problems.len(), problems.join("; ")),
+                    Style::default().fg(Color::Red)));
+            }
+
+            Paragraph::new(help.iter())
+                .block(Block::default().borders(Borders::ALL))
+                .wrap(true)
+                .render(&mut f, chunks[1]);
+        })?;
+
+        match keys.next() {
+            Some(key) => match key? {
+                Key::Char('q') | Key::Esc => break None,
+                Key::Up => selected = selected.saturating_sub(1),
+                Key::Down => selected = (selected + 1).min(rows.len().saturating_sub(1)),
+                Key::Char('\n') => break entries.get(selected).map(|entry| entry.path.clone()),
+                _ => {}
+            },
+            None => break None
+        }
+    };
+
+    terminal.show_cursor()?;
+    Ok(picked)
+}
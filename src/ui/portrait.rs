@@ -0,0 +1,199 @@
+use std::cell::RefCell;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use image::imageops::{self, FilterType};
+use image::RgbaImage;
+use tui::buffer::Buffer;
+use tui::layout::Rect;
+use tui::style::Color;
+use tui::widgets::Widget;
+
+/// Pixels more transparent than this are left blank instead of drawn.
+const ALPHA_THRESHOLD: u8 = 128;
+
+/// A character portrait, either a picture or hand-drawn ASCII art. Text files
+/// (`.txt`, `.ascii`) are used as ASCII art, anything else is decoded as an image.
+/// Images keep their last scaled copy, so a redraw at the same size doesn't
+/// resize again.
+pub enum Portrait {
+    Image(RgbaImage, RefCell<Option<RgbaImage>>),
+    Ascii(Vec<String>)
+}
+
+impl Portrait {
+
+    pub fn load(path: &Path) -> Result<Portrait, io::Error> {
+        let extension = path.extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| extension.to_lowercase());
+
+        match extension.as_deref() {
+            Some("txt") | Some("ascii") => {
+                let art = fs::read_to_string(path)?;
+                Ok(Portrait::Ascii(art.lines().map(|line| line.trim_end().to_owned()).collect()))
+            },
+            _ => {
+                let image = image::open(path)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", path.display(), e)))?;
+                Ok(Portrait::Image(image.to_rgba8(), RefCell::new(None)))
+            }
+        }
+    }
+}
+
+/// How many colors the terminal can show, guessed from `COLORTERM`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ColorMode {
+    TrueColor,
+    Indexed
+}
+
+impl ColorMode {
+
+    pub fn detect() -> ColorMode {
+        match env::var("COLORTERM") {
+            Ok(ref value) if value == "truecolor" || value == "24bit" => ColorMode::TrueColor,
+            _ => ColorMode::Indexed
+        }
+    }
+
+    fn color(self, r: u8, g: u8, b: u8) -> Color {
+        match self {
+            ColorMode::TrueColor => Color::Rgb(r, g, b),
+            ColorMode::Indexed => Color::Indexed(to_xterm_256(r, g, b))
+        }
+    }
+}
+
+/// Maps a color to the closest entry of the xterm 256-color palette, using the
+/// grayscale ramp for near-gray colors and the 6x6x6 cube otherwise.
+fn to_xterm_256(r: u8, g: u8, b: u8) -> u8 {
+    let (max, min) = (r.max(g).max(b), r.min(g).min(b));
+
+    if max - min < 10 {
+        let gray = (r as u16 + g as u16 + b as u16) / 3;
+        return match gray {
+            0..=7 => 16,
+            239..=255 => 231,
+            _ => 232 + ((gray - 8) / 10) as u8
+        };
+    }
+
+    let to_cube = |channel: u8| (channel as u16 * 5 + 127) / 255;
+    (16 + 36 * to_cube(r) + 6 * to_cube(g) + to_cube(b)) as u8
+}
+
+/// Draws a portrait scaled to fit its area. Images use half blocks, so every
+/// cell shows two vertically stacked pixels.
+pub struct PortraitWidget<'a> {
+    portrait: &'a Portrait,
+    color_mode: ColorMode
+}
+
+impl<'a> PortraitWidget<'a> {
+
+    pub fn new(portrait: &'a Portrait, color_mode: ColorMode) -> PortraitWidget<'a> {
+        PortraitWidget { portrait, color_mode }
+    }
+
+    fn draw_image(&self, image: &RgbaImage, cache: &RefCell<Option<RgbaImage>>, area: Rect, buf: &mut Buffer) {
+        let (max_width, max_height) = (area.width as u32, area.height as u32 * 2);
+        if max_width == 0 || max_height == 0 || image.width() == 0 || image.height() == 0 {
+            return;
+        }
+
+        let scale = f64::min(
+            max_width as f64 / image.width() as f64,
+            max_height as f64 / image.height() as f64);
+        let width = ((image.width() as f64 * scale) as u32).clamp(1, max_width);
+        let height = ((image.height() as f64 * scale) as u32).clamp(1, max_height);
+
+        let mut cache = cache.borrow_mut();
+        if cache.as_ref().map(RgbaImage::dimensions) != Some((width, height)) {
+            *cache = Some(imageops::resize(image, width, height, FilterType::Triangle));
+        }
+        let scaled = cache.as_ref().unwrap();
+
+        let left = area.left() + ((max_width - width) / 2) as u16;
+        let top = area.top() + ((max_height - height) / 4) as u16;
+
+        let pixel = |x: u32, y: u32| {
+            if y >= height { return None; }
+            let rgba = scaled.get_pixel(x, y).0;
+            if rgba[3] < ALPHA_THRESHOLD { None } else { Some(self.color_mode.color(rgba[0], rgba[1], rgba[2])) }
+        };
+
+        for y in (0..height).step_by(2) {
+            for x in 0..width {
+                let cell = buf.get_mut(left + x as u16, top + (y / 2) as u16);
+
+                match (pixel(x, y), pixel(x, y + 1)) {
+                    (Some(upper), Some(lower)) => { cell.set_char('▀').set_fg(upper).set_bg(lower); },
+                    (Some(upper), None) => { cell.set_char('▀').set_fg(upper); },
+                    (None, Some(lower)) => { cell.set_char('▄').set_fg(lower); },
+                    (None, None) => {}
+                }
+            }
+        }
+    }
+
+    fn draw_ascii(&self, art: &[String], area: Rect, buf: &mut Buffer) {
+        let art_width = art.iter().map(|line| line.chars().count()).max().unwrap_or(0);
+        let art_height = art.len();
+        if art_width == 0 || area.width == 0 || area.height == 0 {
+            return;
+        }
+
+        // Bigger art is sampled down to the box, smaller art is centered as is.
+        let width = art_width.min(area.width as usize);
+        let height = art_height.min(area.height as usize);
+        let left = area.left() + ((area.width as usize - width) / 2) as u16;
+        let top = area.top() + ((area.height as usize - height) / 2) as u16;
+
+        for y in 0..height {
+            let line: Vec<char> = art[y * art_height / height].chars().collect();
+
+            for x in 0..width {
+                if let Some(ch) = line.get(x * art_width / width) {
+                    buf.get_mut(left + x as u16, top + y as u16).set_char(*ch);
+                }
+            }
+        }
+    }
+}
+
+impl Widget for PortraitWidget<'_> {
+    fn draw(&mut self, area: Rect, buf: &mut Buffer) {
+        match self.portrait {
+            Portrait::Image(image, cache) => self.draw_image(image, cache, area, buf),
+            Portrait::Ascii(art) => self.draw_ascii(art, area, buf)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    #[test]
+    fn scaled_image_is_kept_until_the_size_changes() {
+        let portrait = Portrait::Image(RgbaImage::from_pixel(40, 40, Rgba([255, 0, 0, 255])), RefCell::new(None));
+        let Portrait::Image(_, cache) = &portrait else { unreachable!() };
+        let draw = |area: Rect| PortraitWidget::new(&portrait, ColorMode::TrueColor).draw(area, &mut Buffer::empty(area));
+
+        draw(Rect::new(0, 0, 10, 5));
+        assert_eq!(cache.borrow().as_ref().map(RgbaImage::dimensions), Some((10, 10)));
+
+        // Same size: the cached copy is drawn, not a new one.
+        cache.borrow_mut().as_mut().unwrap().put_pixel(0, 0, Rgba([0, 255, 0, 255]));
+        draw(Rect::new(0, 0, 10, 5));
+        assert_eq!(cache.borrow().as_ref().unwrap().get_pixel(0, 0).0, [0, 255, 0, 255]);
+
+        draw(Rect::new(0, 0, 20, 10));
+        assert_eq!(cache.borrow().as_ref().map(RgbaImage::dimensions), Some((20, 20)));
+    }
+}
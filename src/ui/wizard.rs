@@ -0,0 +1,760 @@
+use std::io;
+use termion::event::Key;
+use termion::input::TermRead;
+use termion::raw::IntoRawMode;
+use termion::screen::AlternateScreen;
+use tui::{Frame, Terminal};
+use tui::backend::{Backend, TermionBackend};
+use tui::layout::{Constraint, Direction, Layout, Rect};
+use tui::widgets::{Widget, Block, Borders, Paragraph, Text, Table, Row};
+use tui::style::{Color, Modifier, Style};
+
+use crate::character::{modifier, AbilityRoll, Character, Stat};
+use crate::rules::{point_buy_cost, point_buy_total, Choices, Entry, Generation, Rules, Spell};
+use crate::rules::{POINT_BUY_BUDGET, POINT_BUY_MAX, POINT_BUY_MIN, STANDARD_ARRAY};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Step {
+    Name,
+    Race,
+    Subrace,
+    Class,
+    Background,
+    Abilities,
+    Skills,
+    Equipment,
+    Spells,
+    Review
+}
+
+const STEPS: [Step; 10] = [
+    Step::Name, Step::Race, Step::Subrace, Step::Class, Step::Background,
+    Step::Abilities, Step::Skills, Step::Equipment, Step::Spells, Step::Review
+];
+
+impl Step {
+    fn title(self) -> &'static str {
+        match self {
+            Step::Name => "Name",
+            Step::Race => "Race",
+            Step::Subrace => "Subrace",
+            Step::Class => "Class",
+            Step::Background => "Background",
+            Step::Abilities => "Ability scores",
+            Step::Skills => "Skills",
+            Step::Equipment => "Equipment",
+            Step::Spells => "Spells",
+            Step::Review => "Review"
+        }
+    }
+}
+
+/// What a key press did to the wizard.
+enum Outcome {
+    Continue,
+    Cancel,
+    Done
+}
+
+struct Wizard<'a> {
+    rules: &'a Rules,
+    step: usize,
+    cursor: usize,
+    name: String,
+    race: usize,
+    subrace: usize,
+    class: usize,
+    background: usize,
+    equipment_pack: usize,
+    generation: Generation,
+    /// Base scores in the order of `Stat::ALL`.
+    scores: [i16; 6],
+    /// Every 4d6 roll so far; the last six are the current scores.
+    rolls: Vec<AbilityRoll>,
+    skills: Vec<String>,
+    spells: Vec<String>,
+    message: String
+}
+
+impl<'a> Wizard<'a> {
+
+    fn new(rules: &'a Rules) -> Wizard<'a> {
+        Wizard {
+            rules,
+            step: 0,
+            cursor: 0,
+            name: String::new(),
+            race: 0,
+            subrace: 0,
+            class: 0,
+            background: 0,
+            equipment_pack: 0,
+            generation: Generation::StandardArray,
+            scores: STANDARD_ARRAY,
+            rolls: Vec::new(),
+            skills: Vec::new(),
+            spells: Vec::new(),
+            message: String::new()
+        }
+    }
+
+    fn choices(&self) -> Choices<'a> {
+        let race = &self.rules.races[self.race];
+        let class = &self.rules.classes[self.class];
+
+        Choices {
+            name: self.name.clone(),
+            race,
+            subrace: race.subraces.get(self.subrace),
+            class,
+            background: &self.rules.backgrounds[self.background],
+            equipment_pack: class.equipment_packs.get(self.equipment_pack)
+                .and_then(|name| self.rules.find_equipment_pack(name)),
+            base_scores: Stat::ALL.iter().copied().zip(self.scores.iter().copied()).collect(),
+            ability_rolls: if self.generation == Generation::Rolled { self.rolls.clone() } else { Vec::new() },
+            skills: self.skills.clone(),
+            spells: self.spells.clone()
+        }
+    }
+
+    fn current_step(&self) -> Step {
+        STEPS[self.step]
+    }
+
+    /// Steps that do not apply to the choices so far are skipped, e.g.
+    /// subraces for humans or spells for fighters.
+    fn applies(&self, step: Step) -> bool {
+        let class = &self.rules.classes[self.class];
+
+        match step {
+            Step::Subrace => !self.rules.races[self.race].subraces.is_empty(),
+            Step::Equipment => !class.equipment_packs.is_empty(),
+            Step::Spells => !self.spell_options().is_empty(),
+            _ => true
+        }
+    }
+
+    fn go_to(&mut self, step: usize) {
+        self.step = step;
+        self.message.clear();
+        self.cursor = match self.current_step() {
+            Step::Race => self.race,
+            Step::Subrace => self.subrace,
+            Step::Class => self.class,
+            Step::Background => self.background,
+            Step::Equipment => self.equipment_pack,
+            _ => 0
+        };
+
+        // Skills and spells may no longer be on offer after going back and
+        // changing the race, background or class.
+        let skill_options = self.skill_options();
+        self.skills.retain(|skill| skill_options.contains(skill));
+        let spell_options: Vec<String> = self.spell_options().iter().map(|spell| spell.id()).collect();
+        self.spells.retain(|spell| spell_options.contains(spell));
+    }
+
+    fn next_step(&mut self) -> Outcome {
+        match (self.step + 1..STEPS.len()).find(|step| self.applies(STEPS[*step])) {
+            Some(step) => {
+                self.go_to(step);
+                Outcome::Continue
+            },
+            None => Outcome::Done
+        }
+    }
+
+    fn previous_step(&mut self) -> Outcome {
+        match (0..self.step).rev().find(|step| self.applies(STEPS[*step])) {
+            Some(step) => {
+                self.go_to(step);
+                Outcome::Continue
+            },
+            None => Outcome::Cancel
+        }
+    }
+
+    /// The class skills that the race and background do not already give.
+    fn skill_options(&self) -> Vec<String> {
+        let choices = self.choices();
+        let granted = choices.granted_skills();
+
+        choices.class.skills.iter()
+            .filter(|skill| !granted.iter().any(|granted| granted.eq_ignore_ascii_case(skill)))
+            .cloned()
+            .collect()
+    }
+
+    fn skill_limit(&self) -> usize {
+        self.rules.classes[self.class].skill_choices.min(self.skill_options().len())
+    }
+
+    /// Cantrips and 1st level spells a level 1 character of the class can pick.
+    fn spell_options(&self) -> Vec<&'a Spell> {
+        let class = &self.rules.classes[self.class];
+
+        match &class.spellcasting {
+            Some(spellcasting) if spellcasting.first_level <= 1 => {
+                let max_level = if spellcasting.spells > 0 { 1 } else { 0 };
+                self.rules.class_spells(&class.name, max_level).into_iter()
+                    .filter(|spell| spell.level > 0 || spellcasting.cantrips > 0)
+                    .collect()
+            },
+            _ => Vec::new()
+        }
+    }
+
+    /// How many cantrips (level 0) or 1st level spells the class picks.
+    fn spell_limit(&self, level: u8) -> usize {
+        let spellcasting = self.rules.classes[self.class].spellcasting.as_ref();
+        let limit = spellcasting.map(|spellcasting| if level == 0 { spellcasting.cantrips } else { spellcasting.spells }).unwrap_or(0);
+        let available = self.spell_options().iter().filter(|spell| spell.level == level).count();
+
+        limit.min(available)
+    }
+
+    fn chosen_spells_of_level(&self, level: u8) -> usize {
+        self.spells.iter()
+            .filter(|name| self.rules.find_spell(name).map(|spell| spell.level) == Some(level))
+            .count()
+    }
+
+    fn option_count(&self) -> usize {
+        match self.current_step() {
+            Step::Name | Step::Review => 0,
+            Step::Race => self.rules.races.len(),
+            Step::Subrace => self.rules.races[self.race].subraces.len(),
+            Step::Class => self.rules.classes.len(),
+            Step::Background => self.rules.backgrounds.len(),
+            Step::Abilities => Stat::ALL.len(),
+            Step::Skills => self.skill_options().len(),
+            Step::Equipment => self.rules.classes[self.class].equipment_packs.len(),
+            Step::Spells => self.spell_options().len()
+        }
+    }
+
+    /// Takes the option under the cursor. Returns false when the step is not complete.
+    fn confirm(&mut self) -> bool {
+        let cursor = self.cursor;
+
+        match self.current_step() {
+            Step::Name if self.name.trim().is_empty() => {
+                self.message = "Type a name first".to_owned();
+                return false;
+            },
+            Step::Race if cursor != self.race => {
+                self.race = cursor;
+                self.subrace = 0;
+            },
+            Step::Subrace => self.subrace = cursor,
+            Step::Class if cursor != self.class => {
+                self.class = cursor;
+                self.equipment_pack = 0;
+                self.skills.clear();
+                self.spells.clear();
+            },
+            Step::Background => self.background = cursor,
+            Step::Equipment => self.equipment_pack = cursor,
+            Step::Skills if self.skills.len() < self.skill_limit() => {
+                self.message = format!("Choose {} skills, {} so far", self.skill_limit(), self.skills.len());
+                return false;
+            },
+            Step::Spells => {
+                for (level, kind) in [(0, "cantrips"), (1, "1st level spells")].iter() {
+                    if self.chosen_spells_of_level(*level) < self.spell_limit(*level) {
+                        self.message = format!("Choose {} {}, {} so far",
+                            self.spell_limit(*level), kind, self.chosen_spells_of_level(*level));
+                        return false;
+                    }
+                }
+            },
+            _ => {}
+        }
+
+        true
+    }
+
+    fn toggle(&mut self) {
+        match self.current_step() {
+            Step::Skills => {
+                let skill = match self.skill_options().get(self.cursor) {
+                    Some(skill) => skill.clone(),
+                    None => return
+                };
+
+                if self.skills.contains(&skill) {
+                    self.skills.retain(|chosen| *chosen != skill);
+                } else if self.skills.len() < self.skill_limit() {
+                    self.skills.push(skill);
+                } else {
+                    self.message = format!("Only {} skills can be chosen; unselect one first", self.skill_limit());
+                }
+            },
+            Step::Spells => {
+                let spell = match self.spell_options().get(self.cursor) {
+                    Some(spell) => *spell,
+                    None => return
+                };
+
+                if self.spells.contains(&spell.id()) {
+                    self.spells.retain(|chosen| *chosen != spell.id());
+                } else if self.chosen_spells_of_level(spell.level) < self.spell_limit(spell.level) {
+                    self.spells.push(spell.id());
+                } else {
+                    self.message = format!("Only {} {} can be chosen; unselect one first",
+                        self.spell_limit(spell.level), if spell.level == 0 { "cantrips" } else { "1st level spells" });
+                }
+            },
+            _ => {}
+        }
+    }
+
+    /// Switches to another way of generating scores, starting it over,
+    /// except that earlier rolls are kept.
+    fn set_generation(&mut self, generation: Generation) {
+        self.generation = generation;
+
+        match generation {
+            Generation::StandardArray => self.scores = STANDARD_ARRAY,
+            Generation::PointBuy => self.scores = [POINT_BUY_MIN; 6],
+            Generation::Rolled if self.rolls.len() >= self.scores.len() => {
+                let last = &self.rolls[self.rolls.len() - self.scores.len()..];
+                for (score, roll) in self.scores.iter_mut().zip(last) {
+                    *score = roll.score;
+                }
+            },
+            Generation::Rolled => self.roll_scores()
+        }
+    }
+
+    /// Rolls a new set of six scores, assigned in the order they were rolled.
+    fn roll_scores(&mut self) {
+        for score in self.scores.iter_mut() {
+            let roll = AbilityRoll::roll();
+            *score = roll.score;
+            self.rolls.push(roll);
+        }
+    }
+
+    fn change_score(&mut self, higher: bool) {
+        match self.generation {
+            Generation::PointBuy => self.buy_score(higher),
+            Generation::StandardArray | Generation::Rolled => self.swap_score(higher)
+        }
+    }
+
+    /// Point buy: raises or lowers the selected score by one, within 8 to 15
+    /// and the budget.
+    fn buy_score(&mut self, higher: bool) {
+        let score = self.scores[self.cursor] + if higher { 1 } else { -1 };
+        if !(POINT_BUY_MIN..=POINT_BUY_MAX).contains(&score) {
+            self.message = format!("Point buy scores go from {} to {}", POINT_BUY_MIN, POINT_BUY_MAX);
+            return;
+        }
+
+        let mut scores = self.scores;
+        scores[self.cursor] = score;
+        match point_buy_total(&scores) {
+            Some(total) if total <= POINT_BUY_BUDGET => self.scores = scores,
+            _ => self.message = format!("Not enough points left for {} ({} points)", score,
+                point_buy_cost(score).unwrap_or(0))
+        }
+    }
+
+    /// Swaps the score of the selected ability with the next higher or lower
+    /// score of the set, so every value stays used exactly once.
+    fn swap_score(&mut self, higher: bool) {
+        let mut values = self.scores.to_vec();
+        values.sort_unstable_by(|a, b| b.cmp(a));
+        values.dedup();
+
+        let current = self.scores[self.cursor];
+        let position = values.iter().position(|score| *score == current).unwrap_or(0);
+        let target = if higher { position.checked_sub(1) } else { Some(position + 1) };
+
+        let target = match target.and_then(|target| values.get(target)) {
+            Some(target) => *target,
+            None => return
+        };
+
+        if let Some(other) = self.scores.iter().position(|score| *score == target) {
+            self.scores.swap(self.cursor, other);
+        }
+    }
+
+    fn handle_key(&mut self, key: Key) -> Outcome {
+        self.message.clear();
+        let step = self.current_step();
+
+        match key {
+            Key::Ctrl('c') => return Outcome::Cancel,
+            Key::Esc => return self.previous_step(),
+            Key::Char('\n') if self.confirm() => return self.next_step(),
+            Key::Char(c) if step == Step::Name && !c.is_control() && self.name.len() < 40 => self.name.push(c),
+            Key::Backspace if step == Step::Name => { self.name.pop(); },
+            Key::Up => self.cursor = self.cursor.saturating_sub(1),
+            Key::Down => self.cursor = (self.cursor + 1).min(self.option_count().saturating_sub(1)),
+            Key::Left if step == Step::Abilities => self.change_score(false),
+            Key::Right if step == Step::Abilities => self.change_score(true),
+            Key::Char('m') if step == Step::Abilities => self.set_generation(self.generation.next()),
+            Key::Char('r') if step == Step::Abilities && self.generation == Generation::Rolled => self.roll_scores(),
+            Key::Char(' ') => self.toggle(),
+            _ => {}
+        }
+
+        Outcome::Continue
+    }
+
+    // ####### DRAWING ########
+    fn draw<B>(&self, f: &mut Frame<B>)
+        where
+            B: Backend
+    {
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .margin(1)
+            .constraints([Constraint::Min(10), Constraint::Length(4)].as_ref())
+            .split(f.size());
+
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Length(22), Constraint::Min(30), Constraint::Percentage(40)].as_ref())
+            .split(rows[0]);
+
+        self.draw_steps(f, columns[0]);
+        self.draw_options(f, columns[1]);
+        self.draw_details(f, columns[2]);
+        self.draw_help(f, rows[1]);
+    }
+
+    fn draw_steps<B>(&self, f: &mut Frame<B>, area: Rect)
+        where
+            B: Backend
+    {
+        let text: Vec<Text> = STEPS.iter().enumerate()
+            .filter(|(_, step)| self.applies(**step))
+            .map(|(index, step)| {
+                let style = if index == self.step {
+                    Style::default().fg(Color::Yellow).modifier(Modifier::BOLD)
+                } else if index < self.step {
+                    Style::default().fg(Color::Green)
+                } else {
+                    Style::default().fg(Color::White)
+                };
+                let marker = if index < self.step { "✓" } else { " " };
+                Text::styled(format!("{} {}\n", marker, step.title()), style)
+            })
+            .collect();
+
+        Paragraph::new(text.iter())
+            .block(Block::default().title("New character").borders(Borders::ALL))
+            .render(f, area);
+    }
+
+    /// The rows of a list step, with a check box for steps that pick several.
+    fn option_rows(&self) -> Vec<Vec<String>> {
+        let rules = self.rules;
+        let choices = self.choices();
+
+        match self.current_step() {
+            Step::Race => rules.races.iter().map(|race| vec![race.name.clone()]).collect(),
+            Step::Subrace => choices.race.subraces.iter().map(|subrace| vec![subrace.name.clone()]).collect(),
+            Step::Class => rules.classes.iter()
+                .map(|class| vec![class.name.clone(), format!("d{}", class.hit_die)])
+                .collect(),
+            Step::Background => rules.backgrounds.iter().map(|background| vec![background.name.clone()]).collect(),
+            Step::Equipment => choices.class.equipment_packs.iter().map(|pack| vec![pack.clone()]).collect(),
+            Step::Abilities => Stat::ALL.iter().zip(self.scores.iter())
+                .map(|(stat, score)| {
+                    let bonus = choices.racial_bonus(*stat);
+                    let total = score + bonus;
+                    let mut row = vec![
+                        stat.abbreviation().to_owned(),
+                        score.to_string(),
+                        if bonus == 0 { String::new() } else { format!("{:+}", bonus) },
+                        format!("{} ({:+})", total, modifier(total))
+                    ];
+                    if self.generation == Generation::PointBuy {
+                        row.push(point_buy_cost(*score).map(|cost| cost.to_string()).unwrap_or_default());
+                    }
+                    row
+                })
+                .collect(),
+            Step::Skills => self.skill_options().iter()
+                .map(|skill| vec![check_box(self.skills.contains(skill)), skill.clone()])
+                .collect(),
+            Step::Spells => self.spell_options().iter()
+                .map(|spell| vec![check_box(self.spells.contains(&spell.id())), spell.level_text(), spell.name.clone()])
+                .collect(),
+            Step::Name | Step::Review => Vec::new()
+        }
+    }
+
+    fn draw_options<B>(&self, f: &mut Frame<B>, area: Rect)
+        where
+            B: Backend
+    {
+        let step = self.current_step();
+        let title = if step == Step::Abilities {
+            format!("{}: {} (m to change)", step.title(), self.generation)
+        } else {
+            step.title().to_owned()
+        };
+        let block = Block::default().title(&title).borders(Borders::ALL);
+
+        if step == Step::Name || step == Step::Review {
+            let text = if step == Step::Name {
+                vec![Text::raw("What is your character called?\n\n"), Text::styled(format!("{}_", self.name),
+                    Style::default().fg(Color::Yellow).modifier(Modifier::BOLD))]
+            } else {
+                vec![Text::raw("Press Enter to create the character, Esc to go back and change something.")]
+            };
+
+            Paragraph::new(text.iter()).block(block).wrap(true).render(f, area);
+            return;
+        }
+
+        let (header, widths): (&[&str], &[u16]) = match step {
+            Step::Class => (&["Class", "Hit die"], &[20, 7]),
+            Step::Abilities if self.generation == Generation::PointBuy =>
+                (&["Ability", "Base", "Race", "Score", "Cost"], &[7, 4, 4, 8, 4]),
+            Step::Abilities => (&["Ability", "Base", "Race", "Score"], &[7, 4, 4, 8]),
+            Step::Skills => (&["", "Skill"], &[3, 20]),
+            Step::Spells => (&["", "Lvl", "Spell"], &[3, 3, 22]),
+            _ => (&[""], &[30])
+        };
+
+        let rows = self.option_rows();
+        let row_style = Style::default().fg(Color::White);
+        let selected_style = Style::default().fg(Color::Yellow).modifier(Modifier::BOLD);
+
+        // tui 0.5 does not scroll tables, so the list starts late enough to show the cursor.
+        let visible = area.height.saturating_sub(4) as usize;
+        let offset = (self.cursor + 1).saturating_sub(visible);
+
+        Table::new(
+            header.iter(),
+            rows.iter().enumerate().skip(offset).map(|(index, row)| {
+                let style = if index == self.cursor { selected_style } else { row_style };
+                Row::StyledData(row.iter(), style)
+            })
+        )
+            .block(block)
+            .header_style(Style::default().fg(Color::Yellow))
+            .widths(widths)
+            .column_spacing(2)
+            .render(f, area);
+    }
+
+    /// Explains the option under the cursor, or the whole character on review.
+    fn details(&self) -> Vec<String> {
+        let choices = self.choices();
+        let cursor = self.cursor;
+
+        let features = |features: &[crate::rules::FeatureTemplate]| -> String {
+            features.iter().map(|feature| feature.name.as_str()).collect::<Vec<_>>().join(", ")
+        };
+        let bonuses = |bonuses: &std::collections::HashMap<Stat, i16>| -> String {
+            Stat::ALL.iter()
+                .filter_map(|stat| bonuses.get(stat).map(|bonus| format!("{} {:+}", stat.abbreviation(), bonus)))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        match self.current_step() {
+            Step::Name => vec!["Esc on this step cancels, Ctrl-C cancels anywhere.".to_owned()],
+            Step::Race => self.rules.races.get(cursor).map(|race| vec![
+                format!("Abilities: {}", bonuses(&race.ability_bonuses)),
+                format!("Speed: {} ft", race.speed),
+                format!("Skills: {}", if race.skills.is_empty() { "-".to_owned() } else { race.skills.join(", ") }),
+                format!("Traits: {}", features(&race.features)),
+            ]).unwrap_or_default(),
+            Step::Subrace => choices.race.subraces.get(cursor).map(|subrace| vec![
+                format!("Abilities: {}", bonuses(&subrace.ability_bonuses)),
+                format!("Traits: {}", features(&subrace.features)),
+            ]).unwrap_or_default(),
+            Step::Class => self.rules.classes.get(cursor).map(|class| vec![
+                format!("Hit die: d{}", class.hit_die),
+                format!("Saving throws: {}", class.saving_throws.iter().map(|stat| stat.abbreviation()).collect::<Vec<_>>().join(", ")),
+                format!("Skills: choose {} from {}", class.skill_choices, class.skills.join(", ")),
+                format!("Spellcasting: {}", class.spellcasting.as_ref()
+                    .map(|spellcasting| format!("{:?}, from level {}", spellcasting.ability, spellcasting.first_level))
+                    .unwrap_or_else(|| "-".to_owned())),
+                format!("Features: {}", features(&class.features)),
+            ]).unwrap_or_default(),
+            Step::Background => self.rules.backgrounds.get(cursor).map(|background| vec![
+                format!("Skills: {}", background.skills.join(", ")),
+                format!("Feature: {}", features(&background.features)),
+            ]).unwrap_or_default(),
+            Step::Abilities => self.ability_details(),
+            Step::Skills => vec![
+                format!("Choose {} ({} chosen).", self.skill_limit(), self.skills.len()),
+                format!("Already proficient: {}", choices.granted_skills().join(", ")),
+            ],
+            Step::Equipment => choices.class.equipment_packs.get(cursor)
+                .and_then(|name| self.rules.find_equipment_pack(name))
+                .map(|pack| pack.items.iter()
+                    .map(|item| if item.quantity > 1 { format!("{} x{}", item.name, item.quantity) } else { item.name.clone() })
+                    .collect())
+                .unwrap_or_default(),
+            Step::Spells => {
+                let mut details = vec![format!("Cantrips {}/{}, 1st level {}/{}",
+                    self.chosen_spells_of_level(0), self.spell_limit(0),
+                    self.chosen_spells_of_level(1), self.spell_limit(1))];
+                if let Some(spell) = self.spell_options().get(cursor) {
+                    details.push(String::new());
+                    details.push(format!("{} ({})", spell.name, spell.school));
+                    details.push(format!("{} | {} | {}", spell.casting_time, spell.range, spell.duration));
+                    details.push(spell.description.clone());
+                }
+                details
+            },
+            Step::Review => review(&choices.build(), self.rules)
+        }
+    }
+
+    fn ability_details(&self) -> Vec<String> {
+        match self.generation {
+            Generation::StandardArray => vec![
+                "Assign the standard array 15, 14, 13, 12, 10 and 8.".to_owned(),
+                "Left/Right swaps the selected score with the next lower or higher one.".to_owned(),
+            ],
+            Generation::PointBuy => {
+                let spent = point_buy_total(&self.scores).unwrap_or(0);
+                vec![
+                    format!("Points spent: {} of {}, {} left", spent, POINT_BUY_BUDGET, POINT_BUY_BUDGET - spent),
+                    String::new(),
+                    "Every score starts at 8. Left/Right lowers or raises the selected score.".to_owned(),
+                    "Costs: 8 = 0, 9 = 1, 10 = 2, 11 = 3, 12 = 4, 13 = 5, 14 = 7, 15 = 9".to_owned(),
+                ]
+            },
+            Generation::Rolled => {
+                let mut lines = vec![
+                    "Six scores of 4d6, dropping the lowest die. r rolls a new set,".to_owned(),
+                    "Left/Right swaps the selected score with the next lower or higher one.".to_owned(),
+                    String::new(),
+                    "Roll history:".to_owned(),
+                ];
+                for (set, rolls) in self.rolls.chunks(self.scores.len()).enumerate() {
+                    let scores: Vec<String> = rolls.iter().map(roll_text).collect();
+                    lines.push(format!("Set {}: {}", set + 1, scores.join(", ")));
+                }
+                lines
+            }
+        }
+    }
+
+    fn draw_details<B>(&self, f: &mut Frame<B>, area: Rect)
+        where
+            B: Backend
+    {
+        let text: Vec<Text> = self.details().into_iter()
+            .map(|line| Text::raw(line + "\n"))
+            .collect();
+
+        Paragraph::new(text.iter())
+            .block(Block::default().title("Details").borders(Borders::ALL))
+            .wrap(true)
+            .render(f, area);
+    }
+
+    fn draw_help<B>(&self, f: &mut Frame<B>, area: Rect)
+        where
+            B: Backend
+    {
+        let help = match self.current_step() {
+            Step::Name => "Type a name, Enter to continue, Esc to cancel",
+            Step::Abilities => "Up/Down to choose an ability, Left/Right to change its score, m to change the method, \
+                r to reroll, Enter to continue, Esc to go back",
+            Step::Skills | Step::Spells => "Up/Down to move, Space to select, Enter to continue, Esc to go back",
+            Step::Review => "Enter to create, Esc to go back",
+            _ => "Up/Down to choose, Enter to continue, Esc to go back"
+        };
+
+        let text = [
+            Text::raw(format!("{}\n", help)),
+            Text::styled(self.message.clone(), Style::default().fg(Color::Red)),
+        ];
+
+        Paragraph::new(text.iter())
+            .block(Block::default().borders(Borders::ALL))
+            .wrap(true)
+            .render(f, area);
+    }
+    // ####### END DRAWING ########
+}
+
+/// "14 (6 5 3 2)": the score and every die, the last one dropped.
+fn roll_text(roll: &AbilityRoll) -> String {
+    let dice: Vec<String> = roll.dice.iter().map(ToString::to_string).collect();
+    format!("{} ({})", roll.score, dice.join(" "))
+}
+
+fn check_box(checked: bool) -> String {
+    if checked { "[x]".to_owned() } else { "[ ]".to_owned() }
+}
+
+/// The derived values of the finished character.
+fn review(character: &Character, rules: &Rules) -> Vec<String> {
+    let scores: Vec<String> = Stat::ALL.iter()
+        .map(|stat| format!("{} {}", stat.abbreviation(), character.get_stat(stat).unwrap_or(10)))
+        .collect();
+    let skills: Vec<&str> = character.get_skills().iter()
+        .filter(|skill| skill.has_proficiency)
+        .map(|skill| skill.name.as_str())
+        .collect();
+
+    let mut lines = vec![
+        format!("{}, {} {}, {}", character.get_name(), character.get_race(), character.get_class(),
+            character.get_description().background),
+        scores.join("  "),
+        format!("Hit points {} | AC {} | Initiative {:+} | Speed {} ft", character.get_max_hitpoints(),
+            character.get_armor_class(), character.get_initiative(), character.get_walking_speed_in_ft()),
+        format!("Skills: {}", skills.join(", ")),
+        format!("Features: {}", character.get_features().iter().map(|feature| feature.name.as_str()).collect::<Vec<_>>().join(", ")),
+        format!("Equipment: {} items", character.get_inventory().len()),
+    ];
+
+    if !character.get_spells().is_empty() {
+        let spells: Vec<&str> = character.get_spells().iter()
+            .map(|reference| rules.find_spell(reference).map(|spell| spell.name.as_str()).unwrap_or(reference))
+            .collect();
+        lines.push(format!("Spells: {}", spells.join(", ")));
+    }
+
+    if !character.get_ability_rolls().is_empty() {
+        lines.push(format!("Ability rolls kept in the file: {}", character.get_ability_rolls().len()));
+    }
+
+    lines
+}
+
+/// Walks through race, class, background, ability scores, skills,
+/// equipment and spells. Returns the new character, or `None` if cancelled.
+pub fn create_character(rules: &Rules) -> Result<Option<Character>, io::Error> {
+    let stdout = io::stdout().into_raw_mode()?;
+    let stdout = AlternateScreen::from(stdout);
+    let backend = TermionBackend::new(stdout);
+    let mut terminal = Terminal::new(backend)?;
+    terminal.hide_cursor()?;
+    terminal.clear()?;
+
+    let mut wizard = Wizard::new(rules);
+    let mut keys = io::stdin().keys();
+
+    let created = loop {
+        terminal.draw(|mut f| wizard.draw(&mut f))?;
+
+        match keys.next() {
+            Some(key) => match wizard.handle_key(key?) {
+                Outcome::Continue => {},
+                Outcome::Cancel => break None,
+                Outcome::Done => break Some(wizard.choices().build())
+            },
+            None => break None
+        }
+    };
+
+    terminal.show_cursor()?;
+    Ok(created)
+}